@@ -0,0 +1,29 @@
+//! Derive macros for [actix-web-validation](https://docs.rs/actix-web-validation).
+//!
+//! The macros are re-exported by `actix-web-validation` when the `derive` feature flag is enabled
+//! and should not be used from this crate directly.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod attrs;
+mod rules;
+mod sanitize;
+
+/// Derives `actix_web_validation::rules::ValidationRules` from validator and garde attributes
+#[proc_macro_derive(ValidationRules)]
+pub fn derive_validation_rules(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    rules::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `actix_web_validation::sanitize::Sanitize` from `#[sanitizer(...)]` field attributes
+#[proc_macro_derive(Sanitize, attributes(sanitizer))]
+pub fn derive_sanitize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    sanitize::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
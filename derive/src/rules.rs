@@ -0,0 +1,363 @@
+//! `#[derive(ValidationRules)]`
+
+use crate::attrs::{self, SerdeContainer, SerdeField};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{
+    parenthesized, Attribute, Data, DeriveInput, Expr, Fields, GenericArgument, PathArguments, Type,
+};
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "ValidationRules can only be derived for structs with named fields",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "ValidationRules can only be derived for structs with named fields",
+        ));
+    };
+
+    let container = SerdeContainer::from_attrs(&input.attrs)?;
+
+    let mut field_rules = Vec::new();
+    for field in &fields.named {
+        let serde = SerdeField::from_attrs(&field.attrs)?;
+        if serde.skip {
+            continue;
+        }
+
+        let ident = field.ident.as_ref().expect("named field");
+        let name = attrs::unraw(ident);
+        let serialized_name = match (&serde.rename, container.rename_all) {
+            (Some(rename), _) => rename.clone(),
+            (None, Some(rule)) => rule.apply(&name),
+            (None, None) => name.clone(),
+        };
+
+        let parsed = FieldAttrs::from_attrs(&field.attrs)?;
+        let (field_type, optional) = field_type(&field.ty, parsed.nested);
+        let required = parsed.required || !(optional || serde.default || container.default);
+        let rules = &parsed.rules;
+
+        field_rules.push(quote! {
+            ::actix_web_validation::rules::FieldRules {
+                name: #name,
+                serialized_name: #serialized_name,
+                field_type: #field_type,
+                required: #required,
+                nullable: #optional,
+                rules: ::std::vec![#(#rules),*],
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::actix_web_validation::rules::ValidationRules for #ident #ty_generics #where_clause {
+            fn rules() -> ::std::vec::Vec<::actix_web_validation::rules::FieldRules> {
+                ::std::vec![#(#field_rules),*]
+            }
+        }
+    })
+}
+
+/// The rules read from the `#[validate(...)]` and `#[garde(...)]` attributes of a field
+#[derive(Default)]
+struct FieldAttrs {
+    rules: Vec<TokenStream>,
+    nested: bool,
+    required: bool,
+}
+
+impl FieldAttrs {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut field = Self::default();
+        for attr in attrs
+            .iter()
+            .filter(|a| a.path().is_ident("validate") || a.path().is_ident("garde"))
+        {
+            let garde = attr.path().is_ident("garde");
+            attr.parse_nested_meta(|meta| field.parse_rule(&meta, garde))?;
+        }
+        Ok(field)
+    }
+
+    fn parse_rule(&mut self, meta: &ParseNestedMeta, garde: bool) -> syn::Result<()> {
+        let Some(rule) = meta.path.get_ident().map(|i| i.to_string()) else {
+            return attrs::skip(meta);
+        };
+
+        match rule.as_str() {
+            "length" => {
+                let (mut min, mut max, mut equal) = (None, None, None);
+                // validator counts characters, garde counts bytes unless a mode flag is given
+                let mut mode = if garde { "Bytes" } else { "Chars" };
+                parse_args_and_flags(
+                    meta,
+                    |key, value| match key {
+                        "min" => min = Some(value),
+                        "max" => max = Some(value),
+                        "equal" => equal = Some(value),
+                        _ => {}
+                    },
+                    |flag| match flag {
+                        "chars" => mode = "Chars",
+                        "bytes" | "simple" => mode = "Bytes",
+                        "graphemes" => mode = "Graphemes",
+                        "utf16" => mode = "Utf16",
+                        _ => {}
+                    },
+                )?;
+                let (min, max, equal) = (cast(min, "u64"), cast(max, "u64"), cast(equal, "u64"));
+                let mode = syn::Ident::new(mode, proc_macro2::Span::call_site());
+                self.rules.push(quote! {
+                    ::actix_web_validation::rules::Rule::Length {
+                        min: #min,
+                        max: #max,
+                        equal: #equal,
+                        mode: ::actix_web_validation::rules::LengthMode::#mode,
+                    }
+                });
+            }
+            "range" => {
+                let (mut min, mut max, mut exclusive_min, mut exclusive_max) =
+                    (None, None, None, None);
+                parse_args(meta, |key, value| match key {
+                    "min" => min = Some(value),
+                    "max" => max = Some(value),
+                    "exclusive_min" => exclusive_min = Some(value),
+                    "exclusive_max" => exclusive_max = Some(value),
+                    "equal" => {
+                        min = Some(value.clone());
+                        max = Some(value);
+                    }
+                    _ => {}
+                })?;
+                let (min, max) = (cast(min, "f64"), cast(max, "f64"));
+                let (exclusive_min, exclusive_max) =
+                    (cast(exclusive_min, "f64"), cast(exclusive_max, "f64"));
+                self.rules.push(quote! {
+                    ::actix_web_validation::rules::Rule::Range {
+                        min: #min,
+                        max: #max,
+                        exclusive_min: #exclusive_min,
+                        exclusive_max: #exclusive_max,
+                    }
+                });
+            }
+            // validator: `regex(path = *RE)`, garde: `pattern("...")` or `pattern(RE)`
+            "regex" | "pattern" => {
+                if let Some(pattern) = parse_arg(meta, "path")? {
+                    let pattern = match pattern {
+                        Expr::Lit(lit) => quote!(::std::string::String::from(#lit)),
+                        expr => quote!(::std::string::ToString::to_string((#expr).as_str())),
+                    };
+                    self.rules.push(quote! {
+                        ::actix_web_validation::rules::Rule::Pattern(#pattern)
+                    });
+                }
+            }
+            // validator: `contains(pattern = "...")`, garde: `contains("...")`
+            "contains" => {
+                if let Some(needle) = parse_arg(meta, "pattern")? {
+                    self.rules.push(quote! {
+                        ::actix_web_validation::rules::Rule::Contains(::std::string::ToString::to_string(&(#needle)))
+                    });
+                }
+            }
+            "email" => {
+                attrs::skip(meta)?;
+                self.rules
+                    .push(quote!(::actix_web_validation::rules::Rule::Email));
+            }
+            "url" => {
+                attrs::skip(meta)?;
+                self.rules
+                    .push(quote!(::actix_web_validation::rules::Rule::Url));
+            }
+            "required" => {
+                attrs::skip(meta)?;
+                self.required = true;
+                self.rules
+                    .push(quote!(::actix_web_validation::rules::Rule::Required));
+            }
+            // validator: `nested`, garde: `dive`
+            "nested" | "dive" => {
+                attrs::skip(meta)?;
+                self.nested = true;
+            }
+            _ => attrs::skip(meta)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses `rule(key = value, ...)`, calling `f` for each key/value pair.
+/// Flags without a value (eg. garde's `length(chars, min = 1)`) are ignored.
+fn parse_args(meta: &ParseNestedMeta, f: impl FnMut(&str, Expr)) -> syn::Result<()> {
+    parse_args_and_flags(meta, f, |_| {})
+}
+
+/// Parses `rule(flag, key = value, ...)`, calling `f` for each key/value pair and `flag` for
+/// each flag without a value
+fn parse_args_and_flags(
+    meta: &ParseNestedMeta,
+    mut f: impl FnMut(&str, Expr),
+    mut flag: impl FnMut(&str),
+) -> syn::Result<()> {
+    if !meta.input.peek(syn::token::Paren) {
+        return attrs::skip(meta);
+    }
+    meta.parse_nested_meta(|nested| {
+        match nested.path.get_ident() {
+            Some(key) if nested.input.peek(syn::Token![=]) => {
+                let value = nested.value()?.parse::<Expr>()?;
+                f(&key.to_string(), value);
+            }
+            Some(key) if nested.input.is_empty() || nested.input.peek(syn::Token![,]) => {
+                flag(&key.to_string())
+            }
+            _ => attrs::skip(&nested)?,
+        }
+        Ok(())
+    })
+}
+
+/// Parses the main argument of a rule, either as a positional argument (garde) or as `key = value` (validator)
+fn parse_arg(meta: &ParseNestedMeta, key: &str) -> syn::Result<Option<Expr>> {
+    if !meta.input.peek(syn::token::Paren) {
+        attrs::skip(meta)?;
+        return Ok(None);
+    }
+
+    let fork = meta.input.fork();
+    let content;
+    parenthesized!(content in fork);
+    let is_key_value = content.peek(syn::Ident) && content.peek2(syn::Token![=]);
+
+    if !is_key_value {
+        let content;
+        parenthesized!(content in meta.input);
+        let expr = content.parse::<Expr>()?;
+        content.parse::<TokenStream>()?;
+        return Ok(Some(expr));
+    }
+
+    let mut value = None;
+    parse_args(meta, |k, v| {
+        if k == key {
+            value = Some(v);
+        }
+    })?;
+    Ok(value)
+}
+
+fn cast(value: Option<Expr>, ty: &str) -> TokenStream {
+    let ty = syn::Ident::new(ty, proc_macro2::Span::call_site());
+    match value {
+        Some(expr) => quote!(::std::option::Option::Some((#expr) as #ty)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+/// Returns the [`FieldType`] of a Rust type, and whether the field is optional
+fn field_type(ty: &Type, nested: bool) -> (TokenStream, bool) {
+    let field_type = |ty: &Type| field_type(ty, nested).0;
+
+    match ty {
+        Type::Reference(r) => (field_type(&r.elem), false),
+        Type::Paren(p) => (field_type(&p.elem), false),
+        Type::Group(g) => (field_type(&g.elem), false),
+        Type::Slice(s) => {
+            let inner = field_type(&s.elem);
+            (
+                quote!(::actix_web_validation::rules::FieldType::Array(::std::boxed::Box::new(#inner))),
+                false,
+            )
+        }
+        Type::Array(a) => {
+            let inner = field_type(&a.elem);
+            (
+                quote!(::actix_web_validation::rules::FieldType::Array(::std::boxed::Box::new(#inner))),
+                false,
+            )
+        }
+        Type::Path(p) if p.qself.is_none() => {
+            let segment = p.path.segments.last().expect("type path has a segment");
+            let args = type_args(&segment.arguments);
+            let kind = segment.ident.to_string();
+
+            match (kind.as_str(), args.as_slice()) {
+                ("Option", [inner]) => (field_type(inner), true),
+                ("Box" | "Arc" | "Rc" | "Cow" | "Redacted", [inner]) => (field_type(inner), false),
+                ("String" | "str" | "char", _) => (
+                    quote!(::actix_web_validation::rules::FieldType::String),
+                    false,
+                ),
+                (
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+                    | "i128" | "isize",
+                    _,
+                ) => (
+                    quote!(::actix_web_validation::rules::FieldType::Integer),
+                    false,
+                ),
+                ("f32" | "f64", _) => (
+                    quote!(::actix_web_validation::rules::FieldType::Number),
+                    false,
+                ),
+                ("bool", _) => (
+                    quote!(::actix_web_validation::rules::FieldType::Boolean),
+                    false,
+                ),
+                (
+                    "Vec" | "VecDeque" | "LinkedList" | "HashSet" | "BTreeSet" | "IndexSet",
+                    [inner],
+                ) => {
+                    let inner = field_type(inner);
+                    (
+                        quote!(::actix_web_validation::rules::FieldType::Array(::std::boxed::Box::new(#inner))),
+                        false,
+                    )
+                }
+                ("HashMap" | "BTreeMap" | "IndexMap", [_, value]) => {
+                    let value = field_type(value);
+                    (
+                        quote!(::actix_web_validation::rules::FieldType::Map(::std::boxed::Box::new(#value))),
+                        false,
+                    )
+                }
+                _ if nested => (
+                    quote!(::actix_web_validation::rules::FieldType::Object(
+                        ::actix_web_validation::rules::ObjectRules::of::<#ty>()
+                    )),
+                    false,
+                ),
+                _ => (quote!(::actix_web_validation::rules::FieldType::Any), false),
+            }
+        }
+        _ => (quote!(::actix_web_validation::rules::FieldType::Any), false),
+    }
+}
+
+fn type_args(arguments: &PathArguments) -> Vec<&Type> {
+    match arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,124 @@
+//! `#[derive(Sanitize)]`
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, GenericArgument, Index, Member, PathArguments, Type};
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Sanitize can only be derived for structs",
+        ));
+    };
+
+    let mut statements = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(index)),
+        };
+        let rules = FieldRules::from_attrs(&field.attrs)?;
+
+        if rules.trim {
+            statements.push(quote!(::actix_web_validation::sanitize::trim(&mut self.#member);));
+        }
+        if rules.lowercase {
+            statements
+                .push(quote!(::actix_web_validation::sanitize::lowercase(&mut self.#member);));
+        }
+        if rules.uppercase {
+            statements
+                .push(quote!(::actix_web_validation::sanitize::uppercase(&mut self.#member);));
+        }
+        if rules.empty_as_none {
+            if !is_option_string(&field.ty) {
+                let name = match &member {
+                    Member::Named(ident) => ident.to_string(),
+                    Member::Unnamed(index) => index.index.to_string(),
+                };
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    format!(
+                        "`empty_as_none` requires an `Option<String>` field, `{name}` is not one"
+                    ),
+                ));
+            }
+            statements
+                .push(quote!(::actix_web_validation::sanitize::empty_as_none(&mut self.#member);));
+        }
+        if rules.nested {
+            statements.push(
+                quote!(::actix_web_validation::sanitize::Sanitize::sanitize(&mut self.#member);),
+            );
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::actix_web_validation::sanitize::Sanitize for #ident #ty_generics #where_clause {
+            fn sanitize(&mut self) {
+                #(#statements)*
+            }
+        }
+    })
+}
+
+/// Returns true if `ty` is spelled `Option<String>`, the only type `empty_as_none` applies to
+fn is_option_string(ty: &Type) -> bool {
+    let last_ident = |ty: &Type| match ty {
+        Type::Path(p) if p.qself.is_none() => p.path.segments.last().cloned(),
+        _ => None,
+    };
+
+    let Some(option) = last_ident(ty).filter(|s| s.ident == "Option") else {
+        return false;
+    };
+    let PathArguments::AngleBracketed(args) = &option.arguments else {
+        return false;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => {
+            last_ident(inner).is_some_and(|s| s.ident == "String")
+        }
+        _ => false,
+    }
+}
+
+/// The rules read from the `#[sanitizer(...)]` attributes of a field
+#[derive(Default)]
+struct FieldRules {
+    trim: bool,
+    lowercase: bool,
+    uppercase: bool,
+    empty_as_none: bool,
+    nested: bool,
+}
+
+impl FieldRules {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut rules = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("sanitizer")) {
+            attr.parse_nested_meta(|meta| {
+                let rule = if meta.path.is_ident("trim") {
+                    &mut rules.trim
+                } else if meta.path.is_ident("lowercase") {
+                    &mut rules.lowercase
+                } else if meta.path.is_ident("uppercase") {
+                    &mut rules.uppercase
+                } else if meta.path.is_ident("empty_as_none") {
+                    &mut rules.empty_as_none
+                } else if meta.path.is_ident("nested") {
+                    &mut rules.nested
+                } else {
+                    return Err(meta.error("unknown sanitizer rule"));
+                };
+                *rule = true;
+                Ok(())
+            })?;
+        }
+        Ok(rules)
+    }
+}
@@ -52,11 +52,7 @@ impl ResponseError for CustomErrorResponse {
 fn error_handler(errors: ::validator::ValidationErrors, _: &HttpRequest) -> actix_web::Error {
     CustomErrorResponse {
         custom_message: "My custom message".to_string(),
-        errors: errors
-            .errors()
-            .iter()
-            .map(|(err, _)| err.to_string())
-            .collect(),
+        errors: errors.errors().keys().map(|err| err.to_string()).collect(),
     }
     .into()
 }
@@ -0,0 +1,236 @@
+//! Offloading expensive validation to the blocking thread pool.
+//!
+//! Validation runs on the async worker that extracted the request, so CPU heavy rules (large
+//! collections, regexes, deeply nested documents) stall every other request on that worker.
+//! Types registered with [`OffloadValidationExt::offload_validation`] are validated with
+//! [`actix_web::rt::task::spawn_blocking`] instead when the request matches the [`OffloadPolicy`]:
+//!
+//! ```ignore
+//! use actix_web_validation::blocking::{OffloadPolicy, OffloadValidationExt};
+//!
+//! App::new()
+//!     // always validate documents on the blocking thread pool
+//!     .offload_validation::<Validated<Json<Document>>>(OffloadPolicy::always())
+//!     // only validate uploads bigger than 64KiB on the blocking thread pool
+//!     .offload_validation::<Validated<Json<Upload>>>(OffloadPolicy::min_payload_size(64 * 1024))
+//! ```
+//!
+//! The registered type must wrap the exact extractor used by the handler (eg.
+//! `ValidatedGroup<Json<Document>, Create>` for grouped validation), and its extractor must be
+//! `Send`. A registration for `Validated<T>` also applies to `ValidationResult<T>`,
+//! `OptionalValidated<T>` and `ValidatedInto<T, _>`. Offloading is not supported by the
+//! `jsonschema` backend.
+//!
+//! Offloaded validation can be bounded with a [`ValidationTimeout`](crate::timeout::ValidationTimeout),
+//! types with a timeout are always offloaded.
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use actix_web::dev::{ServiceFactory, ServiceRequest};
+use actix_web::App;
+
+use crate::group::ValidationGroup;
+
+/// A `Validated` extractor whose validation can run on the blocking thread pool.
+/// Implemented by the `Validated` type of every backend.
+pub trait BlockingValidate {
+    /// The wrapped extractor
+    type Extractor;
+    /// The validation group marker, see [`crate::group`]
+    type Group: 'static;
+    /// The errors reported by the backend
+    type Report;
+
+    /// Validates `value` with the rules of `group`
+    fn validate(value: &Self::Extractor, group: ValidationGroup) -> Result<(), Self::Report>;
+}
+
+/// When validation of a registered type is moved to the blocking thread pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OffloadPolicy {
+    min_payload_size: u64,
+}
+
+impl OffloadPolicy {
+    /// Always validate on the blocking thread pool
+    pub fn always() -> Self {
+        Self::min_payload_size(0)
+    }
+
+    /// Validate on the blocking thread pool when the `Content-Length` of the request is at least
+    /// `bytes`. Requests without a `Content-Length` (eg. chunked bodies) are always offloaded.
+    pub fn min_payload_size(bytes: u64) -> Self {
+        Self {
+            min_payload_size: bytes,
+        }
+    }
+
+    #[cfg_attr(
+        not(any(feature = "validator", feature = "garde", feature = "custom")),
+        allow(dead_code)
+    )]
+    fn applies_to(&self, req: &actix_web::HttpRequest) -> bool {
+        if self.min_payload_size == 0 {
+            return true;
+        }
+        req.headers()
+            .get(actix_web::http::header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok()?.parse::<u64>().ok())
+            .map_or(true, |len| len >= self.min_payload_size)
+    }
+}
+
+/// Validation running on the blocking thread pool, resolving to the extracted value and the
+/// validation result
+pub(crate) type BlockingValidation<T, R> =
+    Pin<Box<dyn Future<Output = Result<(T, Result<(), R>), actix_web::Error>>>>;
+
+/// Starts the blocking validation of an extractor `E` reporting errors as `R`
+pub(crate) type Spawn<E, R> = fn(E, ValidationGroup) -> BlockingValidation<E, R>;
+
+/// The offload policy registered for the extractor `E` validated with the group `G`, reporting
+/// errors as `R`
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+struct Offload<E, G, R> {
+    policy: OffloadPolicy,
+    spawn: Spawn<E, R>,
+    _group: PhantomData<fn() -> G>,
+}
+
+fn offload_with<V>(policy: OffloadPolicy) -> Offload<V::Extractor, V::Group, V::Report>
+where
+    V: BlockingValidate,
+    V::Extractor: Send + 'static,
+    V::Report: Send + 'static,
+{
+    Offload {
+        policy,
+        spawn: spawn::<V>,
+        _group: PhantomData,
+    }
+}
+
+pub(crate) fn spawn<V>(
+    value: V::Extractor,
+    group: ValidationGroup,
+) -> BlockingValidation<V::Extractor, V::Report>
+where
+    V: BlockingValidate,
+    V::Extractor: Send + 'static,
+    V::Report: Send + 'static,
+{
+    let handle = actix_web::rt::task::spawn_blocking(move || {
+        let result = V::validate(&value, group);
+        (value, result)
+    });
+    Box::pin(async move {
+        handle
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)
+    })
+}
+
+/// Moves validation of `value` to the blocking thread pool if an [`Offload`] applying to the
+/// request or a timeout was registered for the extractor `E` and the group `G`. Gives `value`
+/// back otherwise.
+#[cfg(any(feature = "validator", feature = "garde", feature = "custom"))]
+pub(crate) fn offload<E, G, R>(
+    req: &actix_web::HttpRequest,
+    value: E,
+    group: ValidationGroup,
+) -> Result<BlockingValidation<E, R>, E>
+where
+    E: std::ops::Deref + 'static,
+    G: 'static,
+    R: 'static,
+{
+    if let Some(timeout) = crate::timeout::timeout::<E, G, R>(req) {
+        return Ok(crate::timeout::with_timeout(
+            req,
+            std::any::type_name::<E::Target>(),
+            timeout.timeout,
+            (timeout.spawn)(value, group),
+        ));
+    }
+
+    match req.app_data::<Offload<E, G, R>>() {
+        Some(offload) if offload.policy.applies_to(req) => Ok((offload.spawn)(value, group)),
+        _ => Err(value),
+    }
+}
+
+pub trait OffloadValidationExt {
+    /// Validates extracted values of the `Validated` type `V` on the blocking thread pool when
+    /// `policy` applies to the request
+    fn offload_validation<V>(self, policy: OffloadPolicy) -> Self
+    where
+        V: BlockingValidate + 'static,
+        V::Extractor: Send + 'static,
+        V::Report: Send + 'static;
+}
+
+impl<A> OffloadValidationExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn offload_validation<V>(self, policy: OffloadPolicy) -> Self
+    where
+        V: BlockingValidate + 'static,
+        V::Extractor: Send + 'static,
+        V::Report: Send + 'static,
+    {
+        self.app_data(offload_with::<V>(policy))
+    }
+}
+
+impl OffloadValidationExt for &mut actix_web::web::ServiceConfig {
+    fn offload_validation<V>(self, policy: OffloadPolicy) -> Self
+    where
+        V: BlockingValidate + 'static,
+        V::Extractor: Send + 'static,
+        V::Report: Send + 'static,
+    {
+        self.app_data(offload_with::<V>(policy))
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl OffloadValidationExt for crate::test_util::ValidationProbe {
+    fn offload_validation<V>(self, policy: OffloadPolicy) -> Self
+    where
+        V: BlockingValidate + 'static,
+        V::Extractor: Send + 'static,
+        V::Report: Send + 'static,
+    {
+        self.app_data(offload_with::<V>(policy))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn should_apply_policy_by_payload_size() {
+        let small = TestRequest::default()
+            .insert_header(("content-length", "10"))
+            .to_http_request();
+        let large = TestRequest::default()
+            .insert_header(("content-length", "1024"))
+            .to_http_request();
+        let unknown = TestRequest::default().to_http_request();
+
+        let policy = OffloadPolicy::min_payload_size(512);
+        assert!(!policy.applies_to(&small));
+        assert!(policy.applies_to(&large));
+        assert!(policy.applies_to(&unknown));
+
+        assert!(OffloadPolicy::always().applies_to(&small));
+    }
+}
@@ -0,0 +1,47 @@
+//! Recording of request bodies read by inner extractors
+
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use actix_web::dev::Payload;
+use actix_web::error::PayloadError;
+use actix_web::web::{Bytes, BytesMut};
+use futures_core::Stream;
+
+/// Replaces `payload` with a stream keeping a copy of the body read from it
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+pub(crate) fn record(payload: &mut Payload) -> Rc<RefCell<BytesMut>> {
+    let body = Rc::new(RefCell::new(BytesMut::new()));
+    let recording = Recording {
+        payload: payload.take(),
+        body: body.clone(),
+    };
+    *payload = Payload::Stream {
+        payload: Box::pin(recording),
+    };
+    body
+}
+
+/// A payload stream keeping a copy of the chunks read from it
+struct Recording {
+    payload: Payload,
+    body: Rc<RefCell<BytesMut>>,
+}
+
+impl Stream for Recording {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let chunk = Pin::new(&mut this.payload).poll_next(cx);
+        if let Poll::Ready(Some(Ok(chunk))) = &chunk {
+            this.body.borrow_mut().extend_from_slice(chunk);
+        }
+        chunk
+    }
+}
@@ -4,15 +4,24 @@
 //! For usage examples, see the documentation for [`Validated`]
 //!
 
-use crate::validated_definition;
+use crate::blocking::{offload, BlockingValidate, BlockingValidation};
+use crate::group::{All, ByMethod, Create, Group, Update, ValidationGroup};
+use crate::limit;
+use crate::optional::{Peek, Presence};
+use crate::path::{FieldPath, PathSyntax};
+use crate::redaction::{RedactionPolicy, REDACTED};
+use crate::sanitize::sanitize;
+use crate::{validated_definition, validated_into_definition, IntoInner};
 use actix_web::dev::{ServiceFactory, ServiceRequest};
 use actix_web::http::StatusCode;
 use actix_web::FromRequest;
 use actix_web::{App, HttpRequest, HttpResponse, ResponseError};
+use pin_project_lite::pin_project;
 use std::fmt::Display;
 use std::future::Future;
+use std::marker::PhantomData;
 use std::sync::Arc;
-use std::{fmt::Debug, ops::Deref, pin::Pin, task::Poll};
+use std::{fmt::Debug, ops::Deref, task::Poll};
 use thiserror::Error;
 
 /// A trait that can be implemented to provide validation logic.
@@ -24,6 +33,76 @@ pub trait Validate {
 #[derive(Debug)]
 pub struct ValidationError {
     message: String,
+    field: Option<String>,
+    path: Option<FieldPath>,
+    code: Option<String>,
+    patch_path: Option<String>,
+    #[cfg(feature = "spans")]
+    span: Option<crate::spans::Span>,
+}
+
+impl ValidationError {
+    /// Create a new validation error with a message
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            field: None,
+            path: None,
+            code: None,
+            patch_path: None,
+            #[cfg(feature = "spans")]
+            span: None,
+        }
+    }
+
+    /// Set the path of the field that failed validation (eg. `address.city`)
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        let field = field.into();
+        self.path = Some(FieldPath::parse(&field));
+        self.field = Some(field);
+        self
+    }
+
+    /// Set the structured path of the field that failed validation, for paths with map keys
+    /// (eg. `FieldPath::new().field("labels").key("en-US")`)
+    pub fn with_path(mut self, path: FieldPath) -> Self {
+        self.field = Some(path.to_string());
+        self.path = Some(path);
+        self
+    }
+
+    /// Set a machine readable code for the failed rule (eg. `length`)
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn path(&self) -> Option<&FieldPath> {
+        self.path.as_ref()
+    }
+
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// The JSON Pointer of the patch path that caused the error, set by `ValidatedPatch`
+    pub fn patch_path(&self) -> Option<&str> {
+        self.patch_path.as_deref()
+    }
+
+    /// The location of the invalid value in the request body, see [`crate::spans`]
+    #[cfg(feature = "spans")]
+    pub fn span(&self) -> Option<crate::spans::Span> {
+        self.span
+    }
 }
 
 impl Display for ValidationError {
@@ -32,6 +111,12 @@ impl Display for ValidationError {
     }
 }
 
+impl From<ValidationError> for Vec<ValidationError> {
+    fn from(value: ValidationError) -> Self {
+        vec![value]
+    }
+}
+
 /// A validated extactor.
 ///
 /// This type will run any validations on the inner extractors.
@@ -60,285 +145,2031 @@ impl Display for ValidationError {
 /// ```
 pub struct Validated<T>(pub T);
 
-validated_definition!();
-
-pub struct ValidatedFut<T: FromRequest> {
-    req: actix_web::HttpRequest,
-    fut: <T as FromRequest>::Future,
-    error_handler: Option<ValidationErrHandler>,
-}
-impl<T> Future for ValidatedFut<T>
-where
-    T: FromRequest + Debug + Deref,
-    T::Future: Unpin,
-    T::Target: Validate,
-{
-    type Output = Result<Validated<T>, actix_web::Error>;
+validated_definition!(Validated);
 
-    fn poll(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Self::Output> {
-        let this = self.get_mut();
-        let Poll::Ready(res) = Pin::new(&mut this.fut).poll(cx) else {
-            return std::task::Poll::Pending;
-        };
+/// A validated extractor applying the rules of the validation group `G`.
+///
+/// Extracts and validates like [`Validated`], `G` selects the rules that are applied, see
+/// [`crate::group`] and [`ValidateGroup`].
+///
+/// ```ignore
+/// use actix_web_validation::group::{Create, Update};
+///
+/// async fn create(user: ValidatedGroup<Json<User>, Create>) -> impl Responder { .. }
+/// async fn update(user: ValidatedGroup<Json<User>, Update>) -> impl Responder { .. }
+/// ```
+pub struct ValidatedGroup<T, G>(pub T, PhantomData<fn() -> G>);
 
-        let res = match res {
-            Ok(data) => {
-                if let Err(e) = data.validate() {
-                    if let Some(error_handler) = &this.error_handler {
-                        Err((*error_handler)(e, &this.req))
-                    } else {
-                        let err: Error = e.into();
-                        Err(err.into())
-                    }
-                } else {
-                    Ok(Validated(data))
-                }
-            }
-            Err(e) => Err(e.into()),
-        };
+validated_definition!(ValidatedGroup<G>);
 
-        Poll::Ready(res)
+pin_project! {
+    pub struct ValidatedFut<T: FromRequest, G = All, O = Validated<T>> {
+        req: actix_web::HttpRequest,
+        #[pin]
+        fut: <T as FromRequest>::Future,
+        error_handler: Option<ValidationErrHandler>,
+        finish: Finish<T, O>,
+        validating: Option<(BlockingValidation<T, Vec<ValidationError>>, std::time::Instant)>,
+        // Checks the recorded body for unknown fields, see `crate::strict`
+        unknown_fields: Option<Box<dyn FnOnce() -> Vec<FieldPath>>>,
+        spans: Option<AddSpans>,
+        _group: PhantomData<fn() -> G>,
     }
 }
 
-impl<T> FromRequest for Validated<T>
+/// Turns the extracted value and its validation result into the output of [`ValidatedFut`]
+type Finish<T, O> = fn(
+    &HttpRequest,
+    Option<&ValidationErrHandler>,
+    T,
+    Result<(), Vec<ValidationError>>,
+) -> Result<O, actix_web::Error>;
+
+impl<T, G, O> ValidatedFut<T, G, O>
 where
-    T: FromRequest + Debug + Deref,
-    T::Future: Unpin,
-    T::Target: Validate,
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
 {
-    type Error = actix_web::Error;
-
-    type Future = ValidatedFut<T>;
-
-    fn from_request(
+    fn new(
         req: &actix_web::HttpRequest,
         payload: &mut actix_web::dev::Payload,
-    ) -> Self::Future {
+        finish: Finish<T, O>,
+    ) -> Self {
         let error_handler = req
             .app_data::<ValidationErrorHandler>()
             .map(|h| h.handler.clone());
 
+        #[cfg(feature = "strict")]
+        let unknown_fields = crate::strict::record::<T::Target>(req, payload);
+        #[cfg(not(feature = "strict"))]
+        let unknown_fields = None;
+
+        #[cfg(feature = "spans")]
+        let spans = crate::spans::record::<T::Target>(req, payload)
+            .map(|spans| -> AddSpans { Box::new(move |errors| with_spans(errors, &spans())) });
+        #[cfg(not(feature = "spans"))]
+        let spans = None;
+
         let fut = T::from_request(req, payload);
 
         ValidatedFut {
             fut,
             error_handler,
+            finish,
             req: req.clone(),
+            validating: None,
+            unknown_fields,
+            spans,
+            _group: PhantomData,
         }
     }
 }
 
-#[derive(Error, Debug)]
-struct Error {
-    errors: Vec<ValidationError>,
-}
+impl<T, G, O> Future for ValidatedFut<T, G, O>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    G: Group + GroupRules<T::Target>,
+{
+    type Output = Result<O, actix_web::Error>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let mut this = self.project();
 
-impl From<Vec<ValidationError>> for Error {
-    fn from(value: Vec<ValidationError>) -> Self {
-        Self { errors: value }
+        loop {
+            if let Some((validating, started)) = this.validating {
+                let Poll::Ready(res) = validating.as_mut().poll(cx) else {
+                    return std::task::Poll::Pending;
+                };
+                let started = *started;
+                *this.validating = None;
+
+                return Poll::Ready(res.and_then(|(data, result)| {
+                    let result = complete::<T::Target>(
+                        this.req,
+                        started,
+                        result,
+                        this.unknown_fields.take(),
+                        this.spans.take(),
+                    );
+                    (this.finish)(this.req, this.error_handler.as_ref(), data, result)
+                }));
+            }
+
+            let Poll::Ready(res) = this.fut.as_mut().poll(cx) else {
+                return std::task::Poll::Pending;
+            };
+
+            let mut data = match res {
+                Ok(data) => data,
+                Err(e) => return Poll::Ready(Err(e.into())),
+            };
+            sanitize(this.req, &mut data);
+
+            let group = G::group(this.req);
+            let started = std::time::Instant::now();
+
+            match offload::<T, G, Vec<ValidationError>>(this.req, data, group) {
+                // Poll the blocking validation right away, it wakes the task once done
+                Ok(validating) => *this.validating = Some((validating, started)),
+                Err(data) => {
+                    let result = complete::<T::Target>(
+                        this.req,
+                        started,
+                        G::validate(&data, group),
+                        this.unknown_fields.take(),
+                        this.spans.take(),
+                    );
+                    return Poll::Ready((this.finish)(
+                        this.req,
+                        this.error_handler.as_ref(),
+                        data,
+                        result,
+                    ));
+                }
+            }
+        }
     }
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.errors
-                .iter()
-                .map(|e| e.message.as_ref())
-                .collect::<Vec<_>>()
-                .join("\n")
-        )
+/// Applies the checks registered for `T` to its validation result
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+fn complete<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    started: std::time::Instant,
+    result: Result<(), Vec<ValidationError>>,
+    unknown_fields: Option<Box<dyn FnOnce() -> Vec<FieldPath>>>,
+    spans: Option<AddSpans>,
+) -> Result<(), Vec<ValidationError>> {
+    let result = with_serialized_names::<T>(req, with_redaction(req, result));
+    let unknown_fields = unknown_fields.map_or_else(Vec::new, |check| check());
+
+    // Unknown fields are named by the client, so they are not recorded by their path
+    #[cfg(feature = "metrics")]
+    {
+        record_metrics::<T>(req, started, &result);
+        crate::metrics::record_unknown_fields(
+            req,
+            std::any::type_name::<T>(),
+            unknown_fields.len(),
+        );
     }
+
+    let result = with_unknown_fields(result, unknown_fields);
+    result.map_err(|errors| match spans {
+        Some(add_spans) => add_spans(errors),
+        None => errors,
+    })
 }
 
-impl ResponseError for Error {
-    fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(StatusCode::BAD_REQUEST).body(format!(
-            "Validation errors in fields:\n{}",
-            &self
-                .errors
-                .iter()
-                .map(|err| { format!("\t{}", err) })
-                .collect::<Vec<_>>()
-                .join("\n")
-        ))
+/// Responds with the validation errors of invalid values, gives valid values back. Used by the
+/// [`Finish`] of [`Validated`] and [`ValidatedGroup`], by `ValidatedPatch` and for WebSocket
+/// messages.
+///
+/// The value error handler registered for the value takes precedence over the error handler.
+fn reject<T>(
+    req: &HttpRequest,
+    error_handler: Option<&ValidationErrHandler>,
+    data: T,
+    result: Result<(), Vec<ValidationError>>,
+) -> Result<T, actix_web::Error>
+where
+    T: Deref,
+    T::Target: 'static,
+{
+    let Err(e) = result else {
+        return Ok(data);
+    };
+
+    match req.app_data::<ValidationValueErrorHandler<T::Target>>() {
+        Some(value_handler) => {
+            let handler = |errors, req: &HttpRequest| (value_handler.handler)(&data, errors, req);
+            Err(respond(req, Some(&handler), e))
+        }
+        None => Err(validation_error(req, error_handler, e)),
     }
 }
 
-pub type ValidationErrHandler =
-    Arc<dyn Fn(Vec<ValidationError>, &HttpRequest) -> actix_web::Error + Send + Sync>;
+/// Adds the source span of each invalid value to its error, see [`crate::spans`]
+type AddSpans = Box<dyn FnOnce(Vec<ValidationError>) -> Vec<ValidationError>>;
 
-struct ValidationErrorHandler {
-    handler: ValidationErrHandler,
+/// Sets the source span of the invalid value of each error
+#[cfg(feature = "spans")]
+fn with_spans(errors: Vec<ValidationError>, spans: &crate::spans::SpanMap) -> Vec<ValidationError> {
+    errors
+        .into_iter()
+        .map(|mut error| {
+            error.span = error.path().and_then(|path| spans.get(path));
+            error
+        })
+        .collect()
 }
 
-/// Extension trait to provide a convenience method for adding custom error handler
-pub trait ValidationErrorHandlerExt {
-    /// Add a custom error handler for garde validated requests
-    fn validation_error_handler(self, handler: ValidationErrHandler) -> Self;
+/// Replaces the message of the errors of fields matched by the redaction policy, as it may
+/// quote the value
+fn with_redaction(
+    req: &HttpRequest,
+    result: Result<(), Vec<ValidationError>>,
+) -> Result<(), Vec<ValidationError>> {
+    let Some(policy) = req.app_data::<RedactionPolicy>() else {
+        return result;
+    };
+
+    result.map_err(|errors| {
+        errors
+            .into_iter()
+            .map(|mut error| {
+                if error
+                    .path
+                    .as_ref()
+                    .is_some_and(|path| policy.is_redacted(path))
+                {
+                    error.message = REDACTED.to_string();
+                }
+                error
+            })
+            .collect()
+    })
 }
 
-impl<T> ValidationErrorHandlerExt for App<T>
+/// Renames the error fields of `T` to their serialized names, see [`crate::field_names`]
+fn with_serialized_names<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    result: Result<(), Vec<ValidationError>>,
+) -> Result<(), Vec<ValidationError>> {
+    let Some(names) = crate::field_names::field_names::<T>(req) else {
+        return result;
+    };
+
+    result.map_err(|errors| {
+        errors
+            .into_iter()
+            .map(|mut error| {
+                if let Some(path) = error.path.take() {
+                    error = error.with_path(names.rename(&path));
+                }
+                error
+            })
+            .collect()
+    })
+}
+
+/// Adds an `unknown_field` error for each of the unknown `fields`
+fn with_unknown_fields(
+    result: Result<(), Vec<ValidationError>>,
+    fields: Vec<FieldPath>,
+) -> Result<(), Vec<ValidationError>> {
+    if fields.is_empty() {
+        return result;
+    }
+
+    let mut errors = result.err().unwrap_or_default();
+    errors.extend(fields.into_iter().map(|field| {
+        ValidationError::new(format!("unknown field `{field}`"))
+            .with_path(field)
+            .with_code("unknown_field")
+    }));
+    Err(errors)
+}
+
+/// Deserializes a JSON text or binary WebSocket frame into `T` and validates it.
+/// Requires the `ws` feature flag
+///
+/// The sanitizer, error limit, value error handler and error handler registered for the
+/// WebSocket upgrade request `req` are applied. Returns `None` for frames without data (eg. ping and close frames).
+///
+/// See the [`ws`](crate::ws) module for an example.
+#[cfg(feature = "ws")]
+pub fn validate_message<T>(
+    req: &HttpRequest,
+    msg: &impl crate::ws::DataFrame,
+) -> Option<Result<T, crate::ws::ErrorFrame>>
 where
-    T: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+    T: serde::de::DeserializeOwned + Validate + 'static,
 {
-    fn validation_error_handler(self, handler: ValidationErrHandler) -> Self {
-        self.app_data(ValidationErrorHandler { handler })
-    }
+    let data = msg.data()?;
+
+    Some(crate::ws::deserialize::<T>(data).and_then(|mut value| {
+        sanitize(req, &mut value);
+
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        let result = with_serialized_names::<T>(req, with_redaction(req, value.validate()));
+
+        #[cfg(feature = "metrics")]
+        record_metrics::<T>(req, started, &result);
+
+        let error_handler = req
+            .app_data::<ValidationErrorHandler>()
+            .map(|h| h.handler.clone());
+        reject(req, error_handler.as_ref(), Box::new(value), result)
+            .map(|value| *value)
+            .map_err(Into::into)
+    }))
 }
 
-impl ValidationErrorHandlerExt for &mut actix_web::web::ServiceConfig {
-    fn validation_error_handler(self, handler: ValidationErrHandler) -> Self {
-        self.app_data(ValidationErrorHandler { handler })
+/// A `PATCH` request body applied to the current value of the resource and validated.
+/// Requires the `patch` feature flag
+///
+/// The resource is loaded with the [`PatchLoader`](crate::patch::PatchLoader) registered for
+/// `T`, see the [`patch`](crate::patch) module. Validation errors carry the JSON
+/// Pointer of the patch path that caused them, see [`ValidationError::patch_path`].
+#[cfg(feature = "patch")]
+pub struct ValidatedPatch<T>(pub T);
+
+#[cfg(feature = "patch")]
+crate::validated_patch_definition!();
+
+#[cfg(feature = "patch")]
+impl<T> FromRequest for ValidatedPatch<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + Validate + 'static,
+{
+    type Error = actix_web::Error;
+
+    type Future = std::pin::Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+        use actix_web::web::Json;
+
+        let req = req.clone();
+        let patched = crate::patch::patched::<T>(&req, payload);
+
+        Box::pin(async move {
+            let patched = patched.await?;
+            let mut value = patched.value;
+            sanitize(&req, &mut value);
+
+            let error_handler = req
+                .app_data::<ValidationErrorHandler>()
+                .map(|h| h.handler.clone());
+            let group = All::group(&req);
+            let started = std::time::Instant::now();
+
+            // Offloading and timeouts registered for `Validated<Json<T>>` apply
+            let (data, result) =
+                match offload::<Json<T>, All, Vec<ValidationError>>(&req, Json(value), group) {
+                    Ok(validating) => validating.await?,
+                    Err(data) => {
+                        let result = <All as GroupRules<T>>::validate(&data, group);
+                        (data, result)
+                    }
+                };
+            let result = complete::<T>(&req, started, result, patched.unknown_fields, None)
+                .map_err(|e| with_patch_paths(e, &patched.patch));
+            reject(&req, error_handler.as_ref(), data, result)
+                .map(|Json(value)| ValidatedPatch(value))
+        })
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use actix_web::web::Bytes;
-    use actix_web::{http::header::ContentType, post, test, web::Json, App, Responder};
-    use serde::{Deserialize, Serialize};
+/// Sets the patch path that caused each error
+#[cfg(feature = "patch")]
+fn with_patch_paths(
+    errors: Vec<ValidationError>,
+    patch: &crate::patch::Patch,
+) -> Vec<ValidationError> {
+    errors
+        .into_iter()
+        .map(|mut error| {
+            error.patch_path = error.path().and_then(|path| patch.path_of(path));
+            error
+        })
+        .collect()
+}
 
-    #[derive(Debug, Deserialize, Serialize)]
-    struct ExamplePayload {
-        name: String,
-    }
+/// The rules applied by a validation group marker of [`ValidatedGroup`]
+pub trait GroupRules<T: ?Sized> {
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), Vec<ValidationError>>;
+}
 
-    impl Validate for ExamplePayload {
-        fn validate(&self) -> Result<(), Vec<ValidationError>> {
-            if self.name.len() > 4 {
-                Ok(())
-            } else {
-                Err(vec![ValidationError {
-                    message: "name not long enough".to_string(),
-                }])
-            }
-        }
-    }
+/// A type with different rules per validation group
+pub trait ValidateGroup {
+    fn validate_group(&self, group: ValidationGroup) -> Result<(), Vec<ValidationError>>;
+}
 
-    #[post("/")]
-    async fn endpoint(v: Validated<Json<ExamplePayload>>) -> impl Responder {
-        assert!(v.name.len() > 4);
-        HttpResponse::Ok().body(())
+impl<T: Validate + ?Sized> GroupRules<T> for All {
+    fn validate(value: &T, _: ValidationGroup) -> Result<(), Vec<ValidationError>> {
+        value.validate()
     }
+}
 
-    #[actix_web::test]
-    async fn should_validate_simple() {
-        let app = test::init_service(App::new().service(endpoint)).await;
+macro_rules! group_rules_impl {
+    ($($group:ident),*) => {
+        $(
+            impl<T: ValidateGroup + ?Sized> GroupRules<T> for $group {
+                fn validate(value: &T, group: ValidationGroup) -> Result<(), Vec<ValidationError>> {
+                    value.validate_group(group)
+                }
+            }
+        )*
+    };
+}
 
-        // Valid request
-        let req = test::TestRequest::post()
-            .uri("/")
-            .insert_header(ContentType::plaintext())
-            .set_json(ExamplePayload {
-                name: "123456".to_string(),
-            })
-            .to_request();
-        let resp = test::call_service(&app, req).await;
-        assert_eq!(resp.status().as_u16(), 200);
+group_rules_impl!(Create, Update, ByMethod);
 
-        // Invalid request
-        let req = test::TestRequest::post()
-            .uri("/")
-            .insert_header(ContentType::plaintext())
-            .set_json(ExamplePayload {
-                name: "1234".to_string(),
-            })
-            .to_request();
-        let resp = test::call_service(&app, req).await;
-        assert_eq!(resp.status().as_u16(), 400);
+impl<T> BlockingValidate for Validated<T>
+where
+    T: Deref,
+    All: GroupRules<T::Target>,
+{
+    type Extractor = T;
+    type Group = All;
+    type Report = Vec<ValidationError>;
+
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), Vec<ValidationError>> {
+        All::validate(value, group)
     }
+}
 
-    #[actix_web::test]
-    async fn should_respond_with_errors_correctly() {
-        let app = test::init_service(App::new().service(endpoint)).await;
+impl<T> FromRequest for Validated<T>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
 
-        // Invalid request
-        let req = test::TestRequest::post()
-            .uri("/")
-            .insert_header(ContentType::plaintext())
-            .set_json(ExamplePayload {
-                name: "1234".to_string(),
-            })
-            .to_request();
-        let result = test::call_and_read_body(&app, req).await;
-        assert_eq!(
-            result,
-            Bytes::from_static(b"Validation errors in fields:\n\tname not long enough")
-        );
-    }
+    type Future = ValidatedFut<T>;
 
-    #[derive(Debug, Serialize, Error)]
-    struct CustomErrorResponse {
-        custom_message: String,
-        errors: Vec<String>,
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedFut::new(req, payload, |req, error_handler, data, result| {
+            reject(req, error_handler, data, result).map(Validated)
+        })
     }
+}
 
-    impl Display for CustomErrorResponse {
-        fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            unimplemented!()
-        }
+impl<T, G> BlockingValidate for ValidatedGroup<T, G>
+where
+    T: Deref,
+    G: GroupRules<T::Target> + 'static,
+{
+    type Extractor = T;
+    type Group = G;
+    type Report = Vec<ValidationError>;
+
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), Vec<ValidationError>> {
+        G::validate(value, group)
     }
+}
 
-    impl ResponseError for CustomErrorResponse {
-        fn status_code(&self) -> actix_web::http::StatusCode {
-            actix_web::http::StatusCode::BAD_REQUEST
-        }
+impl<T, G> FromRequest for ValidatedGroup<T, G>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    G: Group + GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
 
-        fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
-            HttpResponse::build(self.status_code()).body(serde_json::to_string(self).unwrap())
-        }
-    }
+    type Future = ValidatedFut<T, G, Self>;
 
-    fn error_handler(errors: Vec<ValidationError>, _: &HttpRequest) -> actix_web::Error {
-        CustomErrorResponse {
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedFut::new(req, payload, |req, error_handler, data, result| {
+            reject(req, error_handler, data, result).map(|data| ValidatedGroup(data, PhantomData))
+        })
+    }
+}
+
+/// A validated extractor handing validation failures to the handler.
+///
+/// Extracts and validates like [`Validated`], but invalid values are given to the handler as
+/// a [`Rejected`] instead of responding with the validation errors, eg. to render a form again
+/// with the input of the user. Extraction errors (eg. malformed JSON) are still responded with.
+///
+/// ```
+/// use actix_web::{post, web::Form, HttpResponse};
+/// use serde::Deserialize;
+/// use actix_web_validation::custom::{Validate, ValidationError, ValidationResult, Validated};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Signup {
+///     username: String,
+/// }
+///
+/// impl Validate for Signup {
+///     fn validate(&self) -> Result<(), Vec<ValidationError>> {
+///         if self.username.len() < 5 {
+///             return Err(vec![ValidationError::new("too short").with_field("username")]);
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// #[post("/signup")]
+/// async fn signup(result: ValidationResult<Form<Signup>>) -> HttpResponse {
+///     match result.into_result() {
+///         Ok(Validated(Form(signup))) => HttpResponse::Ok().body(signup.username),
+///         Err(rejected) => HttpResponse::Ok().body(format!(
+///             "<input value=\"{}\"> {}",
+///             rejected.value().username,
+///             rejected.errors()[0]
+///         )),
+///     }
+/// }
+/// ```
+pub struct ValidationResult<T>(pub Result<Validated<T>, Rejected<T>>);
+
+impl<T> ValidationResult<T> {
+    pub fn into_result(self) -> Result<Validated<T>, Rejected<T>> {
+        self.0
+    }
+}
+
+impl<T: Debug> Debug for ValidationResult<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ValidationResult").field(&self.0).finish()
+    }
+}
+
+/// An extracted value that failed validation, see [`ValidationResult`]
+pub struct Rejected<T> {
+    value: T,
+    errors: Vec<ValidationError>,
+}
+
+impl<T> Rejected<T> {
+    /// The extracted (and sanitized) value
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    pub fn into_parts(self) -> (T, Vec<ValidationError>) {
+        (self.value, self.errors)
+    }
+}
+
+impl<T: Debug> Debug for Rejected<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rejected")
+            .field("value", &self.value)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+/// Hands invalid values to the handler, the [`Finish`] of [`ValidationResult`]
+fn keep_rejected<T>(
+    _: &HttpRequest,
+    _: Option<&ValidationErrHandler>,
+    value: T,
+    result: Result<(), Vec<ValidationError>>,
+) -> Result<ValidationResult<T>, actix_web::Error> {
+    Ok(ValidationResult(match result {
+        Ok(()) => Ok(Validated(value)),
+        Err(errors) => Err(Rejected { value, errors }),
+    }))
+}
+
+impl<T> FromRequest for ValidationResult<T>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedFut<T, All, Self>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedFut::new(req, payload, keep_rejected)
+    }
+}
+
+/// A validated extractor for optional input.
+///
+/// `None` when the input is absent (eg. a request without a query string for [`Query`](actix_web::web::Query),
+/// see [`Presence`]). Present input is validated like [`Validated`], so invalid input is still
+/// responded with the validation errors instead of being ignored like with `Option<Validated<T>>`.
+///
+/// ```
+/// use actix_web::{get, web::Query};
+/// use serde::Deserialize;
+/// use actix_web_validation::custom::{OptionalValidated, Validate, ValidationError};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct UserFilter {
+///     name: String,
+/// }
+///
+/// impl Validate for UserFilter {
+///     fn validate(&self) -> Result<(), Vec<ValidationError>> {
+///         if self.name.len() < 3 {
+///             return Err(vec![ValidationError::new("too short").with_field("name")]);
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// #[get("/users")]
+/// async fn list_users(filter: OptionalValidated<Query<UserFilter>>) -> String {
+///     match filter.into_inner() {
+///         Some(filter) => format!("Users named {}", filter.name),
+///         None => "All users".to_string(),
+///     }
+/// }
+/// ```
+pub struct OptionalValidated<T>(pub Option<Validated<T>>);
+
+impl<T> OptionalValidated<T> {
+    pub fn into_inner(self) -> Option<Validated<T>> {
+        self.0
+    }
+}
+
+impl<T: Debug> Debug for OptionalValidated<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OptionalValidated").field(&self.0).finish()
+    }
+}
+
+pin_project! {
+    /// Future that extracts and validates optional input for [`OptionalValidated`]
+    pub struct OptionalValidatedFut<T: FromRequest> {
+        req: HttpRequest,
+        peek: Option<Peek>,
+        #[pin]
+        fut: Option<ValidatedFut<T>>,
+    }
+}
+
+impl<T> Future for OptionalValidatedFut<T>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Output = Result<OptionalValidated<T>, actix_web::Error>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let mut this = self.project();
+        if let Some(peek) = this.peek.as_mut() {
+            let (present, mut payload) = std::task::ready!(std::pin::Pin::new(peek).poll(cx));
+            *this.peek = None;
+            if present {
+                let fut = Validated::from_request(this.req, &mut payload);
+                this.fut.set(Some(fut));
+            }
+        }
+        match this.fut.as_pin_mut() {
+            Some(fut) => fut
+                .poll(cx)
+                .map(|res| res.map(|validated| OptionalValidated(Some(validated)))),
+            None => Poll::Ready(Ok(OptionalValidated(None))),
+        }
+    }
+}
+
+impl<T> FromRequest for OptionalValidated<T>
+where
+    T: FromRequest + Deref + Presence + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
+
+    type Future = OptionalValidatedFut<T>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        OptionalValidatedFut {
+            req: req.clone(),
+            peek: Some(Peek::new::<T>(req, payload)),
+            fut: None,
+        }
+    }
+}
+
+/// A validated extractor converting the validated value into a domain type.
+///
+/// The inner extractor is validated like [`Validated`], then its value is converted into `D`
+/// with [`TryFrom`]. Conversion errors go through the same error handler as validation errors.
+///
+/// ```
+/// use actix_web::{post, web::{self, Json}, App};
+/// use serde::Deserialize;
+/// use actix_web_validation::custom::{ValidatedInto, Validate, ValidationError};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct CreateUserDto {
+///     username: String,
+/// }
+///
+/// impl Validate for CreateUserDto {
+///     fn validate(&self) -> Result<(), Vec<ValidationError>> {
+///         Ok(())
+///     }
+/// }
+///
+/// struct CreateUser {
+///     username: String,
+/// }
+///
+/// impl TryFrom<CreateUserDto> for CreateUser {
+///     type Error = ValidationError;
+///
+///     fn try_from(dto: CreateUserDto) -> Result<Self, Self::Error> {
+///         if dto.username == "admin" {
+///             return Err(ValidationError::new("reserved").with_field("username"));
+///         }
+///         Ok(CreateUser { username: dto.username })
+///     }
+/// }
+///
+/// #[post("/")]
+/// async fn index(user: ValidatedInto<Json<CreateUserDto>, CreateUser>) -> String {
+///     format!("Welcome {}!", user.username)
+/// }
+/// ```
+pub struct ValidatedInto<T, D> {
+    inner: D,
+    _extractor: PhantomData<fn() -> T>,
+}
+
+validated_into_definition!();
+
+impl<T, D> Future for ValidatedIntoFut<T, D>
+where
+    T: FromRequest + Deref + IntoInner + 'static,
+    T::Target: Validate + 'static,
+    D: TryFrom<T::Inner>,
+    D::Error: Into<Vec<ValidationError>>,
+{
+    type Output = Result<ValidatedInto<T, D>, actix_web::Error>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let mut this = self.project();
+        let Poll::Ready(res) = this.fut.as_mut().poll(cx) else {
+            return std::task::Poll::Pending;
+        };
+
+        let res = res.and_then(|Validated(data)| {
+            D::try_from(data.into_inner())
+                .map(ValidatedInto::new)
+                .map_err(|e| {
+                    // Conversion errors are post-processed like the errors of `validate()`
+                    let req = &this.fut.req;
+                    let errors = with_redaction(req, Err(e.into()));
+                    let errors = with_serialized_names::<T::Target>(req, errors).unwrap_err();
+                    validation_error(req, this.fut.error_handler.as_ref(), errors)
+                })
+        });
+
+        Poll::Ready(res)
+    }
+}
+
+impl<T, D> FromRequest for ValidatedInto<T, D>
+where
+    T: FromRequest + Deref + IntoInner + 'static,
+    T::Target: Validate + 'static,
+    D: TryFrom<T::Inner>,
+    D::Error: Into<Vec<ValidationError>>,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedIntoFut<T, D>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedIntoFut {
+            fut: Validated::<T>::from_request(req, payload),
+            _domain: PhantomData,
+        }
+    }
+}
+
+/// Converts validation errors into an error response, using the custom error handler if any
+fn validation_error(
+    req: &HttpRequest,
+    error_handler: Option<&ValidationErrHandler>,
+    errors: Vec<ValidationError>,
+) -> actix_web::Error {
+    respond(
+        req,
+        error_handler.map(|handler| handler.as_ref() as &RespondWith),
+        errors,
+    )
+}
+
+/// An error handler borrowing from the caller
+type RespondWith<'a> = dyn Fn(Vec<ValidationError>, &HttpRequest) -> actix_web::Error + 'a;
+
+/// Responds with `errors` after applying the registered error limit
+fn respond(
+    req: &HttpRequest,
+    error_handler: Option<&RespondWith>,
+    mut errors: Vec<ValidationError>,
+) -> actix_web::Error {
+    let max = limit::max_errors(req).unwrap_or(usize::MAX);
+    let truncated = errors.len() > max;
+    if truncated {
+        errors.truncate(max);
+        limit::mark_truncated(req);
+    }
+    if let Some(error_handler) = error_handler {
+        (*error_handler)(errors, req)
+    } else {
+        Error {
+            errors,
+            truncated,
+            syntax: crate::path::syntax(req),
+        }
+        .into()
+    }
+}
+
+/// The error returned when validation fails and no error handler is registered.
+///
+/// Middleware can recognise validation failures with [`actix_web::Error::as_error`] and read
+/// the report, eg. to log or reformat it:
+///
+/// ```ignore
+/// if let Some(error) = res.response().error().and_then(|e| e.as_error::<actix_web_validation::custom::Error>()) {
+///     log::warn!("validation failed: {}", error);
+/// }
+/// ```
+#[derive(Error, Debug)]
+pub struct Error {
+    errors: Vec<ValidationError>,
+    truncated: bool,
+    syntax: PathSyntax,
+}
+
+impl Error {
+    /// The validation errors, truncated to the registered limit
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<ValidationError> {
+        self.errors
+    }
+
+    /// Whether errors were dropped to respect the limit, see [`crate::limit`]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.errors
+                .iter()
+                .map(|e| e.message.as_ref())
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    }
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        limit::error_response(
+            self.status_code(),
+            format!(
+                "Validation errors in fields:\n{}",
+                &self
+                    .errors
+                    .iter()
+                    .map(|err| {
+                        let line = match &err.path {
+                            Some(path) => format!("\t{}: {}", path.render(self.syntax), err),
+                            None => format!("\t{}", err),
+                        };
+                        #[cfg(feature = "spans")]
+                        let line = crate::spans::with_span(line, err);
+                        line
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            self.truncated,
+            "\t",
+        )
+    }
+}
+
+/// Errors without a field or code are labeled with [`ROOT_FIELD`](crate::metrics::ROOT_FIELD) and
+/// [`UNKNOWN_CODE`](crate::metrics::UNKNOWN_CODE)
+#[cfg(feature = "metrics")]
+fn record_metrics<T: ?Sized>(
+    req: &HttpRequest,
+    started: std::time::Instant,
+    result: &Result<(), Vec<ValidationError>>,
+) {
+    let violations = match result {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .iter()
+            .map(|e| (e.path.clone().unwrap_or_default(), e.code.clone()))
+            .collect(),
+    };
+
+    crate::metrics::record(
+        req,
+        std::any::type_name::<T>(),
+        started.elapsed(),
+        violations,
+    );
+}
+
+pub type ValidationErrHandler =
+    Arc<dyn Fn(Vec<ValidationError>, &HttpRequest) -> actix_web::Error + Send + Sync>;
+
+struct ValidationErrorHandler {
+    handler: ValidationErrHandler,
+}
+
+/// Extension trait to provide a convenience method for adding custom error handler
+pub trait ValidationErrorHandlerExt {
+    /// Add a custom error handler for garde validated requests
+    fn validation_error_handler(self, handler: ValidationErrHandler) -> Self;
+}
+
+impl<T> ValidationErrorHandlerExt for App<T>
+where
+    T: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn validation_error_handler(self, handler: ValidationErrHandler) -> Self {
+        self.app_data(ValidationErrorHandler { handler })
+    }
+}
+
+impl ValidationErrorHandlerExt for &mut actix_web::web::ServiceConfig {
+    fn validation_error_handler(self, handler: ValidationErrHandler) -> Self {
+        self.app_data(ValidationErrorHandler { handler })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl ValidationErrorHandlerExt for crate::test_util::ValidationProbe {
+    fn validation_error_handler(self, handler: ValidationErrHandler) -> Self {
+        self.app_data(ValidationErrorHandler { handler })
+    }
+}
+
+/// An error handler receiving the rejected value of type `T` along with its errors
+pub type ValidationValueErrHandler<T> =
+    Arc<dyn Fn(&T, Vec<ValidationError>, &HttpRequest) -> actix_web::Error + Send + Sync>;
+
+struct ValidationValueErrorHandler<T: ?Sized> {
+    handler: ValidationValueErrHandler<T>,
+}
+
+/// Extension trait to provide a convenience method for adding error handlers per validated type
+pub trait ValidationValueErrorHandlerExt {
+    /// Add an error handler for requests whose extracted value of type `T` failed validation.
+    /// Takes precedence over the error handler of [`ValidationErrorHandlerExt`].
+    fn validation_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: ValidationValueErrHandler<T>,
+    ) -> Self;
+}
+
+impl<A> ValidationValueErrorHandlerExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn validation_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: ValidationValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(ValidationValueErrorHandler { handler })
+    }
+}
+
+impl ValidationValueErrorHandlerExt for &mut actix_web::web::ServiceConfig {
+    fn validation_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: ValidationValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(ValidationValueErrorHandler { handler })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl ValidationValueErrorHandlerExt for crate::test_util::ValidationProbe {
+    fn validation_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: ValidationValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(ValidationValueErrorHandler { handler })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::web::Bytes;
+    use actix_web::{http::header::ContentType, post, test, web::Json, App, Responder};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct ExamplePayload {
+        name: String,
+    }
+
+    impl Validate for ExamplePayload {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            if self.name.len() > 4 {
+                Ok(())
+            } else {
+                Err(vec![ValidationError::new("name not long enough")
+                    .with_field("name")
+                    .with_code("length")])
+            }
+        }
+    }
+
+    #[post("/")]
+    async fn endpoint(v: Validated<Json<ExamplePayload>>) -> impl Responder {
+        assert!(v.name.len() > 4);
+        HttpResponse::Ok().body(())
+    }
+
+    #[actix_web::test]
+    async fn should_validate_simple() {
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        // Valid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "123456".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        // Invalid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
+
+    #[actix_web::test]
+    async fn should_respond_with_errors_correctly() {
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        // Invalid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\n\tname: name not long enough")
+        );
+    }
+
+    #[actix_web::test]
+    async fn should_redact_messages_of_sensitive_fields() {
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .app_data(RedactionPolicy::new().field("name")),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\n\tname: [REDACTED]")
+        );
+    }
+
+    #[derive(Debug, Serialize, Error)]
+    struct CustomErrorResponse {
+        custom_message: String,
+        errors: Vec<String>,
+    }
+
+    impl Display for CustomErrorResponse {
+        fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            unimplemented!()
+        }
+    }
+
+    impl ResponseError for CustomErrorResponse {
+        fn status_code(&self) -> actix_web::http::StatusCode {
+            actix_web::http::StatusCode::BAD_REQUEST
+        }
+
+        fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
+            HttpResponse::build(self.status_code()).body(serde_json::to_string(self).unwrap())
+        }
+    }
+
+    fn error_handler(errors: Vec<ValidationError>, _: &HttpRequest) -> actix_web::Error {
+        CustomErrorResponse {
             custom_message: "My custom message".to_string(),
             errors: errors.iter().map(|err| err.message.clone()).collect(),
         }
-        .into()
+        .into()
+    }
+
+    #[actix_web::test]
+    async fn should_use_allow_custom_error_responses() {
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .validation_error_handler(Arc::new(error_handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(
+                b"{\"custom_message\":\"My custom message\",\"errors\":[\"name not long enough\"]}"
+            )
+        );
+    }
+
+    #[test]
+    async fn debug_for_validated_should_work() {
+        let v = Validated(ExamplePayload {
+            name: "abcde".to_string(),
+        });
+
+        assert_eq!(
+            "Validated(ExamplePayload { name: \"abcde\" })",
+            format!("{v:?}")
+        );
+    }
+
+    struct Username(String);
+
+    impl TryFrom<ExamplePayload> for Username {
+        type Error = ValidationError;
+
+        fn try_from(payload: ExamplePayload) -> Result<Self, Self::Error> {
+            if payload.name == "admin" {
+                return Err(ValidationError::new("reserved").with_field("name"));
+            }
+            Ok(Username(payload.name))
+        }
+    }
+
+    #[post("/into")]
+    async fn into_endpoint(v: ValidatedInto<Json<ExamplePayload>, Username>) -> impl Responder {
+        v.into_inner().0
+    }
+
+    #[actix_web::test]
+    async fn should_convert_validated_values() {
+        let app = test::init_service(App::new().service(into_endpoint)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/into")
+            .set_json(ExamplePayload {
+                name: "123456".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(result, Bytes::from_static(b"123456"));
+
+        let req = test::TestRequest::post()
+            .uri("/into")
+            .set_json(ExamplePayload {
+                name: "admin".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let result = test::read_body(resp).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\n\tname: reserved")
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct ManyErrorsPayload {
+        tags: Vec<String>,
+    }
+
+    impl Validate for ManyErrorsPayload {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            Err(self
+                .tags
+                .iter()
+                .enumerate()
+                .map(|(i, _)| ValidationError::new(format!("tag {i} is invalid")))
+                .collect())
+        }
+    }
+
+    #[post("/many")]
+    async fn many_errors_endpoint(_: Validated<Json<ManyErrorsPayload>>) -> impl Responder {
+        HttpResponse::Ok().body(())
+    }
+
+    #[actix_web::test]
+    async fn should_limit_reported_errors() {
+        let app = test::init_service(
+            App::new()
+                .service(many_errors_endpoint)
+                .app_data(crate::limit::ErrorLimit::max_errors(2)),
+        )
+        .await;
+
+        let payload = ManyErrorsPayload {
+            tags: vec!["a".to_string(); 100],
+        };
+        let req = test::TestRequest::post()
+            .uri("/many")
+            .set_json(&payload)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(crate::limit::TRUNCATED_HEADER).unwrap(),
+            "true"
+        );
+        let result = test::read_body(resp).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\n\ttag 0 is invalid\n\ttag 1 is invalid\n\t(more errors truncated)")
+        );
+
+        // Errors within the limit are not truncated
+        let payload = ManyErrorsPayload {
+            tags: vec!["a".to_string(); 2],
+        };
+        let req = test::TestRequest::post()
+            .uri("/many")
+            .set_json(&payload)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.headers().get(crate::limit::TRUNCATED_HEADER).is_none());
+    }
+
+    /// An extractor that deliberately doesn't implement `Debug` or `DerefMut`
+    #[derive(derive_more::Deref)]
+    struct Handle(ExamplePayload);
+
+    impl FromRequest for Handle {
+        type Error = actix_web::Error;
+        type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+        fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+            std::future::ready(Ok(Handle(ExamplePayload {
+                name: req.query_string().to_string(),
+            })))
+        }
+    }
+
+    #[post("/handle")]
+    async fn handle_endpoint(v: Validated<Handle>) -> impl Responder {
+        v.into_inner().0.name
+    }
+
+    #[actix_web::test]
+    async fn should_validate_extractors_without_debug() {
+        let app = test::init_service(App::new().service(handle_endpoint)).await;
+
+        let req = test::TestRequest::post().uri("/handle?123456").to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(result, Bytes::from_static(b"123456"));
+
+        let req = test::TestRequest::post().uri("/handle?1234").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct ThreadPayload {
+        name: String,
+    }
+
+    impl Validate for ThreadPayload {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            if std::thread::current().name() == Some(self.name.as_str()) {
+                Err(vec![ValidationError::new("validated on the worker thread")])
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[post("/thread")]
+    async fn thread_endpoint(_v: Validated<Json<ThreadPayload>>) -> impl Responder {
+        HttpResponse::Ok().body(())
+    }
+
+    #[actix_web::test]
+    async fn should_offload_validation_above_payload_size() {
+        use crate::blocking::{OffloadPolicy, OffloadValidationExt};
+
+        let app = test::init_service(
+            App::new()
+                .service(thread_endpoint)
+                .offload_validation::<Validated<Json<ThreadPayload>>>(
+                    OffloadPolicy::min_payload_size(256),
+                ),
+        )
+        .await;
+        let worker = std::thread::current().name().unwrap().to_string();
+
+        // Small payloads are validated on the worker thread
+        let req = test::TestRequest::post()
+            .uri("/thread")
+            .set_json(ThreadPayload {
+                name: worker.clone(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        // Large payloads are validated on the blocking thread pool
+        let req = test::TestRequest::post()
+            .uri("/thread")
+            .set_json(ThreadPayload {
+                name: format!("{worker}{}", " ".repeat(256)),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct SlowPayload {
+        millis: u64,
+    }
+
+    impl Validate for SlowPayload {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            std::thread::sleep(std::time::Duration::from_millis(self.millis));
+            Ok(())
+        }
+    }
+
+    #[post("/slow")]
+    async fn slow_endpoint(
+        v: Result<Validated<Json<SlowPayload>>, actix_web::Error>,
+    ) -> impl Responder {
+        match v {
+            Ok(_) => HttpResponse::Ok().body("validated"),
+            Err(e) if e.as_error::<crate::timeout::ValidationTimedOut>().is_some() => {
+                HttpResponse::Accepted().body("timed out")
+            }
+            Err(e) => e.error_response(),
+        }
     }
 
     #[actix_web::test]
-    async fn should_use_allow_custom_error_responses() {
+    async fn should_time_out_slow_validation() {
+        use crate::blocking::{OffloadPolicy, OffloadValidationExt};
+        use crate::timeout::{ValidationTimeout, ValidationTimeoutExt};
+        use std::time::Duration;
+
+        // Types with a timeout are offloaded even if their offload policy does not apply
         let app = test::init_service(
             App::new()
-                .service(endpoint)
-                .validation_error_handler(Arc::new(error_handler)),
+                .service(slow_endpoint)
+                .offload_validation::<Validated<Json<SlowPayload>>>(
+                    OffloadPolicy::min_payload_size(1024 * 1024),
+                )
+                .validation_timeout::<Validated<Json<SlowPayload>>>(ValidationTimeout::new(
+                    Duration::from_millis(50),
+                )),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/slow")
+            .set_json(SlowPayload { millis: 0 })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(result, Bytes::from_static(b"validated"));
+
+        // The handler can tell timeouts apart from validation errors
+        let req = test::TestRequest::post()
+            .uri("/slow")
+            .set_json(SlowPayload { millis: 500 })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 202);
+
+        // Timeouts respond with the configured status
+        let app = test::init_service(
+            App::new()
+                .route(
+                    "/slow",
+                    actix_web::web::post().to(|_: Validated<Json<SlowPayload>>| async {
+                        HttpResponse::Ok().finish()
+                    }),
+                )
+                .validation_timeout::<Validated<Json<SlowPayload>>>(
+                    ValidationTimeout::new(Duration::from_millis(50)).unprocessable_entity(),
+                ),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/slow")
+            .set_json(SlowPayload { millis: 500 })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+    }
+
+    #[cfg(feature = "ws")]
+    #[actix_web::test]
+    async fn should_validate_websocket_messages() {
+        use actix_ws::AggregatedMessage;
+
+        let req = test::TestRequest::default().to_http_request();
+
+        let msg = AggregatedMessage::Text(r#"{"name":"123456"}"#.into());
+        let payload = validate_message::<ExamplePayload>(&req, &msg);
+        assert_eq!(payload.unwrap().unwrap().name, "123456");
+
+        // Rejected messages use the format of the built-in error responses
+        let msg = AggregatedMessage::Text(r#"{"name":"1234"}"#.into());
+        let frame = validate_message::<ExamplePayload>(&req, &msg)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(
+            frame.text(),
+            "Validation errors in fields:\n\tname: name not long enough"
+        );
+    }
+
+    #[cfg(feature = "patch")]
+    async fn patch_endpoint(_: ValidatedPatch<ExamplePayload>) -> impl Responder {
+        HttpResponse::Ok().finish()
+    }
+
+    #[cfg(feature = "patch")]
+    #[actix_web::test]
+    async fn should_report_patch_paths() {
+        use crate::patch::PatchLoaderExt;
+        use actix_web::web;
+
+        fn patch_path_handler(errors: Vec<ValidationError>, _: &HttpRequest) -> actix_web::Error {
+            let paths = errors
+                .iter()
+                .filter_map(|err| err.patch_path())
+                .collect::<Vec<_>>();
+            actix_web::error::ErrorBadRequest(paths.join(","))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .patch_loader::<ExamplePayload, _, _>(|_| async {
+                    Ok(ExamplePayload {
+                        name: "123456".to_string(),
+                    })
+                })
+                .validation_error_handler(Arc::new(patch_path_handler))
+                .route("/", web::patch().to(patch_endpoint)),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(serde_json::json!({ "name": "abc" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(test::read_body(resp).await, Bytes::from_static(b"/name"));
+
+        // Map keys are not split like fields
+        let app = test::init_service(
+            App::new()
+                .patch_loader::<LabelsPayload, _, _>(|_| async {
+                    Ok(LabelsPayload {
+                        labels: std::collections::BTreeMap::new(),
+                    })
+                })
+                .validation_error_handler(Arc::new(patch_path_handler))
+                .route("/", web::patch().to(labels_endpoint)),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(serde_json::json!({ "labels": { "en.US": "", "de": "Hallo" } }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(
+            test::read_body(resp).await,
+            Bytes::from_static(b"/labels/en.US")
+        );
+    }
+
+    #[cfg(feature = "patch")]
+    #[derive(Debug, Deserialize, Serialize)]
+    struct LabelsPayload {
+        labels: std::collections::BTreeMap<String, String>,
+    }
+
+    #[cfg(feature = "patch")]
+    impl Validate for LabelsPayload {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            let errors = self
+                .labels
+                .iter()
+                .filter(|(_, label)| label.is_empty())
+                .map(|(key, _)| {
+                    ValidationError::new("label is empty")
+                        .with_path(FieldPath::new().field("labels").key(key.clone()))
+                })
+                .collect::<Vec<_>>();
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
+            }
+        }
+    }
+
+    #[cfg(feature = "patch")]
+    async fn labels_endpoint(_: ValidatedPatch<LabelsPayload>) -> impl Responder {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn should_render_paths_in_configured_syntax() {
+        #[derive(Debug, Deserialize)]
+        struct Labels {
+            labels: std::collections::BTreeMap<String, String>,
+        }
+
+        impl Validate for Labels {
+            fn validate(&self) -> Result<(), Vec<ValidationError>> {
+                Err(self
+                    .labels
+                    .keys()
+                    .map(|key| {
+                        ValidationError::new("label is empty")
+                            .with_path(FieldPath::new().field("labels").key(key.clone()))
+                    })
+                    .collect())
+            }
+        }
+
+        async fn labels(_: Validated<Json<Labels>>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+
+        for (syntax, path) in [
+            (PathSyntax::Dotted, "labels.en"),
+            (PathSyntax::JsonPointer, "/labels/en"),
+            (PathSyntax::Brackets, "labels[en]"),
+        ] {
+            let app = test::init_service(
+                App::new()
+                    .app_data(syntax)
+                    .route("/", actix_web::web::post().to(labels)),
+            )
+            .await;
+
+            let req = test::TestRequest::post()
+                .uri("/")
+                .set_json(serde_json::json!({ "labels": { "en": "" } }))
+                .to_request();
+            let body = test::call_and_read_body(&app, req).await;
+            let body = std::str::from_utf8(&body).unwrap();
+            assert_eq!(
+                body,
+                format!("Validation errors in fields:\n\t{path}: label is empty")
+            );
+        }
+    }
+
+    // An explicit mapping, without the derive macro
+    impl crate::rules::ValidationRules for ExamplePayload {
+        fn rules() -> Vec<crate::rules::FieldRules> {
+            vec![crate::rules::FieldRules {
+                name: "name",
+                serialized_name: "displayName",
+                field_type: crate::rules::FieldType::String,
+                required: true,
+                nullable: false,
+                rules: Vec::new(),
+            }]
+        }
+    }
+
+    #[actix_web::test]
+    async fn should_report_serialized_field_names() {
+        use crate::field_names::SerializedNamesExt;
+
+        fn field_handler(errors: Vec<ValidationError>, _: &HttpRequest) -> actix_web::Error {
+            let fields = errors
+                .iter()
+                .filter_map(|err| err.field())
+                .collect::<Vec<_>>();
+            actix_web::error::ErrorBadRequest(fields.join(","))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .serialized_names::<ExamplePayload>()
+                .validation_error_handler(Arc::new(field_handler))
+                .service(endpoint),
         )
         .await;
 
         let req = test::TestRequest::post()
             .uri("/")
-            .insert_header(ContentType::plaintext())
             .set_json(ExamplePayload {
                 name: "1234".to_string(),
             })
             .to_request();
-        let result = test::call_and_read_body(&app, req).await;
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
         assert_eq!(
-            result,
+            test::read_body(resp).await,
+            Bytes::from_static(b"displayName")
+        );
+    }
+
+    #[cfg(feature = "spans")]
+    #[actix_web::test]
+    async fn should_report_source_spans() {
+        use crate::spans::SourceSpansExt;
+
+        fn span_handler(errors: Vec<ValidationError>, _: &HttpRequest) -> actix_web::Error {
+            let spans = errors
+                .iter()
+                .filter_map(|err| err.span())
+                .map(|span| span.to_string())
+                .collect::<Vec<_>>();
+            actix_web::error::ErrorBadRequest(spans.join(","))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .source_spans::<ExamplePayload>()
+                .validation_error_handler(Arc::new(span_handler))
+                .service(endpoint),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_payload("{\n  \"name\": \"1234\"\n}")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(
+            test::read_body(resp).await,
+            Bytes::from_static(b"line 2, column 11")
+        );
+
+        let app = test::init_service(
+            App::new()
+                .source_spans::<ExamplePayload>()
+                .service(endpoint),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_payload("{\n  \"name\": \"1234\"\n}")
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            body,
             Bytes::from_static(
-                b"{\"custom_message\":\"My custom message\",\"errors\":[\"name not long enough\"]}"
+                b"Validation errors in fields:\n\tname: name not long enough (line 2, column 11)"
             )
         );
     }
 
-    #[test]
-    async fn debug_for_validated_should_work() {
-        let v = Validated(ExamplePayload {
-            name: "abcde".to_string(),
-        });
+    #[actix_web::test]
+    async fn should_hand_rejected_values_to_the_handler() {
+        async fn signup(result: ValidationResult<Json<ExamplePayload>>) -> HttpResponse {
+            match result.into_result() {
+                Ok(_) => HttpResponse::Ok().body("welcome"),
+                Err(rejected) => {
+                    let (value, errors) = rejected.into_parts();
+                    HttpResponse::Ok().body(format!("{}: {}", value.name, errors[0]))
+                }
+            }
+        }
+
+        let app =
+            test::init_service(App::new().route("/", actix_web::web::post().to(signup))).await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, Bytes::from_static(b"1234: name not long enough"));
+    }
+
+    #[actix_web::test]
+    async fn should_pass_rejected_values_to_value_error_handlers() {
+        fn value_handler(
+            value: &ExamplePayload,
+            errors: Vec<ValidationError>,
+            _: &HttpRequest,
+        ) -> actix_web::Error {
+            actix_web::error::ErrorBadRequest(format!("{}: {}", value.name, errors[0]))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .validation_value_error_handler(Arc::new(value_handler))
+                .service(endpoint),
+        )
+        .await;
 
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
         assert_eq!(
-            "Validated(ExamplePayload { name: \"abcde\" })",
-            format!("{v:?}")
+            test::read_body(resp).await,
+            Bytes::from_static(b"1234: name not long enough")
+        );
+    }
+
+    #[cfg(feature = "ws")]
+    #[actix_web::test]
+    async fn should_pass_rejected_messages_to_value_error_handlers() {
+        use actix_ws::Message;
+
+        fn value_handler(
+            value: &ExamplePayload,
+            errors: Vec<ValidationError>,
+            _: &HttpRequest,
+        ) -> actix_web::Error {
+            actix_web::error::ErrorBadRequest(format!("{}: {}", value.name, errors[0]))
+        }
+
+        let req = test::TestRequest::default()
+            .app_data(ValidationErrorHandler {
+                handler: Arc::new(error_handler),
+            })
+            .app_data(ValidationValueErrorHandler::<ExamplePayload> {
+                handler: Arc::new(value_handler),
+            })
+            .to_http_request();
+
+        let msg = Message::Text(r#"{"name":"1234"}"#.into());
+        let frame = validate_message::<ExamplePayload>(&req, &msg)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(frame.status().as_u16(), 400);
+        assert_eq!(frame.text(), "1234: name not long enough");
+    }
+
+    #[cfg(feature = "patch")]
+    #[actix_web::test]
+    async fn should_pass_rejected_patches_to_value_error_handlers() {
+        use crate::patch::PatchLoaderExt;
+        use actix_web::web;
+
+        fn value_handler(
+            value: &ExamplePayload,
+            errors: Vec<ValidationError>,
+            _: &HttpRequest,
+        ) -> actix_web::Error {
+            actix_web::error::ErrorBadRequest(format!("{}: {}", value.name, errors[0]))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .patch_loader::<ExamplePayload, _, _>(|_| async {
+                    Ok(ExamplePayload {
+                        name: "123456".to_string(),
+                    })
+                })
+                .validation_error_handler(Arc::new(error_handler))
+                .validation_value_error_handler(Arc::new(value_handler))
+                .route("/", web::patch().to(patch_endpoint)),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(serde_json::json!({ "name": "1234" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(
+            test::read_body(resp).await,
+            Bytes::from_static(b"1234: name not long enough")
         );
     }
+
+    #[cfg(feature = "metrics")]
+    #[actix_web::test]
+    async fn should_record_metrics() {
+        use crate::metrics::test::recorded;
+        use crate::metrics::VALIDATION_FAILURES;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let _guard = ::metrics::set_default_local_recorder(&recorder);
+
+        let app =
+            test::init_service(App::new().service(endpoint).service(many_errors_endpoint)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let req = test::TestRequest::post()
+            .uri("/many")
+            .set_json(ManyErrorsPayload {
+                tags: vec!["a".to_string(); 3],
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let failures = recorded(&snapshotter)
+            .into_iter()
+            .filter(|(name, _, _)| name == VALIDATION_FAILURES)
+            .map(|(_, labels, value)| (labels, value))
+            .collect::<Vec<_>>();
+        let labels = |field: &str, code: &str, route: &str, type_name: &str| {
+            vec![
+                ("code".to_string(), code.to_string()),
+                ("field".to_string(), field.to_string()),
+                ("route".to_string(), route.to_string()),
+                ("type".to_string(), type_name.to_string()),
+            ]
+        };
+
+        assert!(failures.contains(&(
+            labels(
+                "name",
+                "length",
+                "/",
+                std::any::type_name::<ExamplePayload>()
+            ),
+            DebugValue::Counter(1)
+        )));
+        assert!(failures.contains(&(
+            labels(
+                "$",
+                "invalid",
+                "/many",
+                std::any::type_name::<ManyErrorsPayload>()
+            ),
+            DebugValue::Counter(3)
+        )));
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct UserPayload {
+        name: Option<String>,
+    }
+
+    impl Validate for UserPayload {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            match &self.name {
+                Some(name) if name.len() < 5 => {
+                    Err(vec![
+                        ValidationError::new("name not long enough").with_field("name")
+                    ])
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl ValidateGroup for UserPayload {
+        fn validate_group(&self, group: ValidationGroup) -> Result<(), Vec<ValidationError>> {
+            if group == ValidationGroup::Create && self.name.is_none() {
+                return Err(vec![ValidationError::new("name is required")
+                    .with_field("name")
+                    .with_code("required")]);
+            }
+            self.validate()
+        }
+    }
+
+    async fn create_user(_: ValidatedGroup<Json<UserPayload>, Create>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    async fn update_user(_: ValidatedGroup<Json<UserPayload>, Update>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    async fn upsert_user(_: ValidatedGroup<Json<UserPayload>, ByMethod>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn should_validate_groups() {
+        use crate::blocking::{OffloadPolicy, OffloadValidationExt};
+        use actix_web::web;
+
+        let app = test::init_service(
+            App::new()
+                // Registrations apply to the group they were made for
+                .offload_validation::<ValidatedGroup<Json<UserPayload>, Create>>(
+                    OffloadPolicy::always(),
+                )
+                .route("/create", web::post().to(create_user))
+                .route("/update", web::post().to(update_user))
+                .route("/upsert", web::post().to(upsert_user))
+                .route("/upsert", web::patch().to(upsert_user)),
+        )
+        .await;
+
+        let cases = [
+            (test::TestRequest::post().uri("/create"), 400),
+            (test::TestRequest::post().uri("/update"), 200),
+            (test::TestRequest::post().uri("/upsert"), 400),
+            (test::TestRequest::patch().uri("/upsert"), 200),
+        ];
+        for (req, status) in cases {
+            let req = req.set_json(UserPayload { name: None }).to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status().as_u16(), status);
+        }
+
+        // The regular rules apply to every group
+        let req = test::TestRequest::post()
+            .uri("/update")
+            .set_json(UserPayload {
+                name: Some("1234".to_string()),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
 }
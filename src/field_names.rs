@@ -0,0 +1,251 @@
+//! Field paths using the names clients send.
+//!
+//! Validation errors use the Rust field identifiers (`first_name`), which differ from the JSON
+//! keys of DTOs with `#[serde(rename_all = "camelCase")]` (`firstName`). Register the types whose
+//! errors should use the serialized names:
+//!
+//! ```ignore
+//! use actix_web_validation::field_names::SerializedNamesExt;
+//! use actix_web_validation::rules::ValidationRules;
+//!
+//! #[derive(Deserialize, Validate, ValidationRules)]
+//! #[serde(rename_all = "camelCase")]
+//! struct CreateUser {
+//!     #[validate(length(min = 1))]
+//!     first_name: String,
+//! }
+//!
+//! App::new().serialized_names::<CreateUser>()
+//! ```
+//!
+//! The names are read from the [`ValidationRules`] of the type, derived from its serde attributes
+//! with the `derive` feature flag or implemented by hand as an explicit mapping. Nested types
+//! are renamed with their own rules. The mapping applies to the paths of every backend: validator
+//! error keys, garde report paths and the field of custom errors. Paths of a
+//! [`RedactionPolicy`](crate::redaction::RedactionPolicy) keep using the Rust field names.
+
+use std::marker::PhantomData;
+
+use actix_web::dev::{ServiceFactory, ServiceRequest};
+use actix_web::{App, HttpRequest};
+
+use crate::path::{FieldPath, PathSegment};
+use crate::rules::{FieldRules, FieldType, ValidationRules};
+
+/// The field names registered for `T`
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+struct SerializedNames<T: ?Sized> {
+    rules: fn() -> Vec<FieldRules>,
+    _type: PhantomData<fn() -> T>,
+}
+
+/// The serialized names of the fields of a type
+#[derive(Debug, Clone)]
+pub struct FieldNames {
+    rules: Vec<FieldRules>,
+}
+
+impl FieldNames {
+    pub fn of<T: ValidationRules + ?Sized>() -> Self {
+        Self { rules: T::rules() }
+    }
+
+    /// The serialized name of the Rust field `name` and the names of its nested fields
+    pub fn field(&self, name: &str) -> Option<(&'static str, Option<FieldNames>)> {
+        let field = self.rules.iter().find(|field| field.name == name)?;
+        let nested = nested(&field.field_type).0;
+        Some((field.serialized_name, nested))
+    }
+
+    /// Maps a validation error path (eg. `address.first_name` or `emails[0]`) to the serialized
+    /// names. Unknown fields keep their name.
+    pub fn path(&self, path: &str) -> String {
+        self.rename(&FieldPath::parse(path)).to_string()
+    }
+
+    /// Maps the fields of `path` to the serialized names, the segment following a map field
+    /// becomes a [`PathSegment::Key`]. Unknown fields keep their name.
+    pub fn rename(&self, path: &FieldPath) -> FieldPath {
+        let mut names = Some(self.clone());
+        let mut map_key = false;
+        let mut renamed = FieldPath::new();
+
+        for segment in path.segments() {
+            let name = match segment {
+                PathSegment::Index(_) => {
+                    renamed.push(segment.clone());
+                    continue;
+                }
+                PathSegment::Field(name) | PathSegment::Key(name)
+                    if std::mem::take(&mut map_key) =>
+                {
+                    renamed.push(PathSegment::Key(name.clone()));
+                    continue;
+                }
+                PathSegment::Key(_) => {
+                    renamed.push(segment.clone());
+                    continue;
+                }
+                PathSegment::Field(name) => name,
+            };
+
+            let field = names
+                .as_ref()
+                .and_then(|names| names.rules.iter().find(|field| field.name == name))
+                .map(|field| (field.serialized_name, nested(&field.field_type)));
+            let Some((serialized_name, (nested_names, is_map))) = field else {
+                names = None;
+                renamed.push(segment.clone());
+                continue;
+            };
+
+            names = nested_names;
+            map_key = is_map;
+            renamed.push(PathSegment::Field(serialized_name.to_string()));
+        }
+
+        renamed
+    }
+}
+
+/// The rules of the type nested in a field, and whether it is keyed by a map
+fn nested(field_type: &FieldType) -> (Option<FieldNames>, bool) {
+    match field_type {
+        FieldType::Array(inner) => nested(inner),
+        FieldType::Map(inner) => (nested(inner).0, true),
+        FieldType::Object(object) => (
+            Some(FieldNames {
+                rules: object.rules(),
+            }),
+            false,
+        ),
+        _ => (None, false),
+    }
+}
+
+/// The field names registered for `T`, if any
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+pub(crate) fn field_names<T: ?Sized + 'static>(req: &HttpRequest) -> Option<FieldNames> {
+    let names = req.app_data::<SerializedNames<T>>()?;
+    Some(FieldNames {
+        rules: (names.rules)(),
+    })
+}
+
+pub trait SerializedNamesExt {
+    /// Reports validation errors of `T` with the serialized field names
+    fn serialized_names<T: ValidationRules + 'static>(self) -> Self;
+}
+
+impl<A> SerializedNamesExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn serialized_names<T: ValidationRules + 'static>(self) -> Self {
+        self.app_data(SerializedNames::<T> {
+            rules: T::rules,
+            _type: PhantomData,
+        })
+    }
+}
+
+impl SerializedNamesExt for &mut actix_web::web::ServiceConfig {
+    fn serialized_names<T: ValidationRules + 'static>(self) -> Self {
+        self.app_data(SerializedNames::<T> {
+            rules: T::rules,
+            _type: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl SerializedNamesExt for crate::test_util::ValidationProbe {
+    fn serialized_names<T: ValidationRules + 'static>(self) -> Self {
+        self.app_data(SerializedNames::<T> {
+            rules: T::rules,
+            _type: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::ObjectRules;
+
+    fn field(
+        name: &'static str,
+        serialized_name: &'static str,
+        field_type: FieldType,
+    ) -> FieldRules {
+        FieldRules {
+            name,
+            serialized_name,
+            field_type,
+            required: true,
+            nullable: false,
+            rules: Vec::new(),
+        }
+    }
+
+    struct Address;
+
+    impl ValidationRules for Address {
+        fn rules() -> Vec<FieldRules> {
+            vec![field("zip_code", "zipCode", FieldType::String)]
+        }
+    }
+
+    struct User;
+
+    impl ValidationRules for User {
+        fn rules() -> Vec<FieldRules> {
+            vec![
+                field("first_name", "firstName", FieldType::String),
+                field(
+                    "addresses",
+                    "homeAddresses",
+                    FieldType::Array(Box::new(FieldType::Object(ObjectRules::of::<Address>()))),
+                ),
+                field(
+                    "labels",
+                    "labelsByKey",
+                    FieldType::Map(Box::new(FieldType::Object(ObjectRules::of::<Address>()))),
+                ),
+            ]
+        }
+    }
+
+    #[test]
+    fn should_map_paths_to_serialized_names() {
+        let names = FieldNames::of::<User>();
+        assert_eq!(names.path("first_name"), "firstName");
+        assert_eq!(
+            names.path("addresses[1].zip_code"),
+            "homeAddresses[1].zipCode"
+        );
+        assert_eq!(
+            names.path("labels.zip_code.zip_code"),
+            "labelsByKey.zip_code.zipCode"
+        );
+        assert_eq!(names.path("unknown.zip_code"), "unknown.zip_code");
+        assert_eq!(
+            names.rename(
+                &FieldPath::new()
+                    .field("labels")
+                    .key("en.US")
+                    .field("zip_code")
+            ),
+            FieldPath::new()
+                .field("labelsByKey")
+                .key("en.US")
+                .field("zipCode")
+        );
+    }
+}
@@ -14,16 +14,25 @@
 //! For usage examples, see the documentation for [`Validated`]
 //!
 
-use crate::validated_definition;
+use crate::blocking::{offload, BlockingValidate, BlockingValidation};
+use crate::group::{All, ByMethod, Create, Group, Update, ValidationGroup};
+use crate::limit;
+use crate::optional::{Peek, Presence};
+use crate::path::{FieldPath, PathSegment, PathSyntax};
+use crate::redaction::{RedactionPolicy, REDACTED};
+use crate::sanitize::sanitize;
+use crate::{validated_definition, validated_into_definition, IntoInner};
 use ::garde::Validate;
 use actix_web::dev::{ServiceFactory, ServiceRequest};
 use actix_web::http::StatusCode;
 use actix_web::FromRequest;
 use actix_web::{App, HttpRequest, HttpResponse, ResponseError};
+use pin_project_lite::pin_project;
 use std::fmt::Display;
 use std::future::Future;
+use std::marker::PhantomData;
 use std::sync::Arc;
-use std::{fmt::Debug, ops::Deref, pin::Pin, task::Poll};
+use std::{fmt::Debug, ops::Deref, task::Poll};
 use thiserror::Error;
 
 /// A validated extactor.
@@ -49,61 +58,501 @@ use thiserror::Error;
 /// ```
 pub struct Validated<T>(pub T);
 
-validated_definition!();
+validated_definition!(Validated);
 
-/// Future that extracts and validates actix requests using the Actix Web [`FromRequest`] trait
+/// A validated extractor applying the rules of the validation group `G`.
 ///
-/// End users of this library should not need to use this directly for most usecases
-pub struct ValidatedFut<T: FromRequest> {
-    req: actix_web::HttpRequest,
-    fut: <T as FromRequest>::Future,
-    error_handler: Option<GardeErrHandler>,
+/// Extracts and validates like [`Validated`], `G` selects the rules that are applied, see
+/// [`crate::group`] and [`GroupRules`].
+///
+/// ```ignore
+/// use actix_web_validation::group::{Create, Update};
+///
+/// async fn create(user: ValidatedGroup<Json<User>, Create>) -> impl Responder { .. }
+/// async fn update(user: ValidatedGroup<Json<User>, Update>) -> impl Responder { .. }
+/// ```
+pub struct ValidatedGroup<T, G>(pub T, PhantomData<fn() -> G>);
+
+validated_definition!(ValidatedGroup<G>);
+
+pin_project! {
+    /// Future that extracts and validates actix requests using the Actix Web [`FromRequest`] trait
+    ///
+    /// End users of this library should not need to use this directly for most usecases
+    pub struct ValidatedFut<T: FromRequest, G = All, O = Validated<T>> {
+        req: actix_web::HttpRequest,
+        #[pin]
+        fut: <T as FromRequest>::Future,
+        error_handler: Option<GardeErrHandler>,
+        finish: Finish<T, O>,
+        validating: Option<(BlockingValidation<T, garde::Report>, std::time::Instant)>,
+        // Checks the recorded body for unknown fields, see `crate::strict`
+        unknown_fields: Option<Box<dyn FnOnce() -> Vec<FieldPath>>>,
+        spans: Option<AddSpans>,
+        _group: PhantomData<fn() -> G>,
+    }
 }
 
-impl<T> Future for ValidatedFut<T>
+/// Turns the extracted value and its validation result into the output of [`ValidatedFut`]
+type Finish<T, O> = fn(
+    &HttpRequest,
+    Option<&GardeErrHandler>,
+    T,
+    Result<(), garde::Report>,
+) -> Result<O, actix_web::Error>;
+
+impl<T, G, O> ValidatedFut<T, G, O>
 where
-    T: FromRequest + Debug + Deref,
-    T::Future: Unpin,
-    T::Target: Validate,
-    <T::Target as garde::Validate>::Context: Default,
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+{
+    fn new(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+        finish: Finish<T, O>,
+    ) -> Self {
+        let error_handler = req
+            .app_data::<GardeErrorHandler>()
+            .map(|h| h.handler.clone());
+
+        #[cfg(feature = "strict")]
+        let unknown_fields = crate::strict::record::<T::Target>(req, payload);
+        #[cfg(not(feature = "strict"))]
+        let unknown_fields = None;
+
+        #[cfg(feature = "spans")]
+        let spans = crate::spans::record::<T::Target>(req, payload)
+            .map(|spans| -> AddSpans { Box::new(move |errors| with_spans(errors, &spans())) });
+        #[cfg(not(feature = "spans"))]
+        let spans = None;
+
+        let fut = T::from_request(req, payload);
+
+        ValidatedFut {
+            fut,
+            error_handler,
+            finish,
+            req: req.clone(),
+            validating: None,
+            unknown_fields,
+            spans,
+            _group: PhantomData,
+        }
+    }
+}
+
+impl<T, G, O> Future for ValidatedFut<T, G, O>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    G: Group + GroupRules<T::Target>,
 {
-    type Output = Result<Validated<T>, actix_web::Error>;
+    type Output = Result<O, actix_web::Error>;
 
     fn poll(
         self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
-        let this = self.get_mut();
-        let Poll::Ready(res) = Pin::new(&mut this.fut).poll(cx) else {
-            return std::task::Poll::Pending;
+        let mut this = self.project();
+
+        loop {
+            if let Some((validating, started)) = this.validating {
+                let Poll::Ready(res) = validating.as_mut().poll(cx) else {
+                    return std::task::Poll::Pending;
+                };
+                let started = *started;
+                *this.validating = None;
+
+                return Poll::Ready(res.and_then(|(data, result)| {
+                    let result = complete::<T::Target>(
+                        this.req,
+                        started,
+                        result,
+                        this.unknown_fields.take(),
+                        this.spans.take(),
+                    );
+                    (this.finish)(this.req, this.error_handler.as_ref(), data, result)
+                }));
+            }
+
+            let Poll::Ready(res) = this.fut.as_mut().poll(cx) else {
+                return std::task::Poll::Pending;
+            };
+
+            let mut data = match res {
+                Ok(data) => data,
+                Err(e) => return Poll::Ready(Err(e.into())),
+            };
+            sanitize(this.req, &mut data);
+
+            let group = G::group(this.req);
+            let started = std::time::Instant::now();
+
+            match offload::<T, G, garde::Report>(this.req, data, group) {
+                // Poll the blocking validation right away, it wakes the task once done
+                Ok(validating) => *this.validating = Some((validating, started)),
+                Err(data) => {
+                    let result = complete::<T::Target>(
+                        this.req,
+                        started,
+                        G::validate(&data, group),
+                        this.unknown_fields.take(),
+                        this.spans.take(),
+                    );
+                    return Poll::Ready((this.finish)(
+                        this.req,
+                        this.error_handler.as_ref(),
+                        data,
+                        result,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Applies the checks registered for `T` to its validation result
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+fn complete<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    started: std::time::Instant,
+    result: Result<(), garde::Report>,
+    unknown_fields: Option<Box<dyn FnOnce() -> Vec<FieldPath>>>,
+    spans: Option<AddSpans>,
+) -> Result<(), garde::Report> {
+    let result = with_serialized_names::<T>(req, with_redaction(req, result));
+    let unknown_fields = unknown_fields.map_or_else(Vec::new, |check| check());
+
+    // Unknown fields are named by the client, so they are not recorded by their path
+    #[cfg(feature = "metrics")]
+    {
+        record_metrics::<T>(req, started, &result);
+        crate::metrics::record_unknown_fields(
+            req,
+            std::any::type_name::<T>(),
+            unknown_fields.len(),
+        );
+    }
+
+    let result = with_unknown_fields(result, unknown_fields);
+    result.map_err(|errors| match spans {
+        Some(add_spans) => add_spans(errors),
+        None => errors,
+    })
+}
+
+/// Responds with the validation errors of invalid values, gives valid values back. Used by the
+/// [`Finish`] of [`Validated`] and [`ValidatedGroup`], by `ValidatedPatch` and for WebSocket
+/// messages.
+///
+/// The value error handler registered for the value takes precedence over the error handler.
+fn reject<T>(
+    req: &HttpRequest,
+    error_handler: Option<&GardeErrHandler>,
+    data: T,
+    result: Result<(), garde::Report>,
+) -> Result<T, actix_web::Error>
+where
+    T: Deref,
+    T::Target: 'static,
+{
+    let Err(e) = result else {
+        return Ok(data);
+    };
+
+    match req.app_data::<GardeValueErrorHandler<T::Target>>() {
+        Some(value_handler) => {
+            let handler = |errors, req: &HttpRequest| (value_handler.handler)(&data, errors, req);
+            Err(respond(req, Some(&handler), e))
+        }
+        None => Err(validation_error(req, error_handler, e)),
+    }
+}
+
+/// Adds the source span of each invalid value to its error, see [`crate::spans`]
+type AddSpans = Box<dyn FnOnce(garde::Report) -> garde::Report>;
+
+/// Appends the span of the invalid value to each error message, where
+/// [`ErrorSpan`](crate::spans::ErrorSpan) reads it back
+#[cfg(feature = "spans")]
+fn with_spans(report: garde::Report, spans: &crate::spans::SpanMap) -> garde::Report {
+    let mut annotated = garde::Report::new();
+    for (path, error) in report.iter() {
+        let error = match spans.get(&FieldPath::parse(&path.to_string())) {
+            Some(span) => garde::Error::new(format!(
+                "{} ({span}, offset {})",
+                error.message(),
+                span.offset
+            )),
+            None => error.clone(),
         };
+        annotated.append(path.clone(), error);
+    }
+    annotated
+}
+
+/// Replaces the message of the errors of fields matched by the redaction policy, as it may
+/// quote the value
+fn with_redaction(
+    req: &HttpRequest,
+    result: Result<(), garde::Report>,
+) -> Result<(), garde::Report> {
+    let Some(policy) = req.app_data::<RedactionPolicy>() else {
+        return result;
+    };
+
+    result.map_err(|report| {
+        let mut redacted = garde::Report::new();
+        for (path, error) in report.iter() {
+            let error = if policy.is_redacted(&FieldPath::parse(&path.to_string())) {
+                garde::Error::new(REDACTED)
+            } else {
+                error.clone()
+            };
+            redacted.append(path.clone(), error);
+        }
+        redacted
+    })
+}
+
+/// Renames the error fields of `T` to their serialized names, see [`crate::field_names`]
+fn with_serialized_names<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    result: Result<(), garde::Report>,
+) -> Result<(), garde::Report> {
+    let Some(names) = crate::field_names::field_names::<T>(req) else {
+        return result;
+    };
 
-        let res = match res {
-            Ok(data) => {
-                if let Err(e) = data.validate() {
-                    if let Some(error_handler) = &this.error_handler {
-                        Err((*error_handler)(e, &this.req))
-                    } else {
-                        let err: Error = e.into();
-                        Err(err.into())
+    result.map_err(|report| {
+        let mut renamed = garde::Report::new();
+        for (path, error) in report.iter() {
+            let path = garde::Path::new(names.path(&path.to_string()));
+            renamed.append(path, error.clone());
+        }
+        renamed
+    })
+}
+
+/// Adds an `unknown field` error for each of the unknown `fields`
+fn with_unknown_fields(
+    result: Result<(), garde::Report>,
+    fields: Vec<FieldPath>,
+) -> Result<(), garde::Report> {
+    if fields.is_empty() {
+        return result;
+    }
+
+    let mut report = result.err().unwrap_or_else(garde::Report::new);
+    for field in fields {
+        let path =
+            field
+                .segments()
+                .iter()
+                .fold(garde::Path::empty(), |path, segment| match segment {
+                    PathSegment::Field(name) | PathSegment::Key(name) => path.join(name.as_str()),
+                    PathSegment::Index(index) => path.join(*index),
+                });
+        report.append(path, garde::Error::new("unknown field"));
+    }
+    Err(report)
+}
+
+/// Deserializes a JSON text or binary WebSocket frame into `T` and validates it.
+/// Requires the `ws` feature flag
+///
+/// The sanitizer, error limit, value error handler and error handler registered for the
+/// WebSocket upgrade request `req` are applied. Returns `None` for frames without data (eg. ping and close frames).
+///
+/// See the [`ws`](crate::ws) module for an example.
+#[cfg(feature = "ws")]
+pub fn validate_message<T>(
+    req: &HttpRequest,
+    msg: &impl crate::ws::DataFrame,
+) -> Option<Result<T, crate::ws::ErrorFrame>>
+where
+    T: serde::de::DeserializeOwned + Validate + 'static,
+    T::Context: Default,
+{
+    let data = msg.data()?;
+
+    Some(crate::ws::deserialize::<T>(data).and_then(|mut value| {
+        sanitize(req, &mut value);
+
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        let result = with_serialized_names::<T>(req, with_redaction(req, value.validate()));
+
+        #[cfg(feature = "metrics")]
+        record_metrics::<T>(req, started, &result);
+
+        let error_handler = req
+            .app_data::<GardeErrorHandler>()
+            .map(|h| h.handler.clone());
+        reject(req, error_handler.as_ref(), Box::new(value), result)
+            .map(|value| *value)
+            .map_err(Into::into)
+    }))
+}
+
+/// A `PATCH` request body applied to the current value of the resource and validated.
+/// Requires the `patch` feature flag
+///
+/// The resource is loaded with the [`PatchLoader`](crate::patch::PatchLoader) registered for
+/// `T`, see the [`patch`](crate::patch) module. Validation error messages end
+/// with the JSON Pointer of the patch path that caused them, eg. `(patch path: /name)`.
+#[cfg(feature = "patch")]
+pub struct ValidatedPatch<T>(pub T);
+
+#[cfg(feature = "patch")]
+crate::validated_patch_definition!();
+
+#[cfg(feature = "patch")]
+impl<T> FromRequest for ValidatedPatch<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + Validate + 'static,
+    T::Context: Default,
+{
+    type Error = actix_web::Error;
+
+    type Future = std::pin::Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+        use actix_web::web::Json;
+
+        let req = req.clone();
+        let patched = crate::patch::patched::<T>(&req, payload);
+
+        Box::pin(async move {
+            let patched = patched.await?;
+            let mut value = patched.value;
+            sanitize(&req, &mut value);
+
+            let error_handler = req
+                .app_data::<GardeErrorHandler>()
+                .map(|h| h.handler.clone());
+            let group = All::group(&req);
+            let started = std::time::Instant::now();
+
+            // Offloading and timeouts registered for `Validated<Json<T>>` apply
+            let (data, result) =
+                match offload::<Json<T>, All, garde::Report>(&req, Json(value), group) {
+                    Ok(validating) => validating.await?,
+                    Err(data) => {
+                        let result = <All as GroupRules<T>>::validate(&data, group);
+                        (data, result)
                     }
-                } else {
-                    Ok(Validated(data))
-                }
+                };
+            let result = complete::<T>(&req, started, result, patched.unknown_fields, None)
+                .map_err(|e| with_patch_paths(e, &patched.patch));
+            reject(&req, error_handler.as_ref(), data, result)
+                .map(|Json(value)| ValidatedPatch(value))
+        })
+    }
+}
+
+/// Appends the patch path that caused each error to its message
+#[cfg(feature = "patch")]
+fn with_patch_paths(report: garde::Report, patch: &crate::patch::Patch) -> garde::Report {
+    let mut annotated = garde::Report::new();
+    for (path, error) in report.iter() {
+        let error = match patch.path_of(&FieldPath::parse(&path.to_string())) {
+            Some(patch_path) => {
+                garde::Error::new(format!("{} (patch path: {patch_path})", error.message()))
             }
-            Err(e) => Err(e.into()),
+            None => error.clone(),
         };
+        annotated.append(path.clone(), error);
+    }
+    annotated
+}
 
-        Poll::Ready(res)
+/// The rules applied by a validation group marker of [`ValidatedGroup`].
+///
+/// [`All`] validates with the default context of the type. The other groups validate with a
+/// context created from the [`ValidationGroup`], so rules can depend on the group:
+///
+/// ```
+/// use actix_web_validation::group::ValidationGroup;
+/// use garde::Validate;
+///
+/// #[derive(Default)]
+/// struct Ctx {
+///     group: Option<ValidationGroup>,
+/// }
+///
+/// impl From<ValidationGroup> for Ctx {
+///     fn from(group: ValidationGroup) -> Self {
+///         Ctx { group: Some(group) }
+///     }
+/// }
+///
+/// #[derive(Validate)]
+/// #[garde(context(Ctx))]
+/// struct User {
+///     #[garde(custom(required_on_create))]
+///     name: Option<String>,
+/// }
+///
+/// fn required_on_create(name: &Option<String>, ctx: &Ctx) -> garde::Result {
+///     match (name, ctx.group) {
+///         (None, Some(ValidationGroup::Create)) => Err(garde::Error::new("required")),
+///         _ => Ok(()),
+///     }
+/// }
+/// ```
+pub trait GroupRules<T: ?Sized> {
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), garde::Report>;
+}
+
+impl<T> GroupRules<T> for All
+where
+    T: Validate + ?Sized,
+    T::Context: Default,
+{
+    fn validate(value: &T, _: ValidationGroup) -> Result<(), garde::Report> {
+        value.validate()
+    }
+}
+
+macro_rules! group_rules_impl {
+    ($($group:ident),*) => {
+        $(
+            impl<T> GroupRules<T> for $group
+            where
+                T: Validate + ?Sized,
+                T::Context: From<ValidationGroup>,
+            {
+                fn validate(value: &T, group: ValidationGroup) -> Result<(), garde::Report> {
+                    value.validate_with(&group.into())
+                }
+            }
+        )*
+    };
+}
+
+group_rules_impl!(Create, Update, ByMethod);
+
+impl<T> BlockingValidate for Validated<T>
+where
+    T: Deref,
+    All: GroupRules<T::Target>,
+{
+    type Extractor = T;
+    type Group = All;
+    type Report = garde::Report;
+
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), garde::Report> {
+        All::validate(value, group)
     }
 }
 
 impl<T> FromRequest for Validated<T>
 where
-    T: FromRequest + Debug + Deref,
-    T::Future: Unpin,
-    T::Target: Validate,
-    <T::Target as garde::Validate>::Context: Default,
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
 {
     type Error = actix_web::Error;
 
@@ -113,134 +562,1007 @@ where
         req: &actix_web::HttpRequest,
         payload: &mut actix_web::dev::Payload,
     ) -> Self::Future {
-        let error_handler = req
-            .app_data::<GardeErrorHandler>()
-            .map(|h| h.handler.clone());
+        ValidatedFut::new(req, payload, |req, error_handler, data, result| {
+            reject(req, error_handler, data, result).map(Validated)
+        })
+    }
+}
+
+impl<T, G> BlockingValidate for ValidatedGroup<T, G>
+where
+    T: Deref,
+    G: GroupRules<T::Target> + 'static,
+{
+    type Extractor = T;
+    type Group = G;
+    type Report = garde::Report;
+
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), garde::Report> {
+        G::validate(value, group)
+    }
+}
+
+impl<T, G> FromRequest for ValidatedGroup<T, G>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    G: Group + GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedFut<T, G, Self>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedFut::new(req, payload, |req, error_handler, data, result| {
+            reject(req, error_handler, data, result).map(|data| ValidatedGroup(data, PhantomData))
+        })
+    }
+}
+
+/// A validated extractor handing validation failures to the handler.
+///
+/// Extracts and validates like [`Validated`], but invalid values are given to the handler as
+/// a [`Rejected`] instead of responding with the validation errors, eg. to render a form again
+/// with the input of the user. Extraction errors (eg. malformed JSON) are still responded with.
+///
+/// ```
+/// use actix_web::{post, web::Form, HttpResponse};
+/// use serde::Deserialize;
+/// use garde::Validate;
+/// use actix_web_validation::garde::{ValidationResult, Validated};
+///
+/// #[derive(Debug, Deserialize, Validate)]
+/// struct Signup {
+///     #[garde(length(min = 5))]
+///     username: String,
+/// }
+///
+/// #[post("/signup")]
+/// async fn signup(result: ValidationResult<Form<Signup>>) -> HttpResponse {
+///     match result.into_result() {
+///         Ok(Validated(Form(signup))) => HttpResponse::Ok().body(signup.username),
+///         Err(rejected) => HttpResponse::Ok().body(format!(
+///             "<input value=\"{}\"> {}",
+///             rejected.value().username,
+///             rejected.errors()
+///         )),
+///     }
+/// }
+/// ```
+pub struct ValidationResult<T>(pub Result<Validated<T>, Rejected<T>>);
+
+impl<T> ValidationResult<T> {
+    pub fn into_result(self) -> Result<Validated<T>, Rejected<T>> {
+        self.0
+    }
+}
+
+impl<T: Debug> Debug for ValidationResult<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ValidationResult").field(&self.0).finish()
+    }
+}
+
+/// An extracted value that failed validation, see [`ValidationResult`]
+pub struct Rejected<T> {
+    value: T,
+    errors: garde::Report,
+}
+
+impl<T> Rejected<T> {
+    /// The extracted (and sanitized) value
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn errors(&self) -> &garde::Report {
+        &self.errors
+    }
+
+    pub fn into_parts(self) -> (T, garde::Report) {
+        (self.value, self.errors)
+    }
+}
+
+impl<T: Debug> Debug for Rejected<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rejected")
+            .field("value", &self.value)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+/// Hands invalid values to the handler, the [`Finish`] of [`ValidationResult`]
+fn keep_rejected<T>(
+    _: &HttpRequest,
+    _: Option<&GardeErrHandler>,
+    value: T,
+    result: Result<(), garde::Report>,
+) -> Result<ValidationResult<T>, actix_web::Error> {
+    Ok(ValidationResult(match result {
+        Ok(()) => Ok(Validated(value)),
+        Err(errors) => Err(Rejected { value, errors }),
+    }))
+}
+
+impl<T> FromRequest for ValidationResult<T>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedFut<T, All, Self>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedFut::new(req, payload, keep_rejected)
+    }
+}
+
+/// A validated extractor for optional input.
+///
+/// `None` when the input is absent (eg. a request without a query string for [`Query`](actix_web::web::Query),
+/// see [`Presence`]). Present input is validated like [`Validated`], so invalid input is still
+/// responded with the validation errors instead of being ignored like with `Option<Validated<T>>`.
+///
+/// ```
+/// use actix_web::{get, web::Query};
+/// use serde::Deserialize;
+/// use garde::Validate;
+/// use actix_web_validation::garde::OptionalValidated;
+///
+/// #[derive(Debug, Deserialize, Validate)]
+/// struct UserFilter {
+///     #[garde(length(min = 3))]
+///     name: String,
+/// }
+///
+/// #[get("/users")]
+/// async fn list_users(filter: OptionalValidated<Query<UserFilter>>) -> String {
+///     match filter.into_inner() {
+///         Some(filter) => format!("Users named {}", filter.name),
+///         None => "All users".to_string(),
+///     }
+/// }
+/// ```
+pub struct OptionalValidated<T>(pub Option<Validated<T>>);
+
+impl<T> OptionalValidated<T> {
+    pub fn into_inner(self) -> Option<Validated<T>> {
+        self.0
+    }
+}
+
+impl<T: Debug> Debug for OptionalValidated<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OptionalValidated").field(&self.0).finish()
+    }
+}
+
+pin_project! {
+    /// Future that extracts and validates optional input for [`OptionalValidated`]
+    pub struct OptionalValidatedFut<T: FromRequest> {
+        req: HttpRequest,
+        peek: Option<Peek>,
+        #[pin]
+        fut: Option<ValidatedFut<T>>,
+    }
+}
+
+impl<T> Future for OptionalValidatedFut<T>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Output = Result<OptionalValidated<T>, actix_web::Error>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let mut this = self.project();
+        if let Some(peek) = this.peek.as_mut() {
+            let (present, mut payload) = std::task::ready!(std::pin::Pin::new(peek).poll(cx));
+            *this.peek = None;
+            if present {
+                let fut = Validated::from_request(this.req, &mut payload);
+                this.fut.set(Some(fut));
+            }
+        }
+        match this.fut.as_pin_mut() {
+            Some(fut) => fut
+                .poll(cx)
+                .map(|res| res.map(|validated| OptionalValidated(Some(validated)))),
+            None => Poll::Ready(Ok(OptionalValidated(None))),
+        }
+    }
+}
+
+impl<T> FromRequest for OptionalValidated<T>
+where
+    T: FromRequest + Deref + Presence + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
+
+    type Future = OptionalValidatedFut<T>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        OptionalValidatedFut {
+            req: req.clone(),
+            peek: Some(Peek::new::<T>(req, payload)),
+            fut: None,
+        }
+    }
+}
+
+/// A validated extractor converting the validated value into a domain type.
+///
+/// The inner extractor is validated like [`Validated`], then its value is converted into `D`
+/// with [`TryFrom`]. Conversion errors go through the same error handler as validation errors.
+///
+/// ```
+/// use actix_web::{post, web::{self, Json}, App};
+/// use serde::Deserialize;
+/// use garde::{Path, Report, Validate};
+/// use actix_web_validation::garde::ValidatedInto;
+///
+/// #[derive(Debug, Deserialize, Validate)]
+/// struct CreateUserDto {
+///     #[garde(length(min = 3))]
+///     username: String,
+/// }
+///
+/// struct CreateUser {
+///     username: String,
+/// }
+///
+/// impl TryFrom<CreateUserDto> for CreateUser {
+///     type Error = Report;
+///
+///     fn try_from(dto: CreateUserDto) -> Result<Self, Self::Error> {
+///         if dto.username == "admin" {
+///             let mut report = Report::new();
+///             report.append(Path::new("username"), garde::Error::new("reserved"));
+///             return Err(report);
+///         }
+///         Ok(CreateUser { username: dto.username })
+///     }
+/// }
+///
+/// #[post("/")]
+/// async fn index(user: ValidatedInto<Json<CreateUserDto>, CreateUser>) -> String {
+///     format!("Welcome {}!", user.username)
+/// }
+/// ```
+pub struct ValidatedInto<T, D> {
+    inner: D,
+    _extractor: PhantomData<fn() -> T>,
+}
+
+validated_into_definition!();
+
+impl<T, D> Future for ValidatedIntoFut<T, D>
+where
+    T: FromRequest + Deref + IntoInner + 'static,
+    T::Target: Validate + 'static,
+    <T::Target as garde::Validate>::Context: Default,
+    D: TryFrom<T::Inner>,
+    D::Error: Into<garde::Report>,
+{
+    type Output = Result<ValidatedInto<T, D>, actix_web::Error>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let mut this = self.project();
+        let Poll::Ready(res) = this.fut.as_mut().poll(cx) else {
+            return std::task::Poll::Pending;
+        };
+
+        let res = res.and_then(|Validated(data)| {
+            D::try_from(data.into_inner())
+                .map(ValidatedInto::new)
+                .map_err(|e| {
+                    // Conversion errors are post-processed like the errors of `validate()`
+                    let req = &this.fut.req;
+                    let errors = with_redaction(req, Err(e.into()));
+                    let errors = with_serialized_names::<T::Target>(req, errors).unwrap_err();
+                    validation_error(req, this.fut.error_handler.as_ref(), errors)
+                })
+        });
+
+        Poll::Ready(res)
+    }
+}
+
+impl<T, D> FromRequest for ValidatedInto<T, D>
+where
+    T: FromRequest + Deref + IntoInner + 'static,
+    T::Target: Validate + 'static,
+    <T::Target as garde::Validate>::Context: Default,
+    D: TryFrom<T::Inner>,
+    D::Error: Into<garde::Report>,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedIntoFut<T, D>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedIntoFut {
+            fut: Validated::<T>::from_request(req, payload),
+            _domain: PhantomData,
+        }
+    }
+}
+
+/// Converts validation errors into an error response, using the custom error handler if any
+fn validation_error(
+    req: &HttpRequest,
+    error_handler: Option<&GardeErrHandler>,
+    errors: garde::Report,
+) -> actix_web::Error {
+    respond(
+        req,
+        error_handler.map(|handler| handler.as_ref() as &RespondWith),
+        errors,
+    )
+}
+
+/// An error handler borrowing from the caller
+type RespondWith<'a> = dyn Fn(garde::Report, &HttpRequest) -> actix_web::Error + 'a;
+
+/// Responds with `errors` after applying the registered error limit
+fn respond(
+    req: &HttpRequest,
+    error_handler: Option<&RespondWith>,
+    mut errors: garde::Report,
+) -> actix_web::Error {
+    let truncated = limit::max_errors(req).is_some_and(|max| truncate(&mut errors, max));
+    if truncated {
+        limit::mark_truncated(req);
+    }
+    if let Some(error_handler) = error_handler {
+        (*error_handler)(errors, req)
+    } else {
+        Error {
+            report: errors,
+            truncated,
+            syntax: crate::path::syntax(req),
+        }
+        .into()
+    }
+}
+
+/// The path and error of every error in `report`, see [`crate::path`].
+///
+/// Garde does not tell map keys apart from fields, so both are [`Field`](crate::path::PathSegment::Field)
+/// segments.
+pub fn error_paths(report: &garde::Report) -> Vec<(FieldPath, &garde::Error)> {
+    report
+        .iter()
+        .map(|(path, error)| (FieldPath::parse(&path.to_string()), error))
+        .collect()
+}
+
+/// Keeps the first `max` errors, returning `true` if any error was dropped
+fn truncate(report: &mut garde::Report, max: usize) -> bool {
+    if report.iter().count() <= max {
+        return false;
+    }
+    let errors = std::mem::replace(report, garde::Report::new()).into_inner();
+    for (path, error) in errors.into_iter().take(max) {
+        report.append(path, error);
+    }
+    true
+}
+
+/// The error returned when validation fails and no error handler is registered.
+///
+/// Middleware can recognise validation failures with [`actix_web::Error::as_error`] and read
+/// the report, eg. to log or reformat it:
+///
+/// ```ignore
+/// if let Some(error) = res.response().error().and_then(|e| e.as_error::<actix_web_validation::garde::Error>()) {
+///     log::warn!("validation failed: {}", error);
+/// }
+/// ```
+#[derive(Error, Debug)]
+pub struct Error {
+    report: garde::Report,
+    truncated: bool,
+    syntax: PathSyntax,
+}
+
+impl Error {
+    /// The validation report, truncated to the registered limit
+    pub fn report(&self) -> &garde::Report {
+        &self.report
+    }
+
+    pub fn into_report(self) -> garde::Report {
+        self.report
+    }
+
+    /// Whether errors were dropped to respect the limit, see [`crate::limit`]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.report)
+    }
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let message = error_paths(&self.report)
+            .into_iter()
+            .map(|(path, error)| format!("{}: {}", path.render(self.syntax), error.message()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        limit::error_response(
+            self.status_code(),
+            format!("Validation errors in fields:\n{}", message),
+            self.truncated,
+            "",
+        )
+    }
+}
+
+/// Garde errors do not have a code, so they are labeled with
+/// [`UNKNOWN_CODE`](crate::metrics::UNKNOWN_CODE)
+#[cfg(feature = "metrics")]
+fn record_metrics<T: ?Sized>(
+    req: &HttpRequest,
+    started: std::time::Instant,
+    result: &Result<(), garde::Report>,
+) {
+    let violations = match result {
+        Ok(()) => Vec::new(),
+        Err(report) => error_paths(report)
+            .into_iter()
+            .map(|(path, _)| (path, None))
+            .collect(),
+    };
+
+    crate::metrics::record(
+        req,
+        std::any::type_name::<T>(),
+        started.elapsed(),
+        violations,
+    );
+}
+
+pub type GardeErrHandler =
+    Arc<dyn Fn(garde::Report, &HttpRequest) -> actix_web::Error + Send + Sync>;
+
+struct GardeErrorHandler {
+    handler: GardeErrHandler,
+}
+
+/// Extension trait to provide a convenience method for adding custom error handler
+pub trait GardeErrorHandlerExt {
+    /// Add a custom error handler for garde validated requests
+    fn garde_error_handler(self, handler: GardeErrHandler) -> Self;
+}
+
+impl<T> GardeErrorHandlerExt for App<T>
+where
+    T: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn garde_error_handler(self, handler: GardeErrHandler) -> Self {
+        self.app_data(GardeErrorHandler { handler })
+    }
+}
+
+impl GardeErrorHandlerExt for &mut actix_web::web::ServiceConfig {
+    fn garde_error_handler(self, handler: GardeErrHandler) -> Self {
+        self.app_data(GardeErrorHandler { handler })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl GardeErrorHandlerExt for crate::test_util::ValidationProbe {
+    fn garde_error_handler(self, handler: GardeErrHandler) -> Self {
+        self.app_data(GardeErrorHandler { handler })
+    }
+}
+
+/// An error handler receiving the rejected value of type `T` along with its errors
+pub type GardeValueErrHandler<T> =
+    Arc<dyn Fn(&T, garde::Report, &HttpRequest) -> actix_web::Error + Send + Sync>;
+
+struct GardeValueErrorHandler<T: ?Sized> {
+    handler: GardeValueErrHandler<T>,
+}
+
+/// Extension trait to provide a convenience method for adding error handlers per validated type
+pub trait GardeValueErrorHandlerExt {
+    /// Add an error handler for requests whose extracted value of type `T` failed validation.
+    /// Takes precedence over the error handler of [`GardeErrorHandlerExt`].
+    fn garde_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: GardeValueErrHandler<T>,
+    ) -> Self;
+}
+
+impl<A> GardeValueErrorHandlerExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn garde_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: GardeValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(GardeValueErrorHandler { handler })
+    }
+}
+
+impl GardeValueErrorHandlerExt for &mut actix_web::web::ServiceConfig {
+    fn garde_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: GardeValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(GardeValueErrorHandler { handler })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl GardeValueErrorHandlerExt for crate::test_util::ValidationProbe {
+    fn garde_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: GardeValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(GardeValueErrorHandler { handler })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::redaction::Redacted;
+    use actix_web::web::Bytes;
+    use actix_web::{http::header::ContentType, post, test, web::Json, App, Responder};
+    use garde::Validate;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    struct ExamplePayload {
+        #[garde(length(min = 5))]
+        name: String,
+    }
+
+    #[post("/")]
+    async fn endpoint(v: Validated<Json<ExamplePayload>>) -> impl Responder {
+        assert!(v.name.len() > 4);
+        HttpResponse::Ok().body(())
+    }
+
+    #[actix_web::test]
+    async fn should_validate_simple() {
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        // Valid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "123456".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        // Invalid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
+
+    #[actix_web::test]
+    async fn should_respond_with_errors_correctly() {
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        // Invalid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\nname: length is lower than 5")
+        );
+    }
+
+    #[actix_web::test]
+    async fn should_redact_messages_of_sensitive_fields() {
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .app_data(RedactionPolicy::new().field("name")),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\nname: [REDACTED]")
+        );
+    }
+
+    #[derive(Debug, Serialize, Error)]
+    struct CustomErrorResponse {
+        custom_message: String,
+        errors: Vec<String>,
+    }
+
+    impl Display for CustomErrorResponse {
+        fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            unimplemented!()
+        }
+    }
+
+    impl ResponseError for CustomErrorResponse {
+        fn status_code(&self) -> actix_web::http::StatusCode {
+            actix_web::http::StatusCode::BAD_REQUEST
+        }
+
+        fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
+            HttpResponse::build(self.status_code()).body(serde_json::to_string(self).unwrap())
+        }
+    }
+
+    fn error_handler(errors: ::garde::Report, _: &HttpRequest) -> actix_web::Error {
+        CustomErrorResponse {
+            custom_message: "My custom message".to_string(),
+            errors: errors.iter().map(|(_, err)| err.to_string()).collect(),
+        }
+        .into()
+    }
+
+    #[actix_web::test]
+    async fn should_use_allow_custom_error_responses() {
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .garde_error_handler(Arc::new(error_handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"{\"custom_message\":\"My custom message\",\"errors\":[\"length is lower than 5\"]}")
+        );
+    }
+
+    #[derive(Debug, Validate)]
+    struct SensitivePayload {
+        #[garde(length(min = 8))]
+        password: Redacted<String>,
+    }
+
+    #[test]
+    async fn should_validate_redacted_values() {
+        let v = SensitivePayload {
+            password: Redacted("hunter2".to_string()),
+        };
+        let report = v.validate().unwrap_err();
+        assert_eq!(report.to_string(), "password: length is lower than 8\n");
+
+        assert_eq!(
+            "Validated(SensitivePayload { password: \"[REDACTED]\" })",
+            format!("{:?}", Validated(v))
+        );
+    }
+
+    #[test]
+    async fn debug_for_validated_should_work() {
+        let v = Validated(ExamplePayload {
+            name: "abcde".to_string(),
+        });
+
+        assert_eq!(
+            "Validated(ExamplePayload { name: \"abcde\" })",
+            format!("{v:?}")
+        );
+    }
+
+    struct Username(String);
+
+    impl TryFrom<ExamplePayload> for Username {
+        type Error = garde::Report;
+
+        fn try_from(payload: ExamplePayload) -> Result<Self, Self::Error> {
+            if payload.name == "admin" {
+                let mut report = garde::Report::new();
+                report.append(garde::Path::new("name"), garde::Error::new("reserved"));
+                return Err(report);
+            }
+            Ok(Username(payload.name))
+        }
+    }
+
+    #[post("/into")]
+    async fn into_endpoint(v: ValidatedInto<Json<ExamplePayload>, Username>) -> impl Responder {
+        v.into_inner().0
+    }
+
+    #[actix_web::test]
+    async fn should_convert_validated_values() {
+        let app = test::init_service(App::new().service(into_endpoint)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/into")
+            .set_json(ExamplePayload {
+                name: "123456".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(result, Bytes::from_static(b"123456"));
+
+        let req = test::TestRequest::post()
+            .uri("/into")
+            .set_json(ExamplePayload {
+                name: "admin".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let result = test::read_body(resp).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\nname: reserved")
+        );
+    }
 
-        let fut = T::from_request(req, payload);
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    struct ManyErrorsPayload {
+        #[garde(inner(length(min = 5)))]
+        tags: Vec<String>,
+    }
 
-        ValidatedFut {
-            fut,
-            error_handler,
-            req: req.clone(),
-        }
+    #[post("/many")]
+    async fn many_errors_endpoint(_: Validated<Json<ManyErrorsPayload>>) -> impl Responder {
+        HttpResponse::Ok().body(())
     }
-}
 
-#[derive(Error, Debug)]
-struct Error {
-    report: garde::Report,
-}
+    #[actix_web::test]
+    async fn should_limit_reported_errors() {
+        let app = test::init_service(
+            App::new()
+                .service(many_errors_endpoint)
+                .app_data(crate::limit::ErrorLimit::fail_fast()),
+        )
+        .await;
 
-impl From<garde::Report> for Error {
-    fn from(value: garde::Report) -> Self {
-        Self { report: value }
+        let req = test::TestRequest::post()
+            .uri("/many")
+            .set_json(ManyErrorsPayload {
+                tags: vec!["a".to_string(); 100],
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(crate::limit::TRUNCATED_HEADER).unwrap(),
+            "true"
+        );
+        let result = test::read_body(resp).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\ntags[0]: length is lower than 5\n(more errors truncated)")
+        );
     }
-}
 
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.report)
+    #[derive(Default)]
+    struct GroupContext {
+        group: Option<ValidationGroup>,
     }
-}
-
-impl ResponseError for Error {
-    fn error_response(&self) -> HttpResponse {
-        let message = self
-            .report
-            .iter()
-            .map(|(path, error)| format!("{path}: {}", error.message()))
-            .collect::<Vec<_>>()
-            .join("\n");
 
-        HttpResponse::build(StatusCode::BAD_REQUEST)
-            .body(format!("Validation errors in fields:\n{}", message))
+    impl From<ValidationGroup> for GroupContext {
+        fn from(group: ValidationGroup) -> Self {
+            GroupContext { group: Some(group) }
+        }
     }
-}
 
-pub type GardeErrHandler =
-    Arc<dyn Fn(garde::Report, &HttpRequest) -> actix_web::Error + Send + Sync>;
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    #[garde(context(GroupContext))]
+    struct UserPayload {
+        #[garde(custom(required_on_create))]
+        name: Option<String>,
+    }
 
-struct GardeErrorHandler {
-    handler: GardeErrHandler,
-}
+    fn required_on_create(name: &Option<String>, ctx: &GroupContext) -> garde::Result {
+        match (name, ctx.group) {
+            (None, Some(ValidationGroup::Create)) => Err(garde::Error::new("required")),
+            _ => Ok(()),
+        }
+    }
 
-/// Extension trait to provide a convenience method for adding custom error handler
-pub trait GardeErrorHandlerExt {
-    /// Add a custom error handler for garde validated requests
-    fn garde_error_handler(self, handler: GardeErrHandler) -> Self;
-}
+    async fn create_user(_: ValidatedGroup<Json<UserPayload>, Create>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
 
-impl<T> GardeErrorHandlerExt for App<T>
-where
-    T: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
-{
-    fn garde_error_handler(self, handler: GardeErrHandler) -> Self {
-        self.app_data(GardeErrorHandler { handler })
+    async fn upsert_user(_: ValidatedGroup<Json<UserPayload>, ByMethod>) -> HttpResponse {
+        HttpResponse::Ok().finish()
     }
-}
 
-impl GardeErrorHandlerExt for &mut actix_web::web::ServiceConfig {
-    fn garde_error_handler(self, handler: GardeErrHandler) -> Self {
-        self.app_data(GardeErrorHandler { handler })
+    #[actix_web::test]
+    async fn should_validate_groups() {
+        use actix_web::web;
+
+        let app = test::init_service(
+            App::new()
+                .route("/create", web::post().to(create_user))
+                .route("/upsert", web::post().to(upsert_user))
+                .route("/upsert", web::patch().to(upsert_user)),
+        )
+        .await;
+
+        let cases = [
+            (test::TestRequest::post().uri("/create"), 400),
+            (test::TestRequest::post().uri("/upsert"), 400),
+            (test::TestRequest::patch().uri("/upsert"), 200),
+        ];
+        for (req, status) in cases {
+            let req = req.set_json(UserPayload { name: None }).to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status().as_u16(), status);
+        }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use actix_web::web::Bytes;
-    use actix_web::{http::header::ContentType, post, test, web::Json, App, Responder};
-    use garde::Validate;
-    use serde::{Deserialize, Serialize};
+    #[cfg(feature = "strict")]
+    #[actix_web::test]
+    async fn should_reject_unknown_fields() {
+        use crate::strict::StrictFieldsExt;
+
+        let app = test::init_service(
+            App::new()
+                .strict_fields::<ExamplePayload>()
+                .service(endpoint),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(serde_json::json!({ "name": "123456", "tags": [{ "nme": "a" }] }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(
+            test::read_body(resp).await,
+            Bytes::from_static(b"Validation errors in fields:\ntags: unknown field")
+        );
+    }
 
+    #[cfg(feature = "metrics")]
     #[derive(Debug, Deserialize, Serialize, Validate)]
-    struct ExamplePayload {
-        #[garde(length(min = 5))]
-        name: String,
+    struct OrderPayload {
+        #[garde(dive)]
+        items: Vec<ExamplePayload>,
     }
 
-    #[post("/")]
-    async fn endpoint(v: Validated<Json<ExamplePayload>>) -> impl Responder {
-        assert!(v.name.len() > 4);
+    #[cfg(feature = "metrics")]
+    #[post("/orders")]
+    async fn orders_endpoint(_: Validated<Json<OrderPayload>>) -> impl Responder {
         HttpResponse::Ok().body(())
     }
 
+    #[cfg(feature = "metrics")]
     #[actix_web::test]
-    async fn should_validate_simple() {
-        let app = test::init_service(App::new().service(endpoint)).await;
+    async fn should_record_metrics() {
+        use crate::metrics::test::recorded;
+        use crate::metrics::VALIDATION_FAILURES;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
 
-        // Valid request
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let _guard = ::metrics::set_default_local_recorder(&recorder);
+
+        let app = test::init_service(App::new().service(orders_endpoint)).await;
+
+        let short = || ExamplePayload {
+            name: "1234".to_string(),
+        };
         let req = test::TestRequest::post()
-            .uri("/")
-            .insert_header(ContentType::plaintext())
-            .set_json(ExamplePayload {
-                name: "123456".to_string(),
+            .uri("/orders")
+            .set_json(OrderPayload {
+                items: vec![short(), short()],
             })
             .to_request();
         let resp = test::call_service(&app, req).await;
-        assert_eq!(resp.status().as_u16(), 200);
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let metrics = recorded(&snapshotter);
+        let failures = metrics
+            .iter()
+            .find(|(name, _, _)| name == VALIDATION_FAILURES)
+            .unwrap();
+        assert_eq!(
+            failures.1,
+            vec![
+                ("code".to_string(), "invalid".to_string()),
+                ("field".to_string(), "items.name".to_string()),
+                ("route".to_string(), "/orders".to_string()),
+                (
+                    "type".to_string(),
+                    std::any::type_name::<OrderPayload>().to_string()
+                )
+            ]
+        );
+        assert_eq!(failures.2, DebugValue::Counter(2));
+    }
+
+    #[actix_web::test]
+    async fn should_offload_validation() {
+        use crate::blocking::{OffloadPolicy, OffloadValidationExt};
+
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .offload_validation::<Validated<Json<ExamplePayload>>>(OffloadPolicy::always()),
+        )
+        .await;
 
-        // Invalid request
         let req = test::TestRequest::post()
             .uri("/")
-            .insert_header(ContentType::plaintext())
             .set_json(ExamplePayload {
-                name: "1234".to_string(),
+                name: "123456".to_string(),
             })
             .to_request();
         let resp = test::call_service(&app, req).await;
-        assert_eq!(resp.status().as_u16(), 400);
-    }
-
-    #[actix_web::test]
-    async fn should_respond_with_errors_correctly() {
-        let app = test::init_service(App::new().service(endpoint)).await;
+        assert_eq!(resp.status().as_u16(), 200);
 
-        // Invalid request
         let req = test::TestRequest::post()
             .uri("/")
-            .insert_header(ContentType::plaintext())
             .set_json(ExamplePayload {
                 name: "1234".to_string(),
             })
@@ -252,68 +1574,245 @@ mod test {
         );
     }
 
-    #[derive(Debug, Serialize, Error)]
-    struct CustomErrorResponse {
-        custom_message: String,
-        errors: Vec<String>,
+    #[cfg(feature = "ws")]
+    #[actix_web::test]
+    async fn should_validate_websocket_messages() {
+        use actix_ws::Message;
+
+        let req = test::TestRequest::default()
+            .app_data(GardeErrorHandler {
+                handler: Arc::new(error_handler),
+            })
+            .to_http_request();
+
+        let msg = Message::Text(r#"{"name":"123456"}"#.into());
+        let payload = validate_message::<ExamplePayload>(&req, &msg);
+        assert_eq!(payload.unwrap().unwrap().name, "123456");
+
+        // Rejected messages use the registered error handler
+        let msg = Message::Binary(Bytes::from_static(br#"{"name":"1234"}"#));
+        let frame = validate_message::<ExamplePayload>(&req, &msg)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(frame.status().as_u16(), 400);
+        assert_eq!(
+            frame.text(),
+            "{\"custom_message\":\"My custom message\",\"errors\":[\"length is lower than 5\"]}"
+        );
+
+        let msg = Message::Text("not json".into());
+        let frame = validate_message::<ExamplePayload>(&req, &msg)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(frame.status().as_u16(), 400);
+
+        let msg = Message::Ping(Bytes::new());
+        assert!(validate_message::<ExamplePayload>(&req, &msg).is_none());
     }
 
-    impl Display for CustomErrorResponse {
-        fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            unimplemented!()
-        }
+    fn sleep(millis: &u64, _: &()) -> garde::Result {
+        std::thread::sleep(std::time::Duration::from_millis(*millis));
+        Ok(())
     }
 
-    impl ResponseError for CustomErrorResponse {
-        fn status_code(&self) -> actix_web::http::StatusCode {
-            actix_web::http::StatusCode::BAD_REQUEST
-        }
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    struct SlowPayload {
+        #[garde(custom(sleep))]
+        millis: u64,
+    }
 
-        fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
-            HttpResponse::build(self.status_code()).body(serde_json::to_string(self).unwrap())
+    #[actix_web::test]
+    async fn should_time_out_slow_validation() {
+        use crate::timeout::{ValidationTimeout, ValidationTimeoutExt};
+        use std::time::Duration;
+
+        // Registering a timeout is enough, validation is offloaded so it can be interrupted
+        let app = test::init_service(
+            App::new()
+                .route(
+                    "/slow",
+                    actix_web::web::post().to(|_: Validated<Json<SlowPayload>>| async {
+                        HttpResponse::Ok().finish()
+                    }),
+                )
+                .validation_timeout::<Validated<Json<SlowPayload>>>(
+                    ValidationTimeout::new(Duration::from_millis(50)).unprocessable_entity(),
+                ),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/slow")
+            .set_json(SlowPayload { millis: 0 })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        let req = test::TestRequest::post()
+            .uri("/slow")
+            .set_json(SlowPayload { millis: 500 })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+    }
+
+    #[cfg(feature = "patch")]
+    async fn patch_endpoint(v: ValidatedPatch<ExamplePayload>) -> impl Responder {
+        HttpResponse::Ok().body(v.into_inner().name)
+    }
+
+    #[cfg(feature = "patch")]
+    #[actix_web::test]
+    async fn should_validate_patched_values() {
+        use crate::patch::{PatchLoaderExt, JSON_PATCH};
+        use actix_web::{http::header::CONTENT_TYPE, web};
+        use serde_json::json;
+
+        let app = test::init_service(
+            App::new()
+                .patch_loader::<ExamplePayload, _, _>(|_| async {
+                    Ok(ExamplePayload {
+                        name: "123456".to_string(),
+                    })
+                })
+                .route("/", web::patch().to(patch_endpoint)),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(json!({ "name": "abcdef" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+        assert_eq!(test::read_body(resp).await, Bytes::from_static(b"abcdef"));
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, JSON_PATCH))
+            .set_payload(r#"[{"op": "replace", "path": "/name", "value": "abc"}]"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("(patch path: /name)"), "{body}");
+    }
+
+    #[cfg(feature = "ws")]
+    #[actix_web::test]
+    async fn should_pass_rejected_messages_to_value_error_handlers() {
+        use actix_ws::Message;
+
+        fn value_handler(
+            value: &ExamplePayload,
+            errors: ::garde::Report,
+            _: &HttpRequest,
+        ) -> actix_web::Error {
+            actix_web::error::ErrorBadRequest(format!("{} ({})", value.name, errors.iter().count()))
         }
+
+        let req = test::TestRequest::default()
+            .app_data(GardeErrorHandler {
+                handler: Arc::new(error_handler),
+            })
+            .app_data(GardeValueErrorHandler::<ExamplePayload> {
+                handler: Arc::new(value_handler),
+            })
+            .to_http_request();
+
+        let msg = Message::Text(r#"{"name":"1234"}"#.into());
+        let frame = validate_message::<ExamplePayload>(&req, &msg)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(frame.status().as_u16(), 400);
+        assert_eq!(frame.text(), "1234 (1)");
     }
 
-    fn error_handler(errors: ::garde::Report, _: &HttpRequest) -> actix_web::Error {
-        CustomErrorResponse {
-            custom_message: "My custom message".to_string(),
-            errors: errors.iter().map(|(_, err)| err.to_string()).collect(),
+    #[cfg(feature = "patch")]
+    #[actix_web::test]
+    async fn should_pass_rejected_patches_to_value_error_handlers() {
+        use crate::patch::PatchLoaderExt;
+        use actix_web::web;
+
+        fn value_handler(
+            value: &ExamplePayload,
+            errors: ::garde::Report,
+            _: &HttpRequest,
+        ) -> actix_web::Error {
+            actix_web::error::ErrorBadRequest(format!("{} ({})", value.name, errors.iter().count()))
         }
-        .into()
+
+        let app = test::init_service(
+            App::new()
+                .patch_loader::<ExamplePayload, _, _>(|_| async {
+                    Ok(ExamplePayload {
+                        name: "123456".to_string(),
+                    })
+                })
+                .garde_error_handler(Arc::new(error_handler))
+                .garde_value_error_handler(Arc::new(value_handler))
+                .route("/", web::patch().to(patch_endpoint)),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(serde_json::json!({ "name": "1234" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(test::read_body(resp).await, Bytes::from_static(b"1234 (1)"));
     }
 
+    #[cfg(feature = "spans")]
     #[actix_web::test]
-    async fn should_use_allow_custom_error_responses() {
+    async fn should_report_source_spans() {
+        use crate::spans::{ErrorSpan, SourceSpansExt, Span};
+
+        fn span_handler(report: ::garde::Report, _: &HttpRequest) -> actix_web::Error {
+            let spans = report
+                .iter()
+                .filter_map(|(_, error)| error.span())
+                .map(|span: Span| format!("{span}, offset {}", span.offset))
+                .collect::<Vec<_>>();
+            actix_web::error::ErrorBadRequest(spans.join(","))
+        }
+
         let app = test::init_service(
             App::new()
-                .service(endpoint)
-                .garde_error_handler(Arc::new(error_handler)),
+                .source_spans::<ExamplePayload>()
+                .service(endpoint),
         )
         .await;
 
         let req = test::TestRequest::post()
             .uri("/")
-            .insert_header(ContentType::plaintext())
-            .set_json(ExamplePayload {
-                name: "1234".to_string(),
-            })
+            .insert_header(ContentType::json())
+            .set_payload("{\n  \"name\": \"1234\"\n}")
             .to_request();
-        let result = test::call_and_read_body(&app, req).await;
+        let body = test::call_and_read_body(&app, req).await;
         assert_eq!(
-            result,
-            Bytes::from_static(b"{\"custom_message\":\"My custom message\",\"errors\":[\"length is lower than 5\"]}")
+            body,
+            Bytes::from_static(
+                b"Validation errors in fields:\nname: length is lower than 5 (line 2, column 11, offset 12)"
+            )
         );
-    }
 
-    #[test]
-    async fn debug_for_validated_should_work() {
-        let v = Validated(ExamplePayload {
-            name: "abcde".to_string(),
-        });
+        let app = test::init_service(
+            App::new()
+                .source_spans::<ExamplePayload>()
+                .garde_error_handler(Arc::new(span_handler))
+                .service(endpoint),
+        )
+        .await;
 
-        assert_eq!(
-            "Validated(ExamplePayload { name: \"abcde\" })",
-            format!("{v:?}")
-        );
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_payload("{\n  \"name\": \"1234\"\n}")
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, Bytes::from_static(b"line 2, column 11, offset 12"));
     }
 }
@@ -0,0 +1,166 @@
+//! Validation groups, applying different rules to the same type depending on the endpoint.
+//!
+//! A DTO is often shared by a create endpoint (every field required) and an update endpoint
+//! (only the supplied fields are validated). The group marker of `ValidatedGroup` selects the
+//! rules:
+//!
+//! ```ignore
+//! use actix_web_validation::group::{ByMethod, Create, Update};
+//!
+//! async fn create(user: ValidatedGroup<Json<User>, Create>) -> impl Responder { .. }
+//! async fn update(user: ValidatedGroup<Json<User>, Update>) -> impl Responder { .. }
+//! // Create for POST and PUT requests, Update for PATCH requests
+//! async fn upsert(user: ValidatedGroup<Json<User>, ByMethod>) -> impl Responder { .. }
+//! ```
+//!
+//! `Validated<T>` applies the regular rules of the type, like `ValidatedGroup<T, All>`. How the
+//! other groups are validated depends on the backend:
+//!
+//! * `validator` and `custom`: the type implements the `ValidateGroup` trait of the backend
+//! * `garde`: the context of the type implements `From<ValidationGroup>`, so rules can read the
+//!   group from the context
+//!
+//! The groups used by [`ByMethod`] can be configured with [`MethodGroups`]:
+//!
+//! ```
+//! use actix_web::{http::Method, App};
+//! use actix_web_validation::group::{MethodGroups, ValidationGroup};
+//!
+//! let app = App::new().app_data(MethodGroups::default().method(Method::PUT, ValidationGroup::Update));
+//! ```
+
+use actix_web::http::Method;
+use actix_web::HttpRequest;
+
+/// The validation group selected for a request
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationGroup {
+    /// The regular rules of the type
+    All,
+    Create,
+    Update,
+}
+
+/// A validation group marker of `ValidatedGroup`
+pub trait Group: 'static {
+    /// The validation group of the request
+    fn group(req: &HttpRequest) -> ValidationGroup;
+}
+
+/// Applies the regular rules of the type (the default group)
+pub struct All;
+
+/// Applies the rules of [`ValidationGroup::Create`]
+pub struct Create;
+
+/// Applies the rules of [`ValidationGroup::Update`]
+pub struct Update;
+
+/// Selects the group from the request method, see [`MethodGroups`]
+pub struct ByMethod;
+
+impl Group for All {
+    fn group(_: &HttpRequest) -> ValidationGroup {
+        ValidationGroup::All
+    }
+}
+
+impl Group for Create {
+    fn group(_: &HttpRequest) -> ValidationGroup {
+        ValidationGroup::Create
+    }
+}
+
+impl Group for Update {
+    fn group(_: &HttpRequest) -> ValidationGroup {
+        ValidationGroup::Update
+    }
+}
+
+impl Group for ByMethod {
+    fn group(req: &HttpRequest) -> ValidationGroup {
+        match req.app_data::<MethodGroups>() {
+            Some(groups) => groups.group(req.method()),
+            None => MethodGroups::default().group(req.method()),
+        }
+    }
+}
+
+/// The validation groups selected by [`ByMethod`].
+///
+/// Defaults to [`ValidationGroup::Create`] for `POST` and `PUT` requests,
+/// [`ValidationGroup::Update`] for `PATCH` requests and [`ValidationGroup::All`] otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodGroups {
+    methods: Vec<(Method, ValidationGroup)>,
+    fallback: ValidationGroup,
+}
+
+impl Default for MethodGroups {
+    fn default() -> Self {
+        Self {
+            methods: vec![
+                (Method::POST, ValidationGroup::Create),
+                (Method::PUT, ValidationGroup::Create),
+                (Method::PATCH, ValidationGroup::Update),
+            ],
+            fallback: ValidationGroup::All,
+        }
+    }
+}
+
+impl MethodGroups {
+    /// Validates requests with `method` with `group`
+    pub fn method(mut self, method: Method, group: ValidationGroup) -> Self {
+        self.methods.retain(|(m, _)| *m != method);
+        self.methods.push((method, group));
+        self
+    }
+
+    /// The group of requests with any other method
+    pub fn fallback(self, group: ValidationGroup) -> Self {
+        Self {
+            fallback: group,
+            ..self
+        }
+    }
+
+    pub fn group(&self, method: &Method) -> ValidationGroup {
+        self.methods
+            .iter()
+            .find(|(m, _)| m == method)
+            .map_or(self.fallback, |(_, group)| *group)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn should_select_groups_by_method() {
+        let groups = MethodGroups::default();
+        assert_eq!(groups.group(&Method::POST), ValidationGroup::Create);
+        assert_eq!(groups.group(&Method::PATCH), ValidationGroup::Update);
+        assert_eq!(groups.group(&Method::GET), ValidationGroup::All);
+
+        let groups = MethodGroups::default()
+            .method(Method::PUT, ValidationGroup::Update)
+            .fallback(ValidationGroup::Create);
+        assert_eq!(groups.group(&Method::PUT), ValidationGroup::Update);
+        assert_eq!(groups.group(&Method::DELETE), ValidationGroup::Create);
+    }
+
+    #[test]
+    fn should_read_method_groups_from_app_data() {
+        let req = TestRequest::put().to_http_request();
+        assert_eq!(ByMethod::group(&req), ValidationGroup::Create);
+
+        let req = TestRequest::put()
+            .app_data(MethodGroups::default().method(Method::PUT, ValidationGroup::Update))
+            .to_http_request();
+        assert_eq!(ByMethod::group(&req), ValidationGroup::Update);
+        assert_eq!(Update::group(&req), ValidationGroup::Update);
+    }
+}
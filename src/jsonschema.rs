@@ -0,0 +1,847 @@
+//! Validation using [JSON Schema](https://json-schema.org) via the [jsonschema](https://docs.rs/jsonschema/latest/jsonschema) crate.
+//! Requires the `jsonschema` feature flag
+//!
+//! Unlike the other backends, the raw JSON body is validated against the schema *before* it is
+//! deserialized, so existing schemas can be enforced without re-encoding them as validation
+//! attributes. Only [`Json`] extractors are supported.
+//!
+//! For usage examples, see the documentation for [`Validated`]
+//!
+
+use crate::limit;
+use crate::path::{FieldPath, PathSegment, PathSyntax};
+use crate::redaction::{RedactionPolicy, REDACTED};
+use crate::validated_definition;
+use actix_web::dev::{ServiceFactory, ServiceRequest};
+use actix_web::error::{ErrorInternalServerError, JsonPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::web::Json;
+use actix_web::FromRequest;
+use actix_web::{App, HttpRequest, HttpResponse, ResponseError};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::{fmt::Debug, pin::Pin, task::Poll};
+use thiserror::Error;
+
+/// A trait that associates a JSON Schema with a type.
+///
+/// The schema is compiled once per type and cached for the lifetime of the process.
+pub trait JsonSchema {
+    /// The schema that request bodies must conform to
+    fn json_schema() -> Value;
+}
+
+/// A validated extactor.
+///
+/// This type will validate the JSON body against the schema of `T` before deserializing it.
+///
+/// ```
+/// use actix_web::{post, web::{self, Json}, App};
+/// use serde::Deserialize;
+/// use serde_json::{json, Value};
+/// use actix_web_validation::jsonschema::{JsonSchema, Validated};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// impl JsonSchema for Info {
+///     fn json_schema() -> Value {
+///         json!({
+///             "type": "object",
+///             "properties": {
+///                 "username": { "type": "string", "minLength": 3 }
+///             },
+///             "required": ["username"]
+///         })
+///     }
+/// }
+///
+/// #[post("/")]
+/// async fn index(info: Validated<Json<Info>>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+/// ```
+pub struct Validated<T>(pub T);
+
+validated_definition!(Validated);
+
+/// Future that extracts and validates JSON bodies using the Actix Web [`FromRequest`] trait
+///
+/// End users of this library should not need to use this directly for most usecases
+pub struct ValidatedFut<T> {
+    req: actix_web::HttpRequest,
+    fut: <Json<Value> as FromRequest>::Future,
+    error_handler: Option<JsonSchemaErrHandler>,
+    #[cfg(feature = "spans")]
+    spans: Option<Box<dyn FnOnce() -> crate::spans::SpanMap>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Future for ValidatedFut<T>
+where
+    T: JsonSchema + DeserializeOwned + 'static,
+{
+    type Output = Result<Validated<Json<T>>, actix_web::Error>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let Poll::Ready(res) = Pin::new(&mut this.fut).poll(cx) else {
+            return std::task::Poll::Pending;
+        };
+
+        let res = match res {
+            Ok(Json(body)) => match compiled_schema::<T>() {
+                Ok(schema) => {
+                    #[cfg(feature = "metrics")]
+                    let started = std::time::Instant::now();
+
+                    // Evaluate one error past the limit to know whether errors were dropped
+                    let max = limit::max_errors(&this.req).unwrap_or(usize::MAX);
+                    let policy = this.req.app_data::<RedactionPolicy>();
+                    let mut errors = {
+                        let mut violations = schema.iter_errors(&body);
+                        let errors = violations
+                            .by_ref()
+                            .take(max.saturating_add(1))
+                            .map(|e| SchemaError::new(&e, policy))
+                            .collect::<Vec<_>>();
+
+                        // Errors past the limit are still counted
+                        #[cfg(feature = "metrics")]
+                        record_metrics::<T>(&this.req, started, &errors, violations);
+
+                        errors
+                    };
+
+                    #[cfg(feature = "spans")]
+                    if let Some(spans) = this.spans.take().filter(|_| !errors.is_empty()) {
+                        let spans = spans();
+                        for error in &mut errors {
+                            error.span = spans.get(&error.path);
+                        }
+                    }
+
+                    let truncated = errors.len() > max;
+                    if truncated {
+                        errors.truncate(max);
+                        limit::mark_truncated(&this.req);
+                    }
+
+                    if !errors.is_empty() {
+                        if let Some(error_handler) = &this.error_handler {
+                            Err((*error_handler)(errors, &this.req))
+                        } else {
+                            Err(Error {
+                                errors,
+                                truncated,
+                                syntax: crate::path::syntax(&this.req),
+                            }
+                            .into())
+                        }
+                    } else {
+                        serde_json::from_value(body)
+                            .map(|data| Validated(Json(data)))
+                            .map_err(|e| JsonPayloadError::Deserialize(e).into())
+                    }
+                }
+                Err(e) => Err(ErrorInternalServerError(e)),
+            },
+            Err(e) => Err(e),
+        };
+
+        Poll::Ready(res)
+    }
+}
+
+impl<T> FromRequest for Validated<Json<T>>
+where
+    T: JsonSchema + DeserializeOwned + 'static,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedFut<T>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let error_handler = req
+            .app_data::<JsonSchemaErrorHandler>()
+            .map(|h| h.handler.clone());
+
+        #[cfg(feature = "spans")]
+        let spans = crate::spans::record::<T>(req, payload);
+        let fut = Json::<Value>::from_request(req, payload);
+
+        ValidatedFut {
+            fut,
+            error_handler,
+            #[cfg(feature = "spans")]
+            spans,
+            req: req.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+type CompiledSchema = Result<Arc<::jsonschema::Validator>, String>;
+
+/// Returns the compiled schema for `T`, compiling and caching it on first use
+fn compiled_schema<T: JsonSchema + 'static>() -> CompiledSchema {
+    static SCHEMAS: OnceLock<RwLock<HashMap<TypeId, CompiledSchema>>> = OnceLock::new();
+
+    let schemas = SCHEMAS.get_or_init(Default::default);
+    let key = TypeId::of::<T>();
+
+    if let Some(schema) = schemas.read().unwrap().get(&key) {
+        return schema.clone();
+    }
+
+    let schema = ::jsonschema::validator_for(&T::json_schema())
+        .map(Arc::new)
+        .map_err(|e| {
+            format!(
+                "invalid JSON schema for {}: {e}",
+                std::any::type_name::<T>()
+            )
+        });
+    schemas.write().unwrap().insert(key, schema.clone());
+    schema
+}
+
+/// A JSON Schema violation
+#[derive(Debug, Clone)]
+pub struct SchemaError {
+    pointer: String,
+    path: FieldPath,
+    keyword: String,
+    message: String,
+    #[cfg(feature = "spans")]
+    span: Option<crate::spans::Span>,
+}
+
+impl SchemaError {
+    fn new(error: &::jsonschema::ValidationError, policy: Option<&RedactionPolicy>) -> Self {
+        let pointer = pointer(error);
+        let path = field_path(&pointer, error.schema_path.as_str());
+
+        let redacted = policy.is_some_and(|p| p.is_redacted(&path));
+        let message = if redacted {
+            error.masked_with(REDACTED).to_string()
+        } else {
+            error.to_string()
+        };
+
+        Self {
+            pointer,
+            path,
+            keyword: keyword(error),
+            message,
+            #[cfg(feature = "spans")]
+            span: None,
+        }
+    }
+
+    /// The [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) to the invalid value (eg. `/items/0/name`)
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// The path to the invalid value using the same notation as the other backends (eg. `items[0].name`)
+    pub fn path(&self) -> String {
+        self.field_path().to_string()
+    }
+
+    /// The structured path to the invalid value, see [`crate::path`].
+    ///
+    /// Object keys matched by `additionalProperties` or `patternProperties` are
+    /// [`Key`](crate::path::PathSegment::Key) segments.
+    pub fn field_path(&self) -> FieldPath {
+        self.path.clone()
+    }
+
+    /// The schema keyword that failed (eg. `minLength`)
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The location of the invalid value in the request body, see [`crate::spans`]
+    #[cfg(feature = "spans")]
+    pub fn span(&self) -> Option<crate::spans::Span> {
+        self.span
+    }
+}
+
+/// The JSON Pointer to the invalid value of `error`
+fn pointer(error: &::jsonschema::ValidationError) -> String {
+    let mut pointer = error.instance_path.to_string();
+    if let ::jsonschema::error::ValidationErrorKind::Required { property } = &error.kind {
+        // Point at the missing property rather than its parent object
+        if let Some(property) = property.as_str() {
+            pointer.push('/');
+            pointer.push_str(&property.replace('~', "~0").replace('/', "~1"));
+        }
+    }
+    pointer
+}
+
+/// The schema keyword that `error` failed
+fn keyword(error: &::jsonschema::ValidationError) -> String {
+    error
+        .schema_path
+        .as_str()
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The structured path of the value at `pointer`, telling map keys apart from fields by the
+/// keywords of `schema_path` that descended into each segment
+fn field_path(pointer: &str, schema_path: &str) -> FieldPath {
+    enum Descent {
+        Property,
+        Key,
+        Item,
+    }
+
+    let mut descents = Vec::new();
+    let mut keywords = schema_path.split('/').skip(1);
+    while let Some(keyword) = keywords.next() {
+        match keyword {
+            "properties" => {
+                keywords.next();
+                descents.push(Descent::Property);
+            }
+            "patternProperties" => {
+                keywords.next();
+                descents.push(Descent::Key);
+            }
+            "additionalProperties" | "unevaluatedProperties" => descents.push(Descent::Key),
+            "prefixItems" => {
+                keywords.next();
+                descents.push(Descent::Item);
+            }
+            "items" | "additionalItems" | "unevaluatedItems" => descents.push(Descent::Item),
+            // Skip names of subschemas that could be mistaken for keywords
+            "$defs" | "definitions" | "dependentSchemas" | "dependencies" => {
+                keywords.next();
+            }
+            _ => {}
+        }
+    }
+
+    let mut descents = descents.into_iter();
+    let mut path = FieldPath::new();
+    for segment in FieldPath::from_pointer(pointer).segments() {
+        let name = match segment {
+            PathSegment::Field(name) | PathSegment::Key(name) => name.clone(),
+            PathSegment::Index(index) => index.to_string(),
+        };
+        path.push(match (descents.next(), segment) {
+            (Some(Descent::Key), _) => PathSegment::Key(name),
+            (Some(Descent::Property), _) => PathSegment::Field(name),
+            _ => segment.clone(),
+        });
+    }
+    path
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The error returned when validation fails and no error handler is registered.
+///
+/// Middleware can recognise validation failures with [`actix_web::Error::as_error`] and read
+/// the report, eg. to log or reformat it:
+///
+/// ```ignore
+/// if let Some(error) = res.response().error().and_then(|e| e.as_error::<actix_web_validation::jsonschema::Error>()) {
+///     log::warn!("validation failed: {}", error);
+/// }
+/// ```
+#[derive(Error, Debug)]
+pub struct Error {
+    errors: Vec<SchemaError>,
+    truncated: bool,
+    syntax: PathSyntax,
+}
+
+impl Error {
+    /// The schema violations, truncated to the registered limit
+    pub fn errors(&self) -> &[SchemaError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<SchemaError> {
+        self.errors
+    }
+
+    /// Whether errors were dropped to respect the limit, see [`crate::limit`]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.errors
+                .iter()
+                .map(|e| e.message.as_ref())
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    }
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        limit::error_response(
+            self.status_code(),
+            format!(
+                "Validation errors in fields:\n{}",
+                &self
+                    .errors
+                    .iter()
+                    .map(|err| {
+                        let line = format!("\t{}: {}", err.field_path().render(self.syntax), err);
+                        #[cfg(feature = "spans")]
+                        let line = crate::spans::with_span(line, err);
+                        line
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            self.truncated,
+            "\t",
+        )
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn record_metrics<'a, T: ?Sized>(
+    req: &HttpRequest,
+    started: std::time::Instant,
+    errors: &[SchemaError],
+    dropped: impl Iterator<Item = ::jsonschema::ValidationError<'a>>,
+) {
+    crate::metrics::record(
+        req,
+        std::any::type_name::<T>(),
+        started.elapsed(),
+        errors
+            .iter()
+            .map(|e| (e.field_path(), Some(e.keyword.clone())))
+            .chain(dropped.map(|e| {
+                let path = field_path(&pointer(&e), e.schema_path.as_str());
+                (path, Some(keyword(&e)))
+            })),
+    );
+}
+
+pub type JsonSchemaErrHandler =
+    Arc<dyn Fn(Vec<SchemaError>, &HttpRequest) -> actix_web::Error + Send + Sync>;
+
+struct JsonSchemaErrorHandler {
+    handler: JsonSchemaErrHandler,
+}
+
+/// Extension trait to provide a convenience method for adding custom error handler
+pub trait JsonSchemaErrorHandlerExt {
+    /// Add a custom error handler for JSON Schema validated requests
+    fn jsonschema_error_handler(self, handler: JsonSchemaErrHandler) -> Self;
+}
+
+impl<T> JsonSchemaErrorHandlerExt for App<T>
+where
+    T: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn jsonschema_error_handler(self, handler: JsonSchemaErrHandler) -> Self {
+        self.app_data(JsonSchemaErrorHandler { handler })
+    }
+}
+
+impl JsonSchemaErrorHandlerExt for &mut actix_web::web::ServiceConfig {
+    fn jsonschema_error_handler(self, handler: JsonSchemaErrHandler) -> Self {
+        self.app_data(JsonSchemaErrorHandler { handler })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl JsonSchemaErrorHandlerExt for crate::test_util::ValidationProbe {
+    fn jsonschema_error_handler(self, handler: JsonSchemaErrHandler) -> Self {
+        self.app_data(JsonSchemaErrorHandler { handler })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::web::Bytes;
+    use actix_web::{http::header::ContentType, post, test, App, Responder};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct ExamplePayload {
+        name: String,
+        #[serde(default)]
+        tags: Vec<String>,
+    }
+
+    impl JsonSchema for ExamplePayload {
+        fn json_schema() -> Value {
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "minLength": 5 },
+                    "tags": { "type": "array", "items": { "type": "string", "maxLength": 3 } }
+                },
+                "required": ["name"]
+            })
+        }
+    }
+
+    #[post("/")]
+    async fn endpoint(v: Validated<Json<ExamplePayload>>) -> impl Responder {
+        assert!(v.name.len() > 4);
+        HttpResponse::Ok().body(())
+    }
+
+    #[actix_web::test]
+    async fn should_validate_simple() {
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        // Valid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({ "name": "123456" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        // Invalid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({ "name": "1234" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
+
+    #[actix_web::test]
+    async fn should_respond_with_errors_correctly() {
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({ "tags": ["ok", "too long"] }))
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(
+                b"Validation errors in fields:\n\ttags[1]: \"too long\" is longer than 3 characters\n\tname: \"name\" is a required property"
+            )
+        );
+    }
+
+    #[cfg(feature = "spans")]
+    #[actix_web::test]
+    async fn should_report_source_spans() {
+        use crate::spans::SourceSpansExt;
+
+        fn span_handler(errors: Vec<SchemaError>, _: &HttpRequest) -> actix_web::Error {
+            let spans = errors
+                .iter()
+                .map(|err| match err.span() {
+                    Some(span) => span.to_string(),
+                    None => "none".to_string(),
+                })
+                .collect::<Vec<_>>();
+            actix_web::error::ErrorBadRequest(spans.join(","))
+        }
+
+        let body = "{\n  \"tags\": [\"ok\", \"too long\"]\n}";
+        let app = test::init_service(
+            App::new()
+                .source_spans::<ExamplePayload>()
+                .service(endpoint),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_payload(body)
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(
+                b"Validation errors in fields:\n\ttags[1]: \"too long\" is longer than 3 characters (line 2, column 18)\n\tname: \"name\" is a required property"
+            )
+        );
+
+        // Missing properties have no span
+        let app = test::init_service(
+            App::new()
+                .source_spans::<ExamplePayload>()
+                .jsonschema_error_handler(Arc::new(span_handler))
+                .service(endpoint),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_payload(body)
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(result, Bytes::from_static(b"line 2, column 18,none"));
+    }
+
+    #[actix_web::test]
+    async fn should_redact_sensitive_values() {
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .app_data(RedactionPolicy::new().field("name")),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({ "name": "1234" }))
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(
+                b"Validation errors in fields:\n\tname: [REDACTED] is shorter than 5 characters"
+            )
+        );
+    }
+
+    #[derive(Debug, Serialize, Error)]
+    struct CustomErrorResponse {
+        custom_message: String,
+        errors: Vec<String>,
+    }
+
+    impl Display for CustomErrorResponse {
+        fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            unimplemented!()
+        }
+    }
+
+    impl ResponseError for CustomErrorResponse {
+        fn status_code(&self) -> actix_web::http::StatusCode {
+            actix_web::http::StatusCode::BAD_REQUEST
+        }
+
+        fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
+            HttpResponse::build(self.status_code()).body(serde_json::to_string(self).unwrap())
+        }
+    }
+
+    fn error_handler(errors: Vec<SchemaError>, _: &HttpRequest) -> actix_web::Error {
+        CustomErrorResponse {
+            custom_message: "My custom message".to_string(),
+            errors: errors
+                .iter()
+                .map(|err| format!("{} {}", err.pointer(), err.keyword()))
+                .collect(),
+        }
+        .into()
+    }
+
+    #[actix_web::test]
+    async fn should_use_allow_custom_error_responses() {
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .jsonschema_error_handler(Arc::new(error_handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_json(json!({ "name": "1234" }))
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(
+                b"{\"custom_message\":\"My custom message\",\"errors\":[\"/name minLength\"]}"
+            )
+        );
+    }
+
+    #[test]
+    async fn pointer_should_map_to_path() {
+        assert_eq!(FieldPath::from_pointer("").to_string(), "");
+        assert_eq!(FieldPath::from_pointer("/name").to_string(), "name");
+        assert_eq!(FieldPath::from_pointer("/a/b/0/c").to_string(), "a.b[0].c");
+        assert_eq!(FieldPath::from_pointer("/a~1b/c~0d").to_string(), "a/b.c~d");
+    }
+
+    #[test]
+    async fn should_tell_map_keys_apart_from_fields() {
+        assert_eq!(
+            field_path(
+                "/labels/en/0",
+                "/properties/labels/additionalProperties/items/maxLength"
+            ),
+            FieldPath::new().field("labels").key("en").index(0)
+        );
+        assert_eq!(
+            field_path(
+                "/labels/42/text",
+                "/properties/labels/patternProperties/^[0-9]+$/$ref/properties/text/type"
+            ),
+            FieldPath::new().field("labels").key("42").field("text")
+        );
+        assert_eq!(
+            field_path("/0", "/properties/0/type"),
+            FieldPath::new().field("0")
+        );
+    }
+
+    #[test]
+    async fn debug_for_validated_should_work() {
+        let v = Validated(ExamplePayload {
+            name: "abcde".to_string(),
+            tags: vec![],
+        });
+
+        assert_eq!(
+            "Validated(ExamplePayload { name: \"abcde\", tags: [] })",
+            format!("{v:?}")
+        );
+    }
+
+    #[actix_web::test]
+    async fn should_limit_reported_errors() {
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .app_data(crate::limit::ErrorLimit::fail_fast()),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({ "tags": ["ok", "too long"] }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(crate::limit::TRUNCATED_HEADER).unwrap(),
+            "true"
+        );
+        let result = test::read_body(resp).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(
+                b"Validation errors in fields:\n\ttags[1]: \"too long\" is longer than 3 characters\n\t(more errors truncated)"
+            )
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[actix_web::test]
+    async fn should_record_metrics_of_truncated_errors() {
+        use crate::metrics::test::recorded;
+        use crate::metrics::VALIDATION_FAILURES;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Translations {
+            labels: HashMap<String, String>,
+        }
+
+        impl JsonSchema for Translations {
+            fn json_schema() -> Value {
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "labels": {
+                            "type": "object",
+                            "additionalProperties": { "type": "string", "maxLength": 3 }
+                        }
+                    }
+                })
+            }
+        }
+
+        async fn translations(_: Validated<Json<Translations>>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let _guard = ::metrics::set_default_local_recorder(&recorder);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(crate::limit::ErrorLimit::fail_fast())
+                .route("/", actix_web::web::post().to(translations)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({ "labels": { "en": "abcd", "de": "abcd", "fr": "abcd" } }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let metrics = recorded(&snapshotter);
+        let failures = metrics
+            .iter()
+            .filter(|(name, _, _)| name == VALIDATION_FAILURES)
+            .collect::<Vec<_>>();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].1,
+            vec![
+                ("code".to_string(), "maxLength".to_string()),
+                ("field".to_string(), "labels".to_string()),
+                ("route".to_string(), "/".to_string()),
+                (
+                    "type".to_string(),
+                    std::any::type_name::<Translations>().to_string()
+                )
+            ]
+        );
+        assert_eq!(failures[0].2, DebugValue::Counter(3));
+    }
+}
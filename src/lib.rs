@@ -1,30 +1,100 @@
 #![doc = include_str!("../README.md")]
 
+// Allows the derive macros to refer to `::actix_web_validation` from within this crate
+extern crate self as actix_web_validation;
+
+pub mod blocking;
+#[cfg(any(feature = "strict", feature = "spans"))]
+mod body;
 #[cfg(feature = "custom")]
 pub mod custom;
+pub mod field_names;
 #[cfg(feature = "garde")]
 pub mod garde;
+pub mod group;
+#[cfg(feature = "jsonschema")]
+pub mod jsonschema;
+pub mod limit;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod optional;
+#[cfg(feature = "patch")]
+pub mod patch;
+pub mod path;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+pub mod redaction;
+pub mod rules;
+pub mod sanitize;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(feature = "spans")]
+pub mod spans;
+#[cfg(feature = "strict")]
+pub mod strict;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod timeout;
+#[cfg(feature = "utoipa")]
+pub mod utoipa;
 #[cfg(feature = "validator")]
 pub mod validator;
+#[cfg(feature = "ws")]
+pub mod ws;
+
+#[cfg(all(
+    feature = "validator",
+    not(feature = "garde"),
+    not(feature = "custom"),
+    not(feature = "jsonschema")
+))]
+pub use crate::validator::{
+    OptionalValidated, Rejected, Validated, ValidatedGroup, ValidatedInto, ValidationResult,
+};
 
-#[cfg(all(feature = "validator", not(feature = "garde"), not(feature = "custom")))]
-pub use crate::validator::Validated;
+#[cfg(all(
+    feature = "garde",
+    not(feature = "validator"),
+    not(feature = "custom"),
+    not(feature = "jsonschema")
+))]
+pub use crate::garde::{
+    OptionalValidated, Rejected, Validated, ValidatedGroup, ValidatedInto, ValidationResult,
+};
 
-#[cfg(all(feature = "garde", not(feature = "validator"), not(feature = "custom")))]
-pub use crate::garde::Validated;
+#[cfg(all(
+    feature = "custom",
+    not(feature = "validator"),
+    not(feature = "garde"),
+    not(feature = "jsonschema")
+))]
+pub use crate::custom::{
+    OptionalValidated, Rejected, Validated, ValidatedGroup, ValidatedInto, ValidationResult,
+};
 
-#[cfg(all(feature = "custom", not(feature = "validator"), not(feature = "garde")))]
-pub use crate::custom::Validated;
+#[cfg(all(
+    feature = "jsonschema",
+    not(feature = "validator"),
+    not(feature = "garde"),
+    not(feature = "custom")
+))]
+pub use crate::jsonschema::Validated;
 
+#[cfg(any(
+    feature = "validator",
+    feature = "garde",
+    feature = "custom",
+    feature = "jsonschema"
+))]
 macro_rules! validated_definition {
-    () => {
-        impl<T> Validated<T> {
+    ($validated:ident $(<$group:ident>)?) => {
+        impl<T $(, $group)?> $validated<T $(, $group)?> {
             pub fn into_inner(self) -> T {
                 self.0
             }
         }
 
-        impl<T> std::ops::Deref for Validated<T> {
+        impl<T $(, $group)?> std::ops::Deref for $validated<T $(, $group)?> {
             type Target = T;
 
             fn deref(&self) -> &Self::Target {
@@ -32,22 +102,165 @@ macro_rules! validated_definition {
             }
         }
 
-        impl<T> std::ops::DerefMut for Validated<T> {
+        impl<T $(, $group)?> std::ops::DerefMut for $validated<T $(, $group)?> {
             fn deref_mut(&mut self) -> &mut Self::Target {
                 &mut self.0
             }
         }
 
-        impl<T> Debug for Validated<T>
+        impl<T $(, $group)?> Debug for $validated<T $(, $group)?>
         where
             T: Debug,
         {
             #[inline]
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                f.debug_tuple("Validated").field(&self.0).finish()
+                f.debug_tuple(stringify!($validated)).field(&self.0).finish()
             }
         }
+
+        #[cfg(feature = "utoipa")]
+        $crate::utoipa::validated_openapi_definition!($validated $(<$group>)?);
     };
 }
 
+#[cfg(any(
+    feature = "validator",
+    feature = "garde",
+    feature = "custom",
+    feature = "jsonschema"
+))]
 pub(crate) use validated_definition;
+
+/// An extractor wrapping a single value, like [`Json`](actix_web::web::Json) or
+/// [`Query`](actix_web::web::Query).
+///
+/// Used by `ValidatedInto` to take the validated value out of the extractor.
+pub trait IntoInner {
+    type Inner;
+
+    fn into_inner(self) -> Self::Inner;
+}
+
+macro_rules! into_inner_impl {
+    ($($extractor:ident),*) => {
+        $(
+            impl<T> IntoInner for actix_web::web::$extractor<T> {
+                type Inner = T;
+
+                fn into_inner(self) -> T {
+                    self.into_inner()
+                }
+            }
+        )*
+    };
+}
+
+into_inner_impl!(Json, Query, Path, Form);
+
+#[cfg(any(feature = "validator", feature = "garde", feature = "custom"))]
+macro_rules! validated_into_definition {
+    () => {
+        impl<T, D> ValidatedInto<T, D> {
+            fn new(inner: D) -> Self {
+                Self {
+                    inner,
+                    _extractor: std::marker::PhantomData,
+                }
+            }
+
+            pub fn into_inner(self) -> D {
+                self.inner
+            }
+        }
+
+        impl<T, D> std::ops::Deref for ValidatedInto<T, D> {
+            type Target = D;
+
+            fn deref(&self) -> &Self::Target {
+                &self.inner
+            }
+        }
+
+        impl<T, D> std::ops::DerefMut for ValidatedInto<T, D> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.inner
+            }
+        }
+
+        impl<T, D> Debug for ValidatedInto<T, D>
+        where
+            D: Debug,
+        {
+            #[inline]
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple("ValidatedInto").field(&self.inner).finish()
+            }
+        }
+
+        pin_project_lite::pin_project! {
+            /// Future that extracts, validates and converts actix requests for [`ValidatedInto`]
+            pub struct ValidatedIntoFut<T: FromRequest, D> {
+                #[pin]
+                fut: ValidatedFut<T>,
+                _domain: std::marker::PhantomData<fn() -> D>,
+            }
+        }
+    };
+}
+
+#[cfg(any(feature = "validator", feature = "garde", feature = "custom"))]
+pub(crate) use validated_into_definition;
+
+#[cfg(all(
+    feature = "patch",
+    any(feature = "validator", feature = "garde", feature = "custom")
+))]
+macro_rules! validated_patch_definition {
+    () => {
+        impl<T> ValidatedPatch<T> {
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T> std::ops::Deref for ValidatedPatch<T> {
+            type Target = T;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<T> std::ops::DerefMut for ValidatedPatch<T> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl<T> Debug for ValidatedPatch<T>
+        where
+            T: Debug,
+        {
+            #[inline]
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple("ValidatedPatch").field(&self.0).finish()
+            }
+        }
+
+        #[cfg(feature = "utoipa")]
+        impl<T> ::utoipa::IntoResponses for ValidatedPatch<T> {
+            fn responses() -> std::collections::BTreeMap<
+                String,
+                ::utoipa::openapi::RefOr<::utoipa::openapi::response::Response>,
+            > {
+                $crate::utoipa::patch_error_responses()
+            }
+        }
+    };
+}
+
+#[cfg(all(
+    feature = "patch",
+    any(feature = "validator", feature = "garde", feature = "custom")
+))]
+pub(crate) use validated_patch_definition;
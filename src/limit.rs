@@ -0,0 +1,128 @@
+//! Limits on the number of reported validation errors.
+//!
+//! By default every violation is reported, so adversarial payloads (eg. thousands of invalid
+//! array items) can produce enormous error responses. Register an [`ErrorLimit`] as app data to
+//! cap the number of errors, or to only report the first one:
+//!
+//! ```
+//! use actix_web::App;
+//! use actix_web_validation::limit::ErrorLimit;
+//!
+//! let app = App::new().app_data(ErrorLimit::max_errors(20));
+//! // or
+//! let app = App::new().app_data(ErrorLimit::fail_fast());
+//! ```
+//!
+//! The limit is applied by every backend before the errors reach the built-in error responses
+//! or a custom error handler. When errors were dropped, the built-in responses end with a
+//! truncation notice and carry the [`TRUNCATED_HEADER`] header, and [`is_truncated`] returns
+//! `true` for the request so custom error handlers can report it too.
+//!
+//! The `jsonschema` backend stops evaluating the schema once the limit is exceeded. The other
+//! backends validate the whole value and drop the extra errors.
+
+use actix_web::{HttpMessage, HttpRequest};
+
+/// The header set to `true` on built-in error responses when errors were dropped
+pub const TRUNCATED_HEADER: &str = "x-validation-errors-truncated";
+
+/// The maximum number of validation errors reported per request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorLimit {
+    max_errors: usize,
+}
+
+impl ErrorLimit {
+    /// Only report the first validation error
+    pub fn fail_fast() -> Self {
+        Self::max_errors(1)
+    }
+
+    /// Report at most `max_errors` validation errors (at least 1)
+    pub fn max_errors(max_errors: usize) -> Self {
+        Self {
+            max_errors: max_errors.max(1),
+        }
+    }
+
+    pub fn get(&self) -> usize {
+        self.max_errors
+    }
+}
+
+/// Marks a request whose validation errors were truncated
+#[derive(Clone, Copy)]
+struct Truncated;
+
+/// Returns `true` when validation errors of the request were dropped because of the [`ErrorLimit`]
+pub fn is_truncated(req: &HttpRequest) -> bool {
+    req.extensions().contains::<Truncated>()
+}
+
+/// The max number of errors configured for the request, if any
+#[cfg(any(
+    feature = "validator",
+    feature = "garde",
+    feature = "custom",
+    feature = "jsonschema"
+))]
+pub(crate) fn max_errors(req: &HttpRequest) -> Option<usize> {
+    req.app_data::<ErrorLimit>().map(ErrorLimit::get)
+}
+
+#[cfg(any(
+    feature = "validator",
+    feature = "garde",
+    feature = "custom",
+    feature = "jsonschema"
+))]
+pub(crate) fn mark_truncated(req: &HttpRequest) {
+    req.extensions_mut().insert(Truncated);
+}
+
+/// Builds a built-in error response, adding the truncation notice if needed. The notice is
+/// indented with `indent` like the error lines of `body`.
+#[cfg(any(
+    feature = "validator",
+    feature = "garde",
+    feature = "custom",
+    feature = "jsonschema"
+))]
+pub(crate) fn error_response(
+    status: actix_web::http::StatusCode,
+    mut body: String,
+    truncated: bool,
+    indent: &str,
+) -> actix_web::HttpResponse {
+    let mut response = actix_web::HttpResponse::build(status);
+    response.content_type(actix_web::http::header::ContentType::plaintext());
+    if truncated {
+        body.push('\n');
+        body.push_str(indent);
+        body.push_str("(more errors truncated)");
+        response.insert_header((TRUNCATED_HEADER, "true"));
+    }
+    response.body(body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn max_errors_should_be_at_least_one() {
+        assert_eq!(ErrorLimit::max_errors(0).get(), 1);
+        assert_eq!(ErrorLimit::max_errors(5).get(), 5);
+        assert_eq!(ErrorLimit::fail_fast().get(), 1);
+    }
+
+    #[test]
+    fn should_mark_requests_as_truncated() {
+        let req = TestRequest::default().to_http_request();
+        assert!(!is_truncated(&req));
+
+        req.extensions_mut().insert(Truncated);
+        assert!(is_truncated(&req));
+    }
+}
@@ -0,0 +1,336 @@
+//! Validation metrics using the [metrics](https://docs.rs/metrics/latest/metrics) crate.
+//! Requires the `metrics` feature flag
+//!
+//! Metrics are reported to whichever recorder is installed for the `metrics` facade,
+//! so any exporter (Prometheus, StatsD, etc) can be used.
+//!
+//! The following metrics are recorded for every `Validated` extraction that reaches validation:
+//!
+//! | Name | Kind | Labels |
+//! |------|------|--------|
+//! | [`VALIDATION_FAILURES`] | counter | `route`, `type`, `field`, `code` |
+//! | [`VALIDATION_DURATION`] | histogram | `route`, `type` |
+//! | [`VALIDATION_TIMEOUTS`] | counter | `route`, `type` |
+//!
+//! The failure counter is incremented once per violation.
+//! The timeout counter is incremented when validation exceeds its
+//! [`ValidationTimeout`](crate::timeout::ValidationTimeout), no duration is recorded then.
+//! `route` is the matched route pattern (eg. `/users/{id}`) so that path parameters do not
+//! create unbounded label cardinality.
+//!
+//! For the same reason `field` only contains the field segments of the violation's
+//! [path](crate::path): list index and map key segments are dropped, so `items[4711].name` is
+//! reported as `items.name`. Violations of the validated value itself are reported with the
+//! field [`ROOT_FIELD`]. Unknown fields rejected by [`strict`](crate::strict) are named by the
+//! client, so they are all reported with the field [`UNKNOWN_FIELD`].
+//! `code` is the backend's error code, or [`UNKNOWN_CODE`] when the error has none. garde
+//! errors carry no code and are always reported as [`UNKNOWN_CODE`].
+//!
+//! The cardinality of `field` is only bounded when map keys are reported as
+//! [`Key`](crate::path::PathSegment::Key) segments. garde does not distinguish map keys from
+//! struct fields in its paths, so map keys of garde validated types still appear in `field`.
+
+// Only the backends record metrics, `record_timeout` and `record_unknown_fields` are not used by
+// `jsonschema`
+#![cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+
+use crate::path::FieldPath;
+use ::metrics::{counter, histogram};
+use actix_web::HttpRequest;
+use std::time::Duration;
+
+/// Counter of validation violations
+pub const VALIDATION_FAILURES: &str = "actix_web_validation_failures_total";
+
+/// Histogram of the time spent validating an extracted value, in seconds
+pub const VALIDATION_DURATION: &str = "actix_web_validation_duration_seconds";
+
+/// Counter of validations that exceeded their timeout
+pub const VALIDATION_TIMEOUTS: &str = "actix_web_validation_timeouts_total";
+
+/// Label value used when the request did not match a route pattern
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// `field` label of violations of the validated value itself
+pub const ROOT_FIELD: &str = "$";
+
+/// `code` label of violations without an error code
+pub const UNKNOWN_CODE: &str = "invalid";
+
+/// `field` label of unknown fields rejected by [`crate::strict`], whose names are chosen by the
+/// client
+pub const UNKNOWN_FIELD: &str = "<unknown>";
+
+fn route(req: &HttpRequest) -> String {
+    req.match_pattern()
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string())
+}
+
+/// The `field` label of `path`, keeping only its field names
+fn field_label(path: &FieldPath) -> String {
+    let label = path.fields();
+    if label.is_empty() {
+        ROOT_FIELD.to_string()
+    } else {
+        label.to_string()
+    }
+}
+
+/// Record the outcome of a single validation.
+///
+/// `violations` yields the path and code of each violation, and is empty for valid values.
+pub(crate) fn record(
+    req: &HttpRequest,
+    type_name: &'static str,
+    elapsed: Duration,
+    violations: impl IntoIterator<Item = (FieldPath, Option<String>)>,
+) {
+    let route = route(req);
+
+    histogram!(VALIDATION_DURATION, "route" => route.clone(), "type" => type_name)
+        .record(elapsed.as_secs_f64());
+
+    for (field, code) in violations {
+        counter!(
+            VALIDATION_FAILURES,
+            "route" => route.clone(),
+            "type" => type_name,
+            "field" => field_label(&field),
+            "code" => code.unwrap_or_else(|| UNKNOWN_CODE.to_string())
+        )
+        .increment(1);
+    }
+}
+
+/// Record a validation that exceeded its timeout
+pub(crate) fn record_timeout(req: &HttpRequest, type_name: &'static str) {
+    counter!(VALIDATION_TIMEOUTS, "route" => route(req), "type" => type_name).increment(1);
+}
+
+/// Record the unknown fields of a single validation, see [`crate::strict`]
+pub(crate) fn record_unknown_fields(req: &HttpRequest, type_name: &'static str, count: usize) {
+    if count == 0 {
+        return;
+    }
+
+    counter!(
+        VALIDATION_FAILURES,
+        "route" => route(req),
+        "type" => type_name,
+        "field" => UNKNOWN_FIELD,
+        "code" => "unknown_field"
+    )
+    .increment(count as u64);
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+
+    /// Runs `f` with a thread local recorder and returns a snapshotter for the recorded metrics
+    fn with_recorder(f: impl FnOnce()) -> Snapshotter {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        ::metrics::with_local_recorder(&recorder, f);
+        snapshotter
+    }
+
+    pub(crate) type Labels = Vec<(String, String)>;
+
+    /// Returns `(name, labels, value)` for every recorded metric, with labels sorted by key
+    pub(crate) fn recorded(snapshotter: &Snapshotter) -> Vec<(String, Labels, DebugValue)> {
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let (_, key) = key.into_parts();
+                let mut labels = key
+                    .labels()
+                    .map(|l| (l.key().to_string(), l.value().to_string()))
+                    .collect::<Vec<_>>();
+                labels.sort();
+                (key.name().to_string(), labels, value)
+            })
+            .collect()
+    }
+
+    fn labels(pairs: &[(&str, &str)]) -> Labels {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn should_record_duration_and_failures() {
+        let req = TestRequest::default().to_http_request();
+
+        let snapshotter = with_recorder(|| {
+            record(
+                &req,
+                "my::Type",
+                Duration::from_millis(5),
+                vec![
+                    (FieldPath::parse("name"), Some("length".to_string())),
+                    (FieldPath::parse("name"), Some("length".to_string())),
+                    (FieldPath::parse("email"), Some("email".to_string())),
+                ],
+            );
+        });
+
+        let mut metrics = recorded(&snapshotter);
+        metrics.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+        assert_eq!(metrics.len(), 3);
+
+        assert_eq!(metrics[0].0, VALIDATION_DURATION);
+        assert_eq!(
+            metrics[0].1,
+            labels(&[("route", "unmatched"), ("type", "my::Type")])
+        );
+        assert_eq!(metrics[0].2, DebugValue::Histogram(vec![0.005.into()]));
+
+        assert_eq!(metrics[1].0, VALIDATION_FAILURES);
+        assert_eq!(
+            metrics[1].1,
+            labels(&[
+                ("code", "email"),
+                ("field", "email"),
+                ("route", "unmatched"),
+                ("type", "my::Type")
+            ])
+        );
+        assert_eq!(metrics[1].2, DebugValue::Counter(1));
+
+        assert_eq!(
+            metrics[2].1,
+            labels(&[
+                ("code", "length"),
+                ("field", "name"),
+                ("route", "unmatched"),
+                ("type", "my::Type")
+            ])
+        );
+        assert_eq!(metrics[2].2, DebugValue::Counter(2));
+    }
+
+    #[test]
+    fn should_drop_indices_and_map_keys_from_field_labels() {
+        let req = TestRequest::default().to_http_request();
+
+        let snapshotter = with_recorder(|| {
+            record(
+                &req,
+                "my::Type",
+                Duration::from_millis(1),
+                vec![
+                    (FieldPath::parse("items[4711].name"), None),
+                    (FieldPath::parse("items[1].name"), None),
+                    (
+                        FieldPath::new().field("labels").key("en.US"),
+                        Some("length".to_string()),
+                    ),
+                    (FieldPath::new(), Some("schema".to_string())),
+                ],
+            );
+        });
+
+        let mut failures = recorded(&snapshotter)
+            .into_iter()
+            .filter(|(name, _, _)| name == VALIDATION_FAILURES)
+            .map(|(_, labels, value)| (labels, value))
+            .collect::<Vec<_>>();
+        failures.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(failures.len(), 3);
+        assert_eq!(
+            failures[0].0,
+            labels(&[
+                ("code", "invalid"),
+                ("field", "items.name"),
+                ("route", "unmatched"),
+                ("type", "my::Type")
+            ])
+        );
+        assert_eq!(failures[0].1, DebugValue::Counter(2));
+        assert_eq!(
+            failures[1].0,
+            labels(&[
+                ("code", "length"),
+                ("field", "labels"),
+                ("route", "unmatched"),
+                ("type", "my::Type")
+            ])
+        );
+        assert_eq!(
+            failures[2].0,
+            labels(&[
+                ("code", "schema"),
+                ("field", "$"),
+                ("route", "unmatched"),
+                ("type", "my::Type")
+            ])
+        );
+    }
+
+    #[test]
+    fn should_not_record_failures_for_valid_values() {
+        let req = TestRequest::default().to_http_request();
+
+        let snapshotter = with_recorder(|| {
+            record(&req, "my::Type", Duration::from_millis(1), Vec::new());
+        });
+
+        let metrics = recorded(&snapshotter);
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].0, VALIDATION_DURATION);
+    }
+
+    #[test]
+    fn should_record_unknown_fields_under_a_constant_label() {
+        let req = TestRequest::default().to_http_request();
+
+        let snapshotter = with_recorder(|| {
+            record_unknown_fields(&req, "my::Type", 2);
+            record_unknown_fields(&req, "my::Type", 0);
+        });
+
+        let metrics = recorded(&snapshotter);
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].0, VALIDATION_FAILURES);
+        assert_eq!(
+            metrics[0].1,
+            labels(&[
+                ("code", "unknown_field"),
+                ("field", "<unknown>"),
+                ("route", "unmatched"),
+                ("type", "my::Type")
+            ])
+        );
+        assert_eq!(metrics[0].2, DebugValue::Counter(2));
+    }
+
+    #[test]
+    fn should_record_timeouts() {
+        let req = TestRequest::default().to_http_request();
+
+        let snapshotter = with_recorder(|| {
+            record_timeout(&req, "my::Type");
+        });
+
+        let metrics = recorded(&snapshotter);
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].0, VALIDATION_TIMEOUTS);
+        assert_eq!(
+            metrics[0].1,
+            labels(&[("route", "unmatched"), ("type", "my::Type")])
+        );
+        assert_eq!(metrics[0].2, DebugValue::Counter(1));
+    }
+}
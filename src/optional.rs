@@ -0,0 +1,204 @@
+//! Optional inputs for `OptionalValidated`.
+//!
+//! Wrapping `Validated` in actix's `Option` extractor turns every failure into `None`, so an
+//! invalid body is silently ignored. `OptionalValidated` is `None` only when the input is
+//! absent, and present input is validated like `Validated`:
+//!
+//! ```ignore
+//! use actix_web_validation::validator::OptionalValidated;
+//!
+//! #[get("/users")]
+//! async fn list_users(filter: OptionalValidated<Query<UserFilter>>) -> impl Responder {
+//!     // ....
+//! }
+//! ```
+//!
+//! Whether an input is absent is decided by its [`Presence`] implementation. A JSON or form
+//! body without a `Content-Length` (eg. a chunked HTTP/1.1 or an HTTP/2 body) is absent only when
+//! its stream is empty: its first chunk is read ahead and handed back to the inner extractor.
+
+// `Peek` is only used by the `OptionalValidated` of the backends
+#![cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use actix_web::dev::Payload;
+use actix_web::error::PayloadError;
+use actix_web::http::header::{Header as HeaderTrait, CONTENT_LENGTH};
+use actix_web::web::{Bytes, Form, Header, Json, Query};
+use actix_web::HttpRequest;
+use futures_core::Stream;
+
+/// Whether the input of an extractor is in a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Present {
+    Yes,
+    No,
+    /// Present unless the body stream is empty, which the headers can't tell
+    UnlessBodyEmpty,
+}
+
+/// An extractor whose input can be absent from a request
+pub trait Presence {
+    /// Whether the input of the extractor is in `req`
+    fn is_present(req: &HttpRequest) -> Present;
+}
+
+/// Whether `req` has a body: known from its `Content-Length`, otherwise from its stream
+fn has_body(req: &HttpRequest) -> Present {
+    let len = req.headers().get(CONTENT_LENGTH);
+    match len.and_then(|len| len.to_str().ok()?.parse::<u64>().ok()) {
+        Some(0) => Present::No,
+        Some(_) => Present::Yes,
+        None => Present::UnlessBodyEmpty,
+    }
+}
+
+/// Absent without a body
+impl<T> Presence for Json<T> {
+    fn is_present(req: &HttpRequest) -> Present {
+        has_body(req)
+    }
+}
+
+/// Absent without a body
+impl<T> Presence for Form<T> {
+    fn is_present(req: &HttpRequest) -> Present {
+        has_body(req)
+    }
+}
+
+/// Absent with an empty query string
+impl<T> Presence for Query<T> {
+    fn is_present(req: &HttpRequest) -> Present {
+        if req.query_string().is_empty() {
+            Present::No
+        } else {
+            Present::Yes
+        }
+    }
+}
+
+/// Absent without the header
+impl<T: HeaderTrait> Presence for Header<T> {
+    fn is_present(req: &HttpRequest) -> Present {
+        if req.headers().contains_key(T::name()) {
+            Present::Yes
+        } else {
+            Present::No
+        }
+    }
+}
+
+/// Future deciding whether the input of `T` is in a request.
+///
+/// Resolves to the payload to extract `T` from, with any chunk read ahead put back in front.
+pub(crate) struct Peek {
+    present: Option<bool>,
+    payload: Payload,
+}
+
+impl Peek {
+    pub(crate) fn new<T: Presence>(req: &HttpRequest, payload: &mut Payload) -> Self {
+        let present = match T::is_present(req) {
+            Present::Yes => Some(true),
+            Present::No => Some(false),
+            Present::UnlessBodyEmpty => None,
+        };
+        Self {
+            present,
+            payload: payload.take(),
+        }
+    }
+}
+
+impl Future for Peek {
+    type Output = (bool, Payload);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while this.present.is_none() {
+            match ready!(Pin::new(&mut this.payload).poll_next(cx)) {
+                Some(Ok(chunk)) if chunk.is_empty() => {}
+                Some(chunk) => {
+                    let rest = this.payload.take();
+                    this.payload = Payload::Stream {
+                        payload: Box::pin(ReadAhead {
+                            chunk: Some(chunk),
+                            rest,
+                        }),
+                    };
+                    this.present = Some(true);
+                }
+                None => this.present = Some(false),
+            }
+        }
+        Poll::Ready((this.present == Some(true), this.payload.take()))
+    }
+}
+
+/// A payload stream yielding a chunk read ahead before the rest of the payload
+struct ReadAhead {
+    chunk: Option<Result<Bytes, PayloadError>>,
+    rest: Payload,
+}
+
+impl Stream for ReadAhead {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.chunk.take() {
+            Some(chunk) => Poll::Ready(Some(chunk)),
+            None => Pin::new(&mut this.rest).poll_next(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::http::header::ContentType;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn should_detect_absent_inputs() {
+        let req = TestRequest::post().to_http_request();
+        assert_eq!(Json::<()>::is_present(&req), Present::UnlessBodyEmpty);
+        assert_eq!(Query::<()>::is_present(&req), Present::No);
+        assert_eq!(Header::<ContentType>::is_present(&req), Present::No);
+
+        let req = TestRequest::post()
+            .uri("/?page=1")
+            .insert_header(ContentType::json())
+            .set_payload("{}")
+            .to_http_request();
+        assert_eq!(Json::<()>::is_present(&req), Present::Yes);
+        assert_eq!(Query::<()>::is_present(&req), Present::Yes);
+        assert_eq!(Header::<ContentType>::is_present(&req), Present::Yes);
+
+        let req = TestRequest::post()
+            .insert_header((CONTENT_LENGTH, "0"))
+            .to_http_request();
+        assert_eq!(Form::<()>::is_present(&req), Present::No);
+    }
+
+    #[actix_web::test]
+    async fn should_peek_bodies_without_length() {
+        let req = TestRequest::post().to_http_request();
+        let (present, _) = Peek::new::<Json<()>>(&req, &mut Payload::None).await;
+        assert!(!present);
+
+        let mut payload = Payload::from(Bytes::from_static(b"{}"));
+        let (present, mut payload) = Peek::new::<Json<()>>(&req, &mut payload).await;
+        assert!(present);
+
+        let chunk = std::future::poll_fn(|cx| Pin::new(&mut payload).poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), Bytes::from_static(b"{}"));
+    }
+}
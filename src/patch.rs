@@ -0,0 +1,369 @@
+//! Validation of `PATCH` requests against the patched resource. Requires the `patch` feature flag
+//!
+//! The body of a `PATCH` request is a partial document, so validating it directly would reject
+//! valid patches (missing required fields) and accept invalid ones (a patch that only breaks an
+//! invariant once merged). Each backend provides a `ValidatedPatch<T>` extractor that:
+//!
+//! 1. parses the body as a [JSON Merge Patch (RFC 7396)](https://www.rfc-editor.org/rfc/rfc7396)
+//!    or, with the `application/json-patch+json` content type, as a
+//!    [JSON Patch (RFC 6902)](https://www.rfc-editor.org/rfc/rfc6902)
+//! 2. loads the current resource with the [`PatchLoader`] registered for `T`
+//! 3. applies the patch and validates the result like `Validated<Json<T>>` would
+//!
+//! Everything registered for `T` or `Validated<Json<T>>` applies to the patched value: the
+//! sanitizer, serialized field names, redaction policy, unknown field check (of the patched
+//! document), error limit, offloading and timeout, the error handler and the value error handler
+//! of `T`. Two things are not supported: source spans (the patched document has no source text)
+//! and a `ValidationResult` equivalent, rejected patches always respond with the errors.
+//!
+//! ```ignore
+//! use actix_web_validation::patch::PatchLoaderExt;
+//! use actix_web_validation::validator::ValidatedPatch;
+//!
+//! async fn update(user: ValidatedPatch<User>) -> impl Responder { /* save the patched user */ }
+//!
+//! App::new()
+//!     .patch_loader::<User, _, _>(|req: HttpRequest| async move {
+//!         let id = req.match_info().query("id").to_owned();
+//!         load_user(&id).await.map_err(actix_web::error::ErrorNotFound)
+//!     })
+//!     .route("/users/{id}", web::patch().to(update))
+//! ```
+//!
+//! Validation errors point to the patch paths (JSON Pointers) that caused them: a `patch_path`
+//! param for `validator`, the [`ValidationError::patch_path`](crate::custom::ValidationError)
+//! for `custom`, and a `(patch path: ..)` suffix of the message for `garde`. Patches that are
+//! malformed or cannot be applied fail with a [`PatchError`].
+
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use actix_web::dev::{Payload, ServiceFactory, ServiceRequest};
+use actix_web::http::header::{ContentType, CONTENT_TYPE};
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_web::{App, FromRequest, HttpRequest, HttpResponse, ResponseError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::path::{FieldPath, PathSyntax};
+
+/// The content type of JSON Patch documents
+pub const JSON_PATCH: &str = "application/json-patch+json";
+
+/// The content type of JSON Merge Patch documents
+pub const MERGE_PATCH: &str = "application/merge-patch+json";
+
+type LoadFuture<T> = Pin<Box<dyn Future<Output = Result<T, actix_web::Error>>>>;
+
+/// Loads the current value of the resource a patch is applied to
+pub struct PatchLoader<T> {
+    load: Rc<dyn Fn(HttpRequest) -> LoadFuture<T>>,
+}
+
+impl<T> PatchLoader<T> {
+    pub fn new<F, Fut>(load: F) -> Self
+    where
+        F: Fn(HttpRequest) -> Fut + 'static,
+        Fut: Future<Output = Result<T, actix_web::Error>> + 'static,
+    {
+        Self {
+            load: Rc::new(move |req| Box::pin(load(req))),
+        }
+    }
+}
+
+pub trait PatchLoaderExt {
+    /// Loads the resource patched by `ValidatedPatch<T>` extractors with `load`
+    fn patch_loader<T, F, Fut>(self, load: F) -> Self
+    where
+        T: 'static,
+        F: Fn(HttpRequest) -> Fut + 'static,
+        Fut: Future<Output = Result<T, actix_web::Error>> + 'static;
+}
+
+impl<A> PatchLoaderExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn patch_loader<T, F, Fut>(self, load: F) -> Self
+    where
+        T: 'static,
+        F: Fn(HttpRequest) -> Fut + 'static,
+        Fut: Future<Output = Result<T, actix_web::Error>> + 'static,
+    {
+        self.app_data(PatchLoader::new(load))
+    }
+}
+
+impl PatchLoaderExt for &mut actix_web::web::ServiceConfig {
+    fn patch_loader<T, F, Fut>(self, load: F) -> Self
+    where
+        T: 'static,
+        F: Fn(HttpRequest) -> Fut + 'static,
+        Fut: Future<Output = Result<T, actix_web::Error>> + 'static,
+    {
+        self.app_data(PatchLoader::new(load))
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl PatchLoaderExt for crate::test_util::ValidationProbe {
+    fn patch_loader<T, F, Fut>(self, load: F) -> Self
+    where
+        T: 'static,
+        F: Fn(HttpRequest) -> Fut + 'static,
+        Fut: Future<Output = Result<T, actix_web::Error>> + 'static,
+    {
+        self.app_data(PatchLoader::new(load))
+    }
+}
+
+/// A patch document
+#[derive(Clone, Debug, PartialEq)]
+pub enum Patch {
+    /// A JSON Merge Patch (RFC 7396)
+    Merge(Value),
+    /// A JSON Patch (RFC 6902)
+    Json(json_patch::Patch),
+}
+
+impl Patch {
+    /// Parses `body` as a JSON Patch for the `application/json-patch+json` content type, and as
+    /// a JSON Merge Patch for `application/merge-patch+json`, `application/json` or no content type
+    pub fn parse(req: &HttpRequest, body: &[u8]) -> Result<Self, PatchError> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap_or_default())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase());
+
+        match content_type.as_deref() {
+            Some(JSON_PATCH) => Ok(Patch::Json(
+                serde_json::from_slice(body).map_err(PatchError::Parse)?,
+            )),
+            None | Some(MERGE_PATCH) | Some("application/json") => Ok(Patch::Merge(
+                serde_json::from_slice(body).map_err(PatchError::Parse)?,
+            )),
+            Some(_) => Err(PatchError::UnsupportedMediaType),
+        }
+    }
+
+    /// Applies the patch to `doc`
+    pub fn apply(&self, doc: &mut Value) -> Result<(), PatchError> {
+        match self {
+            Patch::Merge(patch) => json_patch::merge(doc, patch),
+            Patch::Json(patch) => json_patch::patch(doc, patch).map_err(PatchError::Apply)?,
+        }
+        Ok(())
+    }
+
+    /// The JSON Pointers of the values changed by the patch
+    pub fn paths(&self) -> Vec<String> {
+        match self {
+            Patch::Merge(patch) => {
+                let mut paths = Vec::new();
+                merge_paths(patch, String::new(), &mut paths);
+                paths
+            }
+            Patch::Json(patch) => patch
+                .iter()
+                .filter(|op| !matches!(op, json_patch::PatchOperation::Test(_)))
+                .map(|op| op.path().to_string())
+                .collect(),
+        }
+    }
+
+    /// The patch path that caused an error of `field`. The fields of `field` must use the
+    /// serialized names, which validation errors do for types registered with
+    /// [`serialized_names`](crate::field_names::SerializedNamesExt::serialized_names).
+    ///
+    /// Prefers the path of `field` itself, then its closest changed parent and then a changed
+    /// child. Returns `None` for fields the patch did not touch.
+    pub fn path_of(&self, field: &FieldPath) -> Option<String> {
+        let field = field.render(PathSyntax::JsonPointer);
+        let paths = self.paths();
+
+        let exact = paths.iter().find(|path| **path == field);
+        let parent = paths
+            .iter()
+            .filter(|path| is_parent(path, &field))
+            .max_by_key(|path| path.len());
+        let child = paths.iter().find(|path| is_parent(&field, path));
+
+        exact.or(parent).or(child).cloned()
+    }
+}
+
+fn merge_paths(patch: &Value, path: String, paths: &mut Vec<String>) {
+    match patch {
+        Value::Object(fields) if !fields.is_empty() => {
+            for (key, value) in fields {
+                merge_paths(value, format!("{path}/{}", escape(key)), paths);
+            }
+        }
+        _ if !path.is_empty() => paths.push(path),
+        _ => {}
+    }
+}
+
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn is_parent(parent: &str, path: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// The error returned when a patch cannot be parsed or applied
+#[derive(Debug, Error)]
+pub enum PatchError {
+    #[error("Unsupported patch content type, expected {JSON_PATCH} or {MERGE_PATCH}")]
+    UnsupportedMediaType,
+    #[error("Invalid patch document: {0}")]
+    Parse(serde_json::Error),
+    #[error("Patch could not be applied: {0}")]
+    Apply(json_patch::PatchError),
+    #[error("Patched document is invalid: {0}")]
+    Deserialize(serde_json::Error),
+    #[error("No patch loader registered for {0}")]
+    MissingLoader(&'static str),
+}
+
+impl ResponseError for PatchError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PatchError::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            PatchError::Parse(_) | PatchError::Deserialize(_) => StatusCode::BAD_REQUEST,
+            PatchError::Apply(_) => StatusCode::CONFLICT,
+            PatchError::MissingLoader(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .content_type(ContentType::plaintext())
+            .body(self.to_string())
+    }
+}
+
+/// A resource with a patch applied
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+pub(crate) struct Patched<T> {
+    pub(crate) value: T,
+    pub(crate) patch: Patch,
+    /// Checks the patched document for unknown fields, see [`crate::strict`]
+    pub(crate) unknown_fields: Option<Box<dyn FnOnce() -> Vec<FieldPath>>>,
+}
+
+/// Reads the patch from the request body, loads the resource and applies the patch to it
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+pub(crate) fn patched<T>(
+    req: &HttpRequest,
+    payload: &mut Payload,
+) -> impl Future<Output = Result<Patched<T>, actix_web::Error>>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    let req = req.clone();
+    let body = Bytes::from_request(&req, payload);
+
+    async move {
+        let body = body.await?;
+        let patch = Patch::parse(&req, &body)?;
+
+        let loader = req
+            .app_data::<PatchLoader<T>>()
+            .ok_or(PatchError::MissingLoader(std::any::type_name::<T>()))?;
+        let base = (loader.load)(req.clone()).await?;
+
+        let mut doc =
+            serde_json::to_value(base).map_err(actix_web::error::ErrorInternalServerError)?;
+        patch.apply(&mut doc)?;
+
+        #[cfg(feature = "strict")]
+        let unknown_fields = serde_json::to_vec(&doc)
+            .ok()
+            .and_then(|body| crate::strict::check::<T>(&req, body));
+        #[cfg(not(feature = "strict"))]
+        let unknown_fields = None;
+
+        let value = serde_json::from_value(doc).map_err(PatchError::Deserialize)?;
+
+        Ok(Patched {
+            value,
+            patch,
+            unknown_fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use serde_json::json;
+
+    #[test]
+    fn should_parse_patches_by_content_type() {
+        let req = TestRequest::default().to_http_request();
+        let patch = Patch::parse(&req, br#"{"name": "a", "address": {"city": null}}"#).unwrap();
+        assert_eq!(patch.paths(), vec!["/address/city", "/name"]);
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, JSON_PATCH))
+            .to_http_request();
+        let patch = Patch::parse(
+            &req,
+            br#"[{"op": "test", "path": "/name", "value": "a"}, {"op": "remove", "path": "/tags/0"}]"#,
+        )
+        .unwrap();
+        assert_eq!(patch.paths(), vec!["/tags/0"]);
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "text/plain"))
+            .to_http_request();
+        assert!(matches!(
+            Patch::parse(&req, b"{}"),
+            Err(PatchError::UnsupportedMediaType)
+        ));
+    }
+
+    #[test]
+    fn should_find_patch_paths_of_fields() {
+        let patch = Patch::Merge(json!({"name": "a", "address": {"city": "b"}, "tags": ["c"]}));
+        let path_of = |path: &str| patch.path_of(&FieldPath::parse(path));
+        assert_eq!(path_of("name").as_deref(), Some("/name"));
+        assert_eq!(path_of("tags[0]").as_deref(), Some("/tags"));
+        assert_eq!(path_of("address").as_deref(), Some("/address/city"));
+        assert_eq!(path_of("email"), None);
+
+        let mut doc = json!({"name": "x", "tags": []});
+        patch.apply(&mut doc).unwrap();
+        assert_eq!(
+            doc,
+            json!({"name": "a", "address": {"city": "b"}, "tags": ["c"]})
+        );
+    }
+
+    #[test]
+    fn should_find_patch_paths_of_map_keys() {
+        let patch = Patch::Merge(json!({"labels": {"en.US": "a", "a/b": "b"}}));
+
+        let key = FieldPath::new().field("labels").key("en.US");
+        assert_eq!(patch.path_of(&key).as_deref(), Some("/labels/en.US"));
+        let key = FieldPath::parse("labels[\"a/b\"]");
+        assert_eq!(patch.path_of(&key).as_deref(), Some("/labels/a~1b"));
+    }
+}
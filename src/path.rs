@@ -0,0 +1,249 @@
+//! Structured field paths and the syntaxes they are rendered in.
+//!
+//! Every backend reports the location of an error as a [`FieldPath`] made of field, list index
+//! and map key segments. The built-in error responders render paths in the [`PathSyntax`]
+//! registered as app data, [`PathSyntax::Dotted`] by default:
+//!
+//! | Syntax                       | Example                |
+//! |------------------------------|------------------------|
+//! | [`PathSyntax::Dotted`]       | `a.b[0].c`             |
+//! | [`PathSyntax::JsonPointer`]  | `/a/b/0/c`             |
+//! | [`PathSyntax::Brackets`]     | `a[b][0][c]`           |
+//!
+//! ```
+//! use actix_web::App;
+//! use actix_web_validation::path::PathSyntax;
+//!
+//! let app = App::new().app_data(PathSyntax::JsonPointer);
+//! ```
+//!
+//! Map keys are reported by garde as regular fields, and validator reports map entries by their
+//! position. Custom errors can use [`PathSegment::Key`] segments with
+//! `ValidationError::with_path`.
+
+use std::fmt::Display;
+
+use actix_web::HttpRequest;
+
+/// A single segment of a [`FieldPath`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A struct field
+    Field(String),
+    /// A list index
+    Index(usize),
+    /// A map key
+    Key(String),
+}
+
+/// The location of an invalid value
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FieldPath {
+    segments: Vec<PathSegment>,
+}
+
+impl FieldPath {
+    /// The path of the validated value itself
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, name: impl Into<String>) -> Self {
+        self.push(PathSegment::Field(name.into()));
+        self
+    }
+
+    pub fn index(mut self, index: usize) -> Self {
+        self.push(PathSegment::Index(index));
+        self
+    }
+
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.push(PathSegment::Key(key.into()));
+        self
+    }
+
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// The path without its list indices and map keys (eg. `items.name` for `items[0].name`)
+    pub fn fields(&self) -> Self {
+        let segments = self
+            .segments
+            .iter()
+            .filter(|segment| matches!(segment, PathSegment::Field(_)))
+            .cloned()
+            .collect();
+        Self { segments }
+    }
+
+    /// Parses a dotted path (eg. `a.b[0].c` or `labels["en-US"]`)
+    pub fn parse(path: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut rest = path;
+        while !rest.is_empty() {
+            if let Some(bracket) = rest.strip_prefix('[') {
+                let end = bracket.find(']').unwrap_or(bracket.len());
+                let inner = &bracket[..end];
+                segments.push(match inner.parse() {
+                    Ok(index) => PathSegment::Index(index),
+                    Err(_) => PathSegment::Key(inner.trim_matches('"').to_string()),
+                });
+                rest = bracket.get(end + 1..).unwrap_or_default();
+            } else {
+                let field = rest.strip_prefix('.').unwrap_or(rest);
+                let end = field.find(['.', '[']).unwrap_or(field.len());
+                segments.push(PathSegment::Field(field[..end].to_string()));
+                rest = &field[end..];
+            }
+        }
+        Self { segments }
+    }
+
+    /// Parses a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901), numeric segments
+    /// are list indices
+    pub fn from_pointer(pointer: &str) -> Self {
+        let segments = pointer
+            .split('/')
+            .skip(1)
+            .map(|segment| {
+                let segment = segment.replace("~1", "/").replace("~0", "~");
+                match segment.parse() {
+                    Ok(index) if !segment.is_empty() && !segment.starts_with('+') => {
+                        PathSegment::Index(index)
+                    }
+                    _ => PathSegment::Field(segment),
+                }
+            })
+            .collect();
+        Self { segments }
+    }
+
+    pub fn render(&self, syntax: PathSyntax) -> String {
+        let mut path = String::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            match (syntax, segment) {
+                (PathSyntax::Dotted, PathSegment::Field(name)) if i == 0 => path.push_str(name),
+                (PathSyntax::Dotted, PathSegment::Field(name)) => {
+                    path.push('.');
+                    path.push_str(name);
+                }
+                (PathSyntax::Dotted, PathSegment::Key(key)) if is_plain(key) => {
+                    if i > 0 {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                }
+                (PathSyntax::Dotted, PathSegment::Key(key)) => {
+                    path.push_str(&format!("[\"{key}\"]"));
+                }
+                (PathSyntax::Dotted | PathSyntax::Brackets, PathSegment::Index(index)) => {
+                    path.push_str(&format!("[{index}]"));
+                }
+                (PathSyntax::Brackets, PathSegment::Field(name) | PathSegment::Key(name))
+                    if i == 0 =>
+                {
+                    path.push_str(name)
+                }
+                (PathSyntax::Brackets, PathSegment::Field(name) | PathSegment::Key(name)) => {
+                    path.push_str(&format!("[{name}]"));
+                }
+                (PathSyntax::JsonPointer, PathSegment::Field(name) | PathSegment::Key(name)) => {
+                    path.push('/');
+                    path.push_str(&name.replace('~', "~0").replace('/', "~1"));
+                }
+                (PathSyntax::JsonPointer, PathSegment::Index(index)) => {
+                    path.push_str(&format!("/{index}"));
+                }
+            }
+        }
+        path
+    }
+}
+
+/// Keys that can be rendered like fields in the dotted syntax
+fn is_plain(key: &str) -> bool {
+    !key.is_empty() && !key.contains(['.', '[', ']', '"'])
+}
+
+/// Renders the path in the dotted syntax
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(PathSyntax::Dotted))
+    }
+}
+
+/// The syntax the built-in error responders render field paths in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PathSyntax {
+    /// `a.b[0].c`, map keys that are not plain identifiers are quoted (`labels["en.US"]`)
+    #[default]
+    Dotted,
+    /// RFC 6901 JSON Pointers (`/a/b/0/c`)
+    JsonPointer,
+    /// The form field notation of HTML form frameworks (`a[b][0][c]`)
+    Brackets,
+}
+
+/// The path syntax configured for the request
+#[cfg_attr(
+    not(any(
+        feature = "validator",
+        feature = "garde",
+        feature = "custom",
+        feature = "jsonschema"
+    )),
+    allow(dead_code)
+)]
+pub(crate) fn syntax(req: &HttpRequest) -> PathSyntax {
+    req.app_data::<PathSyntax>().copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_render_paths_in_each_syntax() {
+        let path = FieldPath::new().field("a").field("b").index(0).field("c");
+        assert_eq!(path.render(PathSyntax::Dotted), "a.b[0].c");
+        assert_eq!(path.render(PathSyntax::JsonPointer), "/a/b/0/c");
+        assert_eq!(path.render(PathSyntax::Brackets), "a[b][0][c]");
+
+        let path = FieldPath::new()
+            .field("labels")
+            .key("en.US/x")
+            .field("text");
+        assert_eq!(path.render(PathSyntax::Dotted), "labels[\"en.US/x\"].text");
+        assert_eq!(
+            path.render(PathSyntax::JsonPointer),
+            "/labels/en.US~1x/text"
+        );
+        assert_eq!(path.render(PathSyntax::Brackets), "labels[en.US/x][text]");
+
+        assert_eq!(FieldPath::new().render(PathSyntax::JsonPointer), "");
+    }
+
+    #[test]
+    fn should_parse_paths() {
+        let path = FieldPath::new().field("a").field("b").index(0).field("c");
+        assert_eq!(FieldPath::parse("a.b[0].c"), path);
+        assert_eq!(FieldPath::from_pointer("/a/b/0/c"), path);
+
+        let path = FieldPath::new().field("labels").key("en.US").field("text");
+        assert_eq!(FieldPath::parse("labels[\"en.US\"].text"), path);
+        assert_eq!(
+            FieldPath::from_pointer("/a~1b/c~0d"),
+            FieldPath::new().field("a/b").field("c~d")
+        );
+    }
+}
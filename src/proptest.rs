@@ -0,0 +1,714 @@
+//! Generation of valid and boundary-violating JSON payloads from [`ValidationRules`] with the
+//! [proptest](https://docs.rs/proptest/latest/proptest) crate. Requires the `proptest` feature flag
+//!
+//! [`valid`] generates payloads satisfying every rule of a type. [`invalid`] generates payloads
+//! breaking a single rule at its boundary (eg. a length of `min - 1` or a number just out of
+//! range), and reports which field and rule were broken:
+//!
+//! ```ignore
+//! use actix_web_validation::proptest::{invalid, valid};
+//!
+//! proptest! {
+//!     #[test]
+//!     fn rejects_invalid_users(payload in invalid::<CreateUser>()) {
+//!         let resp = call(&payload.value);
+//!         prop_assert_eq!(resp.status(), 400, "{} should violate {:?}", payload.field, payload.rule);
+//!     }
+//! }
+//! ```
+//!
+//! [`assert_rules`] runs both strategies through an extractor and checks the outcome:
+//!
+//! ```ignore
+//! #[test]
+//! fn create_user_rules() {
+//!     assert_rules::<CreateUser, Validated<Json<CreateUser>>>(StatusCode::BAD_REQUEST);
+//! }
+//! ```
+//!
+//! Fields whose values can't be generated (types without `ValidationRules` like enums or dates,
+//! and nested objects deeper than 4 levels) are left out when they are optional. [`valid`] and
+//! [`invalid`] panic when such a field is required, rather than generating payloads that fail
+//! to deserialize.
+//!
+//! Patterns are only used to generate valid values, values violating a pattern are not
+//! generated.
+
+use ::proptest::collection::{btree_map, vec};
+use ::proptest::prelude::*;
+use ::proptest::strategy::Union;
+use ::proptest::test_runner::TestRunner;
+use actix_web::http::StatusCode;
+use actix_web::FromRequest;
+use serde_json::{Map, Value};
+
+use crate::path::{FieldPath, PathSegment};
+use crate::rules::{FieldRules, FieldType, LengthMode, Rule, ValidationRules};
+
+/// Nested objects deeper than this are not generated, to support recursive types
+const MAX_DEPTH: usize = 4;
+
+/// The max length of generated values for fields without a max length
+const DEFAULT_MAX_LENGTH: u64 = 8;
+
+/// A payload violating a single rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidPayload {
+    pub value: Value,
+    /// The path of the field violating the rule (eg. `addresses[0].zip`)
+    pub field: FieldPath,
+    pub rule: Rule,
+}
+
+/// Generates JSON objects satisfying the rules of `T`.
+/// Panics if values of a required field of `T` can't be generated.
+pub fn valid<T: ValidationRules>() -> BoxedStrategy<Value> {
+    generated::<T, _>(valid_object(&T::rules(), 0))
+        .prop_map(Value::Object)
+        .boxed()
+}
+
+/// Generates JSON objects violating a single rule of `T`. Pattern rules are never violated.
+/// Panics if `T` has no rule that can be violated, or if values of a required field of `T`
+/// can't be generated.
+pub fn invalid<T: ValidationRules>() -> BoxedStrategy<InvalidPayload> {
+    let _ = generated::<T, _>(valid_object(&T::rules(), 0));
+    let violations = object_violations(&T::rules(), 0);
+    assert!(
+        !violations.is_empty(),
+        "{} has no rule that can be violated",
+        std::any::type_name::<T>()
+    );
+    Union::new(violations)
+        .prop_map(|(value, field, rule)| InvalidPayload {
+            value: Value::Object(value),
+            field,
+            rule,
+        })
+        .boxed()
+}
+
+/// Asserts extractor `E` accepts the JSON payloads generated by [`valid`], and rejects the
+/// payloads generated by [`invalid`] with `status`.
+///
+/// Must not be called from an async test, since it starts its own actix runtime.
+pub fn assert_rules<T: ValidationRules, E: FromRequest>(status: StatusCode) {
+    let system = actix_web::rt::System::new();
+    let extract = |value: &Value| {
+        system.block_on(async {
+            let (req, mut payload) = actix_web::test::TestRequest::post()
+                .set_json(value)
+                .to_http_parts();
+            E::from_request(&req, &mut payload)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.into().error_response().status())
+        })
+    };
+
+    TestRunner::default()
+        .run(&valid::<T>(), |value| {
+            let result = extract(&value);
+            prop_assert!(result.is_ok(), "rejected with {:?}", result);
+            Ok(())
+        })
+        .unwrap_or_else(|e| panic!("valid payload was rejected: {e}"));
+
+    TestRunner::default()
+        .run(&invalid::<T>(), |payload| {
+            let result = extract(&payload.value);
+            prop_assert_eq!(result, Err(status));
+            Ok(())
+        })
+        .unwrap_or_else(|e| panic!("invalid payload was not rejected: {e}"));
+}
+
+type Violation<T> = BoxedStrategy<(T, FieldPath, Rule)>;
+
+/// A strategy, or the path of the value that can't be generated relative to the generated value
+type Generated<T> = Result<BoxedStrategy<T>, String>;
+
+fn generated<T, V>(strategy: Generated<V>) -> BoxedStrategy<V> {
+    strategy.unwrap_or_else(|field| {
+        panic!(
+            "values of the required field `{field}` of {} can't be generated, \
+            its type has no `ValidationRules` or is nested too deeply",
+            std::any::type_name::<T>()
+        )
+    })
+}
+
+fn valid_object(fields: &[FieldRules], depth: usize) -> Generated<Map<String, Value>> {
+    let fields = fields
+        .iter()
+        .map(|field| {
+            let name = field.serialized_name.to_string();
+            let optional = !field.required && !field.rules.contains(&Rule::Required);
+            let value = match valid_value(&field.field_type, &field.rules, depth) {
+                Ok(value) if optional => ::proptest::option::of(value).boxed(),
+                Ok(value) => value.prop_map(Some).boxed(),
+                // Left out rather than generated as an undeserializable `null`
+                Err(_) if optional => Just(None).boxed(),
+                Err(path) => return Err(format!("{name}{path}")),
+            };
+            Ok(value.prop_map(move |value| (name.clone(), value)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(fields
+        .prop_map(|fields| {
+            fields
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value?)))
+                .collect()
+        })
+        .boxed())
+}
+
+fn valid_value(field_type: &FieldType, rules: &[Rule], depth: usize) -> Generated<Value> {
+    Ok(match field_type {
+        FieldType::String => valid_string(rules).prop_map(Value::String).boxed(),
+        FieldType::Integer => {
+            let (min, max) = integer_bounds(rules);
+            (min..=max).prop_map(Value::from).boxed()
+        }
+        FieldType::Number => {
+            let (min, max) = number_bounds(rules);
+            (min..=max).prop_map(Value::from).boxed()
+        }
+        FieldType::Boolean => any::<bool>().prop_map(Value::from).boxed(),
+        FieldType::Array(items) => {
+            let (min, max) = length_bounds(rules);
+            match valid_value(items, &[], depth) {
+                Ok(items) => vec(items, min..=max).prop_map(Value::Array).boxed(),
+                Err(_) if min == 0 => Just(Value::Array(Vec::new())).boxed(),
+                Err(path) => return Err(format!("[0]{path}")),
+            }
+        }
+        FieldType::Map(values) => {
+            let (min, max) = length_bounds(rules);
+            match valid_value(values, &[], depth) {
+                Ok(values) => btree_map("[a-z]{1,8}", values, min..=max)
+                    .prop_map(|map| Value::Object(map.into_iter().collect()))
+                    .boxed(),
+                Err(_) if min == 0 => Just(Value::Object(Map::new())).boxed(),
+                Err(path) => return Err(format!("[..]{path}")),
+            }
+        }
+        FieldType::Object(object) if depth < MAX_DEPTH => valid_object(&object.rules(), depth + 1)
+            .map_err(|path| format!(".{path}"))?
+            .prop_map(Value::Object)
+            .boxed(),
+        FieldType::Object(_) | FieldType::Any => return Err(String::new()),
+    })
+}
+
+/// The violations of an object, with the path of the violating field. Objects that can't be
+/// generated have none.
+fn object_violations(fields: &[FieldRules], depth: usize) -> Vec<Violation<Map<String, Value>>> {
+    let mut violations = Vec::new();
+    let Ok(valid) = valid_object(fields, depth) else {
+        return violations;
+    };
+
+    for field in fields {
+        let name = field.serialized_name;
+
+        for violation in value_violations(&field.field_type, &field.rules, depth) {
+            violations.push(
+                (valid.clone(), violation)
+                    .prop_map(move |(mut object, (value, path, rule))| {
+                        object.insert(name.to_string(), value);
+                        (
+                            object,
+                            prefixed(PathSegment::Field(name.to_string()), path),
+                            rule,
+                        )
+                    })
+                    .boxed(),
+            );
+        }
+
+        if field.rules.contains(&Rule::Required) {
+            violations.push(
+                valid
+                    .clone()
+                    .prop_map(move |mut object| {
+                        object.remove(name);
+                        (object, FieldPath::new().field(name), Rule::Required)
+                    })
+                    .boxed(),
+            );
+        }
+    }
+
+    violations
+}
+
+/// The violations of a value, with the path of the violating value relative to it
+fn value_violations(field_type: &FieldType, rules: &[Rule], depth: usize) -> Vec<Violation<Value>> {
+    let direct = |value: BoxedStrategy<Value>, rule: &Rule| {
+        let rule = rule.clone();
+        value
+            .prop_map(move |value| (value, FieldPath::new(), rule.clone()))
+            .boxed()
+    };
+
+    match field_type {
+        FieldType::String => invalid_strings(rules)
+            .into_iter()
+            .map(|(value, rule)| direct(value.prop_map(Value::String).boxed(), &rule))
+            .collect(),
+        FieldType::Integer => invalid_numbers(rules, true)
+            .into_iter()
+            .map(|(value, rule)| direct(Just(value).boxed(), &rule))
+            .collect(),
+        FieldType::Number => invalid_numbers(rules, false)
+            .into_iter()
+            .map(|(value, rule)| direct(Just(value).boxed(), &rule))
+            .collect(),
+        FieldType::Array(items) => {
+            let mut violations = invalid_lengths(rules)
+                .into_iter()
+                .filter_map(|(len, rule)| {
+                    let value = match valid_value(items, &[], depth) {
+                        Ok(items) => vec(items, len).prop_map(Value::Array).boxed(),
+                        Err(_) if len == 0 => Just(Value::Array(Vec::new())).boxed(),
+                        Err(_) => return None,
+                    };
+                    Some(direct(value, &rule))
+                })
+                .collect::<Vec<_>>();
+
+            // A single invalid item
+            for violation in value_violations(items, &[], depth) {
+                violations.push(
+                    violation
+                        .prop_map(|(value, path, rule)| {
+                            let path = prefixed(PathSegment::Index(0), path);
+                            (Value::Array(vec![value]), path, rule)
+                        })
+                        .boxed(),
+                );
+            }
+
+            violations
+        }
+        FieldType::Map(values) => invalid_lengths(rules)
+            .into_iter()
+            .filter_map(|(len, rule)| {
+                let value = match valid_value(values, &[], depth) {
+                    Ok(values) => btree_map("[a-z]{1,8}", values, len)
+                        .prop_map(|map| Value::Object(map.into_iter().collect()))
+                        .boxed(),
+                    Err(_) if len == 0 => Just(Value::Object(Map::new())).boxed(),
+                    Err(_) => return None,
+                };
+                Some(direct(value, &rule))
+            })
+            .collect(),
+        FieldType::Object(object) if depth < MAX_DEPTH => {
+            object_violations(&object.rules(), depth + 1)
+                .into_iter()
+                .map(|violation| {
+                    violation
+                        .prop_map(|(object, path, rule)| (Value::Object(object), path, rule))
+                        .boxed()
+                })
+                .collect()
+        }
+        FieldType::Boolean | FieldType::Object(_) | FieldType::Any => Vec::new(),
+    }
+}
+
+/// `path` below `segment`
+fn prefixed(segment: PathSegment, path: FieldPath) -> FieldPath {
+    let mut prefixed = FieldPath::new();
+    prefixed.push(segment);
+    for segment in path.segments() {
+        prefixed.push(segment.clone());
+    }
+    prefixed
+}
+
+/// The text around generated characters required by the format rules of a string
+fn affixes(rules: &[Rule]) -> (&str, &str) {
+    rules
+        .iter()
+        .find_map(|rule| match rule {
+            Rule::Email => Some(("", "@example.com")),
+            Rule::Url => Some(("https://example.com/", "")),
+            Rule::Contains(needle) => Some(("", needle.as_str())),
+            _ => None,
+        })
+        .unwrap_or(("", ""))
+}
+
+/// The length of the affixes in the length mode of the length rule
+fn affix_length(rules: &[Rule], prefix: &str, suffix: &str) -> usize {
+    let mode = rules.iter().find_map(|rule| match rule {
+        Rule::Length { mode, .. } => Some(*mode),
+        _ => None,
+    });
+    let measure = |s: &str| match mode {
+        Some(LengthMode::Bytes) => s.len(),
+        Some(LengthMode::Utf16) => s.encode_utf16().count(),
+        // Without a segmentation library graphemes are approximated by characters
+        _ => s.chars().count(),
+    };
+    measure(prefix) + measure(suffix)
+}
+
+/// ASCII letters, which have the same length in every [`LengthMode`]
+fn letters(len: std::ops::RangeInclusive<usize>) -> BoxedStrategy<String> {
+    vec(::proptest::char::range('a', 'z'), len)
+        .prop_map(String::from_iter)
+        .boxed()
+}
+
+/// Strings of length `len`, following the format rules when possible
+fn strings_of_length(rules: &[Rule], len: usize) -> BoxedStrategy<String> {
+    let (prefix, suffix) = affixes(rules);
+    let affix = affix_length(rules, prefix, suffix);
+    if len <= affix {
+        return letters(len..=len);
+    }
+
+    let (prefix, suffix) = (prefix.to_string(), suffix.to_string());
+    letters(len - affix..=len - affix)
+        .prop_map(move |filler| format!("{prefix}{filler}{suffix}"))
+        .boxed()
+}
+
+fn valid_string(rules: &[Rule]) -> BoxedStrategy<String> {
+    let pattern = rules.iter().find_map(|rule| match rule {
+        Rule::Pattern(pattern) => ::proptest::string::string_regex(pattern).ok(),
+        _ => None,
+    });
+    if let Some(pattern) = pattern {
+        return pattern.boxed();
+    }
+
+    let (prefix, suffix) = affixes(rules);
+    let affix = affix_length(rules, prefix, suffix);
+    // Emails need a local part
+    let min_filler = usize::from(rules.contains(&Rule::Email));
+    let (min, max) = length_bounds(rules);
+    let min = min.saturating_sub(affix).max(min_filler);
+    let max = max.saturating_sub(affix).max(min);
+
+    let (prefix, suffix) = (prefix.to_string(), suffix.to_string());
+    letters(min..=max)
+        .prop_map(move |filler| format!("{prefix}{filler}{suffix}"))
+        .boxed()
+}
+
+fn invalid_strings(rules: &[Rule]) -> Vec<(BoxedStrategy<String>, Rule)> {
+    let mut violations = invalid_lengths(rules)
+        .into_iter()
+        .map(|(len, rule)| (strings_of_length(rules, len), rule))
+        .collect::<Vec<_>>();
+
+    for rule in rules {
+        let value = match rule {
+            Rule::Email => Just("not-an-email".to_string()).boxed(),
+            Rule::Url => Just("not a url".to_string()).boxed(),
+            Rule::Contains(needle) => {
+                let (min, max) = length_bounds(rules);
+                let needle = needle.clone();
+                letters(min..=max)
+                    .prop_filter("must not contain the needle", move |s| !s.contains(&needle))
+                    .boxed()
+            }
+            _ => continue,
+        };
+        violations.push((value, rule.clone()));
+    }
+
+    violations
+}
+
+/// The min and max length of valid values
+fn length_bounds(rules: &[Rule]) -> (usize, usize) {
+    let (min, max) = length(rules);
+    let min = min.unwrap_or(0);
+    let max = max.unwrap_or(min + DEFAULT_MAX_LENGTH);
+    (min as usize, max.max(min) as usize)
+}
+
+/// The closest invalid lengths to the length rule
+fn invalid_lengths(rules: &[Rule]) -> Vec<(usize, Rule)> {
+    let Some(rule) = rules
+        .iter()
+        .find(|rule| matches!(rule, Rule::Length { .. }))
+    else {
+        return Vec::new();
+    };
+
+    let (min, max) = length(rules);
+    let mut lengths = Vec::new();
+    if let Some(min) = min.filter(|min| *min > 0) {
+        lengths.push((min as usize - 1, rule.clone()));
+    }
+    if let Some(max) = max {
+        lengths.push((max as usize + 1, rule.clone()));
+    }
+    lengths
+}
+
+fn length(rules: &[Rule]) -> (Option<u64>, Option<u64>) {
+    rules
+        .iter()
+        .find_map(|rule| match rule {
+            Rule::Length {
+                min, max, equal, ..
+            } => Some(match equal {
+                Some(equal) => (Some(*equal), Some(*equal)),
+                None => (*min, *max),
+            }),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// The smallest step away from a float bound
+fn step(bound: f64) -> f64 {
+    (bound.abs() * 1e-6).max(1e-6)
+}
+
+/// The inclusive `(min, max)` bounds of the range rule, if any
+fn range(rules: &[Rule]) -> (Option<f64>, Option<f64>, Option<&Rule>) {
+    let Some(rule) = rules.iter().find(|rule| matches!(rule, Rule::Range { .. })) else {
+        return (None, None, None);
+    };
+    let Rule::Range {
+        min,
+        max,
+        exclusive_min,
+        exclusive_max,
+    } = rule
+    else {
+        unreachable!()
+    };
+
+    let min = min.or(exclusive_min.map(|min| min + step(min)));
+    let max = max.or(exclusive_max.map(|max| max - step(max)));
+    (min, max, Some(rule))
+}
+
+/// Default bounds for values without a range, that fit any numeric type
+fn default_bounds(min: Option<f64>, max: Option<f64>) -> (f64, f64) {
+    match (min, max) {
+        (Some(min), Some(max)) => (min, max.max(min)),
+        (Some(min), None) => (min, min + 100.0),
+        (None, Some(max)) => (max.min(0.0) - if max < 0.0 { 100.0 } else { 0.0 }, max),
+        (None, None) => (0.0, 100.0),
+    }
+}
+
+fn integer_bounds(rules: &[Rule]) -> (i64, i64) {
+    let (min, max, _) = range(rules);
+    let (min, max) = default_bounds(min.map(f64::ceil), max.map(f64::floor));
+    (min as i64, (max as i64).max(min as i64))
+}
+
+fn number_bounds(rules: &[Rule]) -> (f64, f64) {
+    let (min, max, _) = range(rules);
+    default_bounds(min, max)
+}
+
+/// The closest invalid values to the range rule
+fn invalid_numbers(rules: &[Rule], integer: bool) -> Vec<(Value, Rule)> {
+    let Some(
+        rule @ Rule::Range {
+            min,
+            max,
+            exclusive_min,
+            exclusive_max,
+        },
+    ) = rules.iter().find(|rule| matches!(rule, Rule::Range { .. }))
+    else {
+        return Vec::new();
+    };
+
+    let below = |bound: f64| {
+        if integer {
+            Value::from(bound.ceil() as i64 - 1)
+        } else {
+            Value::from(bound - step(bound))
+        }
+    };
+    let above = |bound: f64| {
+        if integer {
+            Value::from(bound.floor() as i64 + 1)
+        } else {
+            Value::from(bound + step(bound))
+        }
+    };
+    let exactly = |bound: f64, round: fn(f64) -> f64| {
+        if integer {
+            Value::from(round(bound) as i64)
+        } else {
+            Value::from(bound)
+        }
+    };
+
+    let mut values = Vec::new();
+    values.extend(min.map(below));
+    values.extend(max.map(above));
+    values.extend(exclusive_min.map(|min| exactly(min, f64::floor)));
+    values.extend(exclusive_max.map(|max| exactly(max, f64::ceil)));
+    values
+        .into_iter()
+        .map(|value| (value, rule.clone()))
+        .collect()
+}
+
+#[cfg(all(test, feature = "derive", feature = "validator"))]
+mod test {
+    use super::*;
+    use ::proptest::test_runner::TestCaseError;
+    use actix_web::web::Json;
+    use validator::Validate;
+
+    #[allow(dead_code)]
+    #[derive(Debug, serde::Deserialize, Validate, crate::rules::ValidationRules)]
+    #[serde(rename_all = "camelCase")]
+    struct CreateUser {
+        #[validate(length(min = 3, max = 32))]
+        user_name: String,
+        #[validate(email)]
+        email: Option<String>,
+        #[validate(range(min = 18, exclusive_max = 150))]
+        age: u8,
+        #[validate(range(exclusive_min = 0.0, max = 1.0))]
+        ratio: f64,
+        #[validate(required, url)]
+        homepage: Option<String>,
+        #[validate(nested)]
+        addresses: Vec<Address>,
+        #[validate(length(max = 3))]
+        tags: Vec<String>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, serde::Deserialize, Validate, crate::rules::ValidationRules)]
+    struct Address {
+        #[validate(length(equal = 5))]
+        zip: String,
+    }
+
+    #[test]
+    fn should_generate_valid_payloads() {
+        TestRunner::default()
+            .run(&valid::<CreateUser>(), |value| {
+                let user: CreateUser = serde_json::from_value(value)
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+                prop_assert!(user.validate().is_ok(), "{:?}", user.validate());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn should_generate_minimally_invalid_payloads() {
+        TestRunner::default()
+            .run(&invalid::<CreateUser>(), |payload| {
+                let user: CreateUser = serde_json::from_value(payload.value.clone())
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+                let errors = user.validate().unwrap_err();
+                // Validator reports errors by Rust field name
+                let Some(PathSegment::Field(field)) = payload.field.segments().first() else {
+                    return Err(TestCaseError::fail(format!("{payload:?}")));
+                };
+                let rules = CreateUser::rules();
+                let field = rules.iter().find(|f| f.serialized_name == field).unwrap();
+                prop_assert_eq!(errors.errors().len(), 1, "{:?}", payload);
+                prop_assert!(errors.errors().contains_key(field.name), "{:?}", payload);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn should_measure_lengths_in_the_length_mode() {
+        let rules = vec![
+            Rule::Length {
+                min: None,
+                max: None,
+                equal: Some(4),
+                mode: LengthMode::Bytes,
+            },
+            Rule::Contains("é".to_string()),
+        ];
+
+        TestRunner::default()
+            .run(&valid_string(&rules), |value| {
+                prop_assert_eq!(value.len(), 4, "{}", value);
+                prop_assert!(value.contains('é'), "{}", value);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, serde::Deserialize, serde::Serialize)]
+    enum Role {
+        Admin,
+        User,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, serde::Deserialize, Validate, crate::rules::ValidationRules)]
+    struct Member {
+        #[validate(length(min = 1))]
+        name: String,
+        role: Option<Role>,
+        #[validate(length(max = 2))]
+        roles: Vec<Role>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, serde::Deserialize, Validate, crate::rules::ValidationRules)]
+    struct Admin {
+        role: Role,
+    }
+
+    #[test]
+    fn should_leave_out_values_that_cannot_be_generated() {
+        TestRunner::default()
+            .run(&valid::<Member>(), |value| {
+                prop_assert!(value.get("role").is_none(), "{}", value);
+                prop_assert_eq!(&value["roles"], &Value::Array(Vec::new()));
+                let member: Member = serde_json::from_value(value)
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+                prop_assert!(member.validate().is_ok(), "{:?}", member.validate());
+                Ok(())
+            })
+            .unwrap();
+
+        TestRunner::default()
+            .run(&invalid::<Member>(), |payload| {
+                // Too many roles can't be generated, only the name is violated
+                prop_assert_eq!(&payload.field, &FieldPath::new().field("name"));
+                let member: Member = serde_json::from_value(payload.value)
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+                prop_assert!(member.validate().is_err());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "values of the required field `role`")]
+    fn should_reject_required_values_that_cannot_be_generated() {
+        let _ = valid::<Admin>();
+    }
+
+    #[test]
+    fn should_assert_rules_of_extractors() {
+        assert_rules::<CreateUser, crate::validator::Validated<Json<CreateUser>>>(
+            StatusCode::BAD_REQUEST,
+        );
+    }
+}
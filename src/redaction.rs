@@ -0,0 +1,297 @@
+//! Redaction of sensitive values from validation errors.
+//!
+//! Some validation libraries echo the rejected value back in their errors (eg. the `value` param
+//! of a [validator](https://docs.rs/validator/latest/validator) error). For passwords, tokens and
+//! other PII that value must never end up in a response or a log.
+//!
+//! There are two ways to mark a field as sensitive:
+//!
+//! * By field path, using a [`RedactionPolicy`] registered as app data.
+//!   The errors of matching fields are masked before they reach an error handler: the `value`
+//!   param of validator errors is replaced with [`REDACTED`], while garde and custom errors,
+//!   which only carry a message that may quote the value, get [`REDACTED`] as their message.
+//! * By marker, wrapping the field type in [`Redacted`].
+//!   The value is masked in its `Debug` output, including the `Debug` impl of `Validated`, and
+//!   in the errors of the validator backend. garde and custom messages are written by the
+//!   validation code, so a message quoting the inner value is not masked: list those fields in a
+//!   [`RedactionPolicy`] too.
+//!
+//! ```
+//! use actix_web::App;
+//! use actix_web_validation::redaction::RedactionPolicy;
+//!
+//! let app = App::new().app_data(RedactionPolicy::new().field("password").field("card.number"));
+//! ```
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "validator")]
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::ops::{Deref, DerefMut};
+
+use crate::path::FieldPath;
+
+/// The placeholder that replaces redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A set of field paths whose values must never be exposed in validation errors.
+///
+/// Paths name fields as they are declared in Rust (eg. `user.password`), regardless of the
+/// serialized names registered with [`field_names`](crate::field_names). The jsonschema backend
+/// validates JSON before it is deserialized, so its errors are matched by their JSON property
+/// names instead. List indices and map keys are ignored when matching, so `items.token` matches
+/// `items[0].token`, and a path also covers every field nested below it.
+#[derive(Clone, Debug, Default)]
+pub struct RedactionPolicy {
+    fields: Vec<FieldPath>,
+}
+
+impl RedactionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Redact the value of the field at `path`, see [`FieldPath::parse`]
+    pub fn field(mut self, path: &str) -> Self {
+        self.fields.push(FieldPath::parse(path).fields());
+        self
+    }
+
+    /// Returns true if the value of the field at `path` must be redacted
+    pub fn is_redacted(&self, path: &FieldPath) -> bool {
+        let path = path.fields();
+        self.fields
+            .iter()
+            .any(|field| path.segments().starts_with(field.segments()))
+    }
+}
+
+#[cfg(feature = "validator")]
+thread_local! {
+    static MASKING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with [`Redacted`] values serializing as [`REDACTED`].
+///
+/// validator serializes the rejected value into the `value` param of its errors, so the
+/// validator backend validates within this scope.
+#[cfg(feature = "validator")]
+pub(crate) fn masked<R>(f: impl FnOnce() -> R) -> R {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            MASKING.with(|masking| masking.set(self.0));
+        }
+    }
+
+    let _reset = Reset(MASKING.with(|masking| masking.replace(true)));
+    f()
+}
+
+#[cfg(feature = "serde")]
+fn is_masking() -> bool {
+    #[cfg(feature = "validator")]
+    return MASKING.with(|masking| masking.get());
+    #[cfg(not(feature = "validator"))]
+    return false;
+}
+
+/// Marks a value as sensitive.
+///
+/// `Redacted` serializes and deserializes transparently, so values survive round trips (eg. when
+/// persisting a DTO or applying a [`ValidatedPatch`](crate::patch)), but its `Debug` impl never
+/// exposes the wrapped value. Neither do validator errors, which serialize the rejected value.
+/// Validation rules are forwarded to the inner value, so it can be used as a drop-in replacement
+/// for the field type.
+///
+/// Validator errors are masked by serializing every `Redacted` value as [`REDACTED`] while the
+/// validator backend runs `validate` (or `TryFrom` for `ValidatedInto`), on the thread running it.
+/// Code called from there sees the mask too: a custom validation function serializing the value
+/// (eg. to hash or log it) gets [`REDACTED`], not the secret. Serialization anywhere else is
+/// unaffected.
+///
+/// The garde and custom backends do not serialize values, so their messages are whatever the
+/// validation code wrote. Only the `Debug` output of the marker is masked there; fields whose
+/// messages may quote the value need a [`RedactionPolicy`].
+///
+/// ```
+/// use actix_web_validation::redaction::Redacted;
+///
+/// let password = Redacted("hunter2".to_string());
+/// assert_eq!(format!("{password:?}"), "\"[REDACTED]\"");
+/// assert_eq!(password.len(), 7);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Redacted<T>(pub T);
+
+impl<T> Redacted<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Redacted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Redacted<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> Debug for Redacted<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(REDACTED, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for Redacted<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if is_masking() {
+            serializer.serialize_str(REDACTED)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Redacted<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Redacted)
+    }
+}
+
+#[cfg(feature = "validator")]
+mod validator_rules {
+    use super::Redacted;
+    use std::borrow::Cow;
+    use validator::{
+        AsRegex, ValidateContains, ValidateEmail, ValidateLength, ValidateRegex, ValidateRequired,
+        ValidateUrl,
+    };
+
+    impl<T: ValidateLength<u64>> ValidateLength<u64> for Redacted<T> {
+        fn length(&self) -> Option<u64> {
+            self.0.length()
+        }
+    }
+
+    impl<T: ValidateEmail> ValidateEmail for Redacted<T> {
+        fn as_email_string(&self) -> Option<Cow<'_, str>> {
+            self.0.as_email_string()
+        }
+    }
+
+    impl<T: ValidateUrl> ValidateUrl for Redacted<T> {
+        fn as_url_string(&self) -> Option<Cow<'_, str>> {
+            self.0.as_url_string()
+        }
+    }
+
+    impl<T: ValidateContains> ValidateContains for Redacted<T> {
+        fn validate_contains(&self, needle: &str) -> bool {
+            self.0.validate_contains(needle)
+        }
+    }
+
+    impl<T: ValidateRegex> ValidateRegex for Redacted<T> {
+        fn validate_regex(&self, regex: impl AsRegex) -> bool {
+            self.0.validate_regex(regex)
+        }
+    }
+
+    impl<T: ValidateRequired> ValidateRequired for Redacted<T> {
+        fn is_some(&self) -> bool {
+            self.0.is_some()
+        }
+    }
+}
+
+#[cfg(feature = "garde")]
+mod garde_rules {
+    use super::Redacted;
+    use garde::rules::length::{bytes::HasBytes, chars::HasChars, simple::HasSimpleLength};
+    use garde::rules::AsStr;
+
+    impl<T: AsStr> AsStr for Redacted<T> {
+        fn as_str(&self) -> &str {
+            self.0.as_str()
+        }
+    }
+
+    impl<T: HasSimpleLength> HasSimpleLength for Redacted<T> {
+        fn length(&self) -> usize {
+            self.0.length()
+        }
+    }
+
+    impl<T: HasBytes> HasBytes for Redacted<T> {
+        fn num_bytes(&self) -> usize {
+            self.0.num_bytes()
+        }
+    }
+
+    impl<T: HasChars> HasChars for Redacted<T> {
+        fn num_chars(&self) -> usize {
+            self.0.num_chars()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn policy_should_match_paths_ignoring_indices_and_keys() {
+        let policy = RedactionPolicy::new()
+            .field("password")
+            .field("cards[].number")
+            .field("secrets");
+
+        assert!(policy.is_redacted(&FieldPath::parse("password")));
+        assert!(policy.is_redacted(&FieldPath::parse("cards[0].number")));
+        assert!(policy.is_redacted(&FieldPath::parse("cards[12].number")));
+        assert!(policy.is_redacted(&FieldPath::new().field("secrets").key("aws.key")));
+        assert!(policy.is_redacted(&FieldPath::parse("secrets.nested.value")));
+        assert!(!policy.is_redacted(&FieldPath::parse("cards[0].expiry")));
+        assert!(!policy.is_redacted(&FieldPath::parse("username")));
+        assert!(!policy.is_redacted(&FieldPath::parse("passwords")));
+    }
+
+    #[test]
+    fn redacted_should_not_expose_value() {
+        let v = Redacted("hunter2".to_string());
+
+        assert_eq!(format!("{v:?}"), "\"[REDACTED]\"");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn redacted_should_serialize_transparently() {
+        let v = Redacted("hunter2".to_string());
+        assert_eq!(serde_json::to_string(&v).unwrap(), "\"hunter2\"");
+
+        let v: Redacted<String> = serde_json::from_str("\"hunter2\"").unwrap();
+        assert_eq!(v.as_str(), "hunter2");
+    }
+
+    #[cfg(feature = "validator")]
+    #[test]
+    fn redacted_should_be_masked_while_validating() {
+        let v = Redacted("hunter2".to_string());
+
+        let masked = masked(|| serde_json::to_string(&v).unwrap());
+        assert_eq!(masked, "\"[REDACTED]\"");
+        assert_eq!(serde_json::to_string(&v).unwrap(), "\"hunter2\"");
+    }
+}
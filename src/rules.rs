@@ -0,0 +1,290 @@
+//! Static metadata describing the validation rules of a type.
+//!
+//! Validation libraries only expose rules as code, so tooling that wants to reuse them
+//! (OpenAPI documents, JSON Schemas, test data generators, etc) needs a description of the rules
+//! that can be inspected at runtime. That description is provided by the [`ValidationRules`] trait.
+//!
+//! With the `derive` feature flag, `#[derive(ValidationRules)]` reads the `#[validate(...)]`
+//! (validator) and `#[garde(...)]` (garde) attributes of a struct and generates the
+//! implementation. Serde `rename`, `rename_all` and `skip` attributes are honored, so field names
+//! match what clients send.
+//!
+//! ```ignore
+//! use actix_web_validation::rules::ValidationRules;
+//!
+//! #[derive(Deserialize, Validate, ValidationRules)]
+//! #[serde(rename_all = "camelCase")]
+//! struct CreateUser {
+//!     #[validate(length(min = 3, max = 32))]
+//!     user_name: String,
+//!     #[validate(email)]
+//!     email: String,
+//! }
+//! ```
+
+#[cfg(feature = "derive")]
+pub use actix_web_validation_derive::ValidationRules;
+
+/// A type whose validation rules can be described at runtime.
+pub trait ValidationRules {
+    /// The rules of every (de)serialized field, in declaration order
+    fn rules() -> Vec<FieldRules>;
+}
+
+/// The validation rules of a single field
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRules {
+    /// The name of the Rust field
+    pub name: &'static str,
+    /// The name of the field as it is (de)serialized
+    pub serialized_name: &'static str,
+    /// The shape of the field value
+    pub field_type: FieldType,
+    /// `false` when the field may be omitted (eg. `Option` or `#[serde(default)]` fields)
+    pub required: bool,
+    /// `true` when the field accepts `null` (eg. `Option` fields)
+    pub nullable: bool,
+    pub rules: Vec<Rule>,
+}
+
+impl FieldRules {
+    /// Returns the length rule of the field, if any
+    pub fn length(&self) -> Option<(Option<u64>, Option<u64>)> {
+        self.rules.iter().find_map(|rule| match rule {
+            Rule::Length {
+                min, max, equal, ..
+            } => Some(match equal {
+                Some(equal) => (Some(*equal), Some(*equal)),
+                None => (*min, *max),
+            }),
+            _ => None,
+        })
+    }
+
+    /// Returns how the length rule of the field measures strings, if any
+    pub fn length_mode(&self) -> Option<LengthMode> {
+        self.rules.iter().find_map(|rule| match rule {
+            Rule::Length { mode, .. } => Some(*mode),
+            _ => None,
+        })
+    }
+}
+
+/// The shape of a field value
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array(Box<FieldType>),
+    /// A map with string keys
+    Map(Box<FieldType>),
+    /// A nested type with its own validation rules
+    Object(ObjectRules),
+    /// A type without known structure
+    Any,
+}
+
+/// The validation rules of a nested type.
+///
+/// Nested types are identified by their type name, so recursive types can be detected without
+/// calling [`ObjectRules::rules`] (which would recurse forever).
+#[derive(Debug, Clone, Copy)]
+pub struct ObjectRules {
+    type_name: &'static str,
+    rules: fn() -> Vec<FieldRules>,
+}
+
+impl ObjectRules {
+    pub fn of<T: ValidationRules + ?Sized>() -> Self {
+        Self {
+            type_name: std::any::type_name::<T>(),
+            rules: T::rules,
+        }
+    }
+
+    /// The name of the nested type, as returned by [`std::any::type_name`]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The rules of every (de)serialized field of the nested type
+    pub fn rules(&self) -> Vec<FieldRules> {
+        (self.rules)()
+    }
+}
+
+impl PartialEq for ObjectRules {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_name == other.type_name
+    }
+}
+
+/// A single validation rule
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Rule {
+    /// The length of a string (measured in `mode`) or the number of items of a collection
+    Length {
+        min: Option<u64>,
+        max: Option<u64>,
+        equal: Option<u64>,
+        mode: LengthMode,
+    },
+    Range {
+        min: Option<f64>,
+        max: Option<f64>,
+        exclusive_min: Option<f64>,
+        exclusive_max: Option<f64>,
+    },
+    /// A regular expression that the value must match
+    Pattern(String),
+    /// A substring that the value must contain
+    Contains(String),
+    Email,
+    Url,
+    /// An `Option` field that must be present
+    Required,
+}
+
+/// How the length of a string is measured. Collections always count their items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LengthMode {
+    /// Unicode code points, like validator's `length` and garde's `length(chars, ...)`
+    #[default]
+    Chars,
+    /// UTF-8 bytes, like garde's `length(...)` and `length(bytes, ...)`
+    Bytes,
+    /// Extended grapheme clusters, like garde's `length(graphemes, ...)`
+    Graphemes,
+    /// UTF-16 code units, like garde's `length(utf16, ...)`
+    Utf16,
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod test {
+    use super::*;
+    use validator::Validate as _;
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, validator::Validate, ValidationRules)]
+    #[serde(rename_all = "camelCase")]
+    struct CreateUser {
+        #[validate(length(min = 3, max = 32))]
+        user_name: String,
+        #[validate(email)]
+        email: Option<String>,
+        #[validate(range(min = 18, exclusive_max = 150))]
+        age: u8,
+        #[validate(required, url)]
+        homepage: Option<String>,
+        #[validate(nested)]
+        #[serde(rename = "home")]
+        address: Address,
+        #[validate(length(max = 3))]
+        tags: Vec<String>,
+        #[serde(skip)]
+        #[allow(unused)]
+        internal: bool,
+    }
+
+    #[derive(serde::Deserialize, validator::Validate, ValidationRules)]
+    struct Address {
+        #[validate(length(equal = 5))]
+        zip: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(serde::Deserialize, garde::Validate, ValidationRules)]
+    struct GardePayload {
+        #[garde(length(chars, min = 1), contains("-"))]
+        slug: String,
+        #[garde(range(min = 0.5, max = 1.5))]
+        #[serde(default)]
+        ratio: f64,
+        #[garde(skip)]
+        untouched: bool,
+        #[garde(length(max = 16))]
+        code: String,
+        #[garde(length(utf16, max = 16))]
+        emoji: String,
+    }
+
+    #[test]
+    fn derives_validator_rules() {
+        let rules = CreateUser::rules();
+        let names: Vec<_> = rules.iter().map(|f| f.serialized_name).collect();
+        assert_eq!(
+            names,
+            vec!["userName", "email", "age", "homepage", "home", "tags"]
+        );
+
+        assert_eq!(rules[0].name, "user_name");
+        assert_eq!(rules[0].field_type, FieldType::String);
+        assert!(rules[0].required);
+        assert_eq!(rules[0].length(), Some((Some(3), Some(32))));
+        assert_eq!(rules[0].length_mode(), Some(LengthMode::Chars));
+
+        assert!(!rules[1].required);
+        assert_eq!(rules[1].rules, vec![Rule::Email]);
+
+        assert_eq!(rules[2].field_type, FieldType::Integer);
+        assert_eq!(
+            rules[2].rules,
+            vec![Rule::Range {
+                min: Some(18.0),
+                max: None,
+                exclusive_min: None,
+                exclusive_max: Some(150.0),
+            }]
+        );
+
+        assert!(rules[3].required);
+        assert_eq!(rules[3].rules, vec![Rule::Required, Rule::Url]);
+
+        assert_eq!(
+            rules[4].field_type,
+            FieldType::Object(ObjectRules::of::<Address>())
+        );
+        assert_eq!(
+            rules[5].field_type,
+            FieldType::Array(Box::new(FieldType::String))
+        );
+    }
+
+    #[test]
+    fn derives_nested_rules() {
+        let FieldType::Object(nested) = CreateUser::rules()[4].field_type else {
+            panic!("expected a nested object");
+        };
+        assert_eq!(nested.rules()[0].length(), Some((Some(5), Some(5))));
+    }
+
+    #[test]
+    fn derives_garde_rules() {
+        let rules = GardePayload::rules();
+        assert_eq!(rules.len(), 5);
+
+        assert_eq!(rules[0].length(), Some((Some(1), None)));
+        assert_eq!(rules[0].length_mode(), Some(LengthMode::Chars));
+        assert_eq!(rules[3].length_mode(), Some(LengthMode::Bytes));
+        assert_eq!(rules[4].length_mode(), Some(LengthMode::Utf16));
+        assert!(rules[0].rules.contains(&Rule::Contains("-".to_string())));
+
+        assert!(!rules[1].required);
+        assert_eq!(rules[1].field_type, FieldType::Number);
+        assert_eq!(
+            rules[1].rules,
+            vec![Rule::Range {
+                min: Some(0.5),
+                max: Some(1.5),
+                exclusive_min: None,
+                exclusive_max: None,
+            }]
+        );
+
+        assert!(rules[2].rules.is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+//! Normalization of extracted values before validation.
+//!
+//! Many validation failures are caused by trivially fixable input, like trailing whitespace or
+//! `""` where `None` was meant. Types implementing [`Sanitize`] can be registered with
+//! [`SanitizerExt::sanitizer`], and `Validated` will sanitize the extracted value before
+//! validating it. The handler receives the sanitized value.
+//!
+//! With the `derive` feature flag, `#[derive(Sanitize)]` generates the implementation from
+//! `#[sanitizer(...)]` field attributes:
+//!
+//! * `trim` removes leading and trailing whitespace
+//! * `lowercase` / `uppercase` converts the value to lower/upper case
+//! * `empty_as_none` replaces `Some("")` with `None` on `Option<String>` fields (applied after
+//!   `trim`), other field types are rejected at compile time
+//! * `nested` sanitizes a field whose type implements [`Sanitize`]
+//!
+//! `trim`, `lowercase` and `uppercase` apply to `String`, `Option<String>`, collections of those
+//! and [`Redacted`] values.
+//!
+//! ```ignore
+//! use actix_web_validation::sanitize::{Sanitize, SanitizerExt};
+//!
+//! #[derive(Deserialize, Validate, Sanitize)]
+//! struct CreateUser {
+//!     #[sanitizer(trim, lowercase)]
+//!     #[validate(email)]
+//!     email: String,
+//!     #[sanitizer(trim, empty_as_none)]
+//!     nickname: Option<String>,
+//! }
+//!
+//! App::new().sanitizer::<CreateUser>()
+//! ```
+//!
+//! Values extracted with [`Json`], [`Form`], [`Query`] and [`Path`] are sanitized. Other
+//! extractors can be sanitized by implementing [`Sanitize`] for them and registering them with
+//! [`SanitizerExt::sanitizer`] as well:
+//!
+//! ```ignore
+//! impl<T: Sanitize> Sanitize for MyExtractor<T> {
+//!     fn sanitize(&mut self) {
+//!         self.0.sanitize()
+//!     }
+//! }
+//!
+//! App::new().sanitizer::<MyExtractor<CreateUser>>()
+//! ```
+//!
+//! Sanitizers are not applied by the `jsonschema` backend, which validates the raw body before
+//! it is deserialized.
+
+use actix_web::dev::{ServiceFactory, ServiceRequest};
+use actix_web::web::{Form, Json, Path, Query};
+use actix_web::App;
+
+use crate::redaction::Redacted;
+
+#[cfg(feature = "derive")]
+pub use actix_web_validation_derive::Sanitize;
+
+/// A type that can normalize itself before being validated
+pub trait Sanitize {
+    fn sanitize(&mut self);
+}
+
+impl<T: Sanitize> Sanitize for Option<T> {
+    fn sanitize(&mut self) {
+        if let Some(value) = self {
+            value.sanitize();
+        }
+    }
+}
+
+impl<T: Sanitize> Sanitize for Vec<T> {
+    fn sanitize(&mut self) {
+        self.iter_mut().for_each(Sanitize::sanitize);
+    }
+}
+
+impl<T: Sanitize + ?Sized> Sanitize for Box<T> {
+    fn sanitize(&mut self) {
+        (**self).sanitize();
+    }
+}
+
+macro_rules! sanitize_extractor_impl {
+    ($($extractor:ident),*) => {
+        $(
+            impl<T: Sanitize> Sanitize for $extractor<T> {
+                fn sanitize(&mut self) {
+                    (**self).sanitize();
+                }
+            }
+        )*
+    };
+}
+
+sanitize_extractor_impl!(Json, Form, Query, Path);
+
+/// String values that the string rules of `#[derive(Sanitize)]` can be applied to
+pub trait SanitizeStr {
+    /// Calls `f` with every string of the value
+    fn for_each_str(&mut self, f: &mut dyn FnMut(&mut String));
+}
+
+impl SanitizeStr for String {
+    fn for_each_str(&mut self, f: &mut dyn FnMut(&mut String)) {
+        f(self)
+    }
+}
+
+impl<T: SanitizeStr> SanitizeStr for Option<T> {
+    fn for_each_str(&mut self, f: &mut dyn FnMut(&mut String)) {
+        if let Some(value) = self {
+            value.for_each_str(f);
+        }
+    }
+}
+
+impl<T: SanitizeStr> SanitizeStr for Vec<T> {
+    fn for_each_str(&mut self, f: &mut dyn FnMut(&mut String)) {
+        self.iter_mut().for_each(|value| value.for_each_str(f));
+    }
+}
+
+impl<T: SanitizeStr> SanitizeStr for Redacted<T> {
+    fn for_each_str(&mut self, f: &mut dyn FnMut(&mut String)) {
+        self.0.for_each_str(f)
+    }
+}
+
+/// Removes leading and trailing whitespace
+pub fn trim<T: SanitizeStr + ?Sized>(value: &mut T) {
+    value.for_each_str(&mut |s| {
+        let trimmed = s.trim();
+        if trimmed.len() != s.len() {
+            *s = trimmed.to_string();
+        }
+    });
+}
+
+pub fn lowercase<T: SanitizeStr + ?Sized>(value: &mut T) {
+    value.for_each_str(&mut |s| *s = s.to_lowercase());
+}
+
+pub fn uppercase<T: SanitizeStr + ?Sized>(value: &mut T) {
+    value.for_each_str(&mut |s| *s = s.to_uppercase());
+}
+
+/// Replaces `Some("")` with `None`
+pub fn empty_as_none(value: &mut Option<String>) {
+    if value.as_deref() == Some("") {
+        *value = None;
+    }
+}
+
+/// The sanitizer registered for `T`
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+struct Sanitizer<T: ?Sized> {
+    sanitize: fn(&mut T),
+}
+
+impl<T: Sanitize + ?Sized> Sanitizer<T> {
+    fn new() -> Self {
+        Self {
+            sanitize: T::sanitize,
+        }
+    }
+}
+
+/// Sanitizes `value` if a sanitizer was registered for its type. Extractors are sanitized
+/// through their own [`Sanitize`] implementation, so `Validated` does not require `DerefMut`.
+#[cfg(any(feature = "validator", feature = "garde", feature = "custom"))]
+pub(crate) fn sanitize<T: ?Sized + 'static>(req: &actix_web::HttpRequest, value: &mut T) {
+    if let Some(sanitizer) = req.app_data::<Sanitizer<T>>() {
+        (sanitizer.sanitize)(value);
+    }
+}
+
+pub trait SanitizerExt {
+    /// Sanitizes extracted values of type `T` before they are validated, including values
+    /// extracted with [`Json`], [`Form`], [`Query`] and [`Path`]
+    fn sanitizer<T: Sanitize + 'static>(self) -> Self;
+}
+
+impl<A> SanitizerExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn sanitizer<T: Sanitize + 'static>(self) -> Self {
+        self.app_data(Sanitizer::<T>::new())
+            .app_data(Sanitizer::<Json<T>>::new())
+            .app_data(Sanitizer::<Form<T>>::new())
+            .app_data(Sanitizer::<Query<T>>::new())
+            .app_data(Sanitizer::<Path<T>>::new())
+    }
+}
+
+impl SanitizerExt for &mut actix_web::web::ServiceConfig {
+    fn sanitizer<T: Sanitize + 'static>(self) -> Self {
+        self.app_data(Sanitizer::<T>::new())
+            .app_data(Sanitizer::<Json<T>>::new())
+            .app_data(Sanitizer::<Form<T>>::new())
+            .app_data(Sanitizer::<Query<T>>::new())
+            .app_data(Sanitizer::<Path<T>>::new())
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl SanitizerExt for crate::test_util::ValidationProbe {
+    fn sanitizer<T: Sanitize + 'static>(self) -> Self {
+        self.app_data(Sanitizer::<T>::new())
+            .app_data(Sanitizer::<Json<T>>::new())
+            .app_data(Sanitizer::<Form<T>>::new())
+            .app_data(Sanitizer::<Query<T>>::new())
+            .app_data(Sanitizer::<Path<T>>::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_apply_string_rules() {
+        let mut value = Some(vec![" Foo ".to_string(), "BAR".to_string()]);
+        trim(&mut value);
+        lowercase(&mut value);
+        assert_eq!(value, Some(vec!["foo".to_string(), "bar".to_string()]));
+
+        let mut value = Redacted("secret ".to_string());
+        trim(&mut value);
+        uppercase(&mut value);
+        assert_eq!(value.into_inner(), "SECRET");
+    }
+
+    #[test]
+    fn should_replace_empty_strings_with_none() {
+        let mut value = Some(String::new());
+        empty_as_none(&mut value);
+        assert_eq!(value, None);
+
+        let mut value = Some(" ".to_string());
+        empty_as_none(&mut value);
+        assert_eq!(value, Some(" ".to_string()));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn should_derive_sanitize() {
+        #[derive(Debug, PartialEq, Sanitize)]
+        struct Address {
+            #[sanitizer(trim, uppercase)]
+            country: String,
+        }
+
+        #[derive(Debug, PartialEq, Sanitize)]
+        struct CreateUser {
+            #[sanitizer(trim, lowercase)]
+            email: String,
+            #[sanitizer(trim, empty_as_none)]
+            nickname: Option<String>,
+            #[sanitizer(nested)]
+            addresses: Vec<Address>,
+            untouched: String,
+        }
+
+        let mut user = CreateUser {
+            email: " John@Example.com\n".to_string(),
+            nickname: Some("   ".to_string()),
+            addresses: vec![Address {
+                country: " ca".to_string(),
+            }],
+            untouched: " as is ".to_string(),
+        };
+        user.sanitize();
+
+        assert_eq!(
+            user,
+            CreateUser {
+                email: "john@example.com".to_string(),
+                nickname: None,
+                addresses: vec![Address {
+                    country: "CA".to_string(),
+                }],
+                untouched: " as is ".to_string(),
+            }
+        );
+    }
+}
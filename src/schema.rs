@@ -0,0 +1,422 @@
+//! [JSON Schema](https://json-schema.org) generation from [`ValidationRules`].
+//! Requires the `schema` feature flag
+//!
+//! The generated schemas carry the same constraints as the server side validation
+//! (`minLength`, `maximum`, `pattern`, `format: email`, etc), so clients can validate input before
+//! sending it.
+//!
+//! ```ignore
+//! use actix_web_validation::rules::ValidationRules;
+//! use actix_web_validation::schema::{json_schema, Schemas};
+//!
+//! #[derive(Deserialize, Validate, ValidationRules)]
+//! struct CreateUser {
+//!     #[validate(length(min = 3, max = 32))]
+//!     name: String,
+//! }
+//!
+//! let schema = json_schema::<CreateUser>();
+//!
+//! // Serves the schema from `GET /schemas/CreateUser`
+//! App::new().service(Schemas::new("/schemas").register::<CreateUser>())
+//! ```
+//!
+//! Rules without a JSON Schema equivalent (eg. `contains`, or string lengths that are not
+//! measured in characters) are omitted.
+
+use std::collections::BTreeMap;
+
+use actix_web::dev::{AppService, HttpServiceFactory};
+use actix_web::{web, HttpRequest, HttpResponse, Resource};
+use serde_json::{json, Map, Value};
+
+use crate::rules::{FieldRules, FieldType, LengthMode, Rule, ValidationRules};
+
+/// The JSON Schema dialect of the generated schemas
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Returns the JSON Schema of `T`, generated from its validation rules
+pub fn json_schema<T: ValidationRules>() -> Value {
+    let mut schema = object_schema(&T::rules(), &mut Vec::new());
+    let map = schema.as_object_mut().expect("object schema");
+    map.insert("$schema".to_string(), json!(DIALECT));
+    map.insert("title".to_string(), json!(type_name::<T>()));
+    schema
+}
+
+fn object_schema(rules: &[FieldRules], parents: &mut Vec<&'static str>) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in rules {
+        properties.insert(
+            field.serialized_name.to_string(),
+            field_schema(field, parents),
+        );
+        if field.required {
+            required.push(json!(field.serialized_name));
+        }
+    }
+
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!("object"));
+    schema.insert("properties".to_string(), Value::Object(properties));
+    if !required.is_empty() {
+        schema.insert("required".to_string(), Value::Array(required));
+    }
+    Value::Object(schema)
+}
+
+fn field_schema(field: &FieldRules, parents: &mut Vec<&'static str>) -> Value {
+    let mut schema = type_schema(&field.field_type, parents);
+    let Value::Object(map) = &mut schema else {
+        return schema;
+    };
+
+    // `required` rejects `None`, so those fields don't accept `null`
+    if field.nullable && !field.rules.contains(&Rule::Required) {
+        if let Some(Value::String(field_type)) = map.remove("type") {
+            map.insert("type".to_string(), json!([field_type, "null"]));
+        }
+    }
+
+    if let Some((min, max)) = field.length() {
+        let keys = match field.field_type {
+            FieldType::Array(_) => Some(("minItems", "maxItems")),
+            FieldType::Map(_) | FieldType::Object(_) => Some(("minProperties", "maxProperties")),
+            // JSON Schema measures strings in code points, other length modes are omitted
+            _ if field.length_mode() == Some(LengthMode::Chars) => Some(("minLength", "maxLength")),
+            _ => None,
+        };
+        if let Some((min_key, max_key)) = keys {
+            if let Some(min) = min {
+                map.insert(min_key.to_string(), json!(min));
+            }
+            if let Some(max) = max {
+                map.insert(max_key.to_string(), json!(max));
+            }
+        }
+    }
+
+    for rule in &field.rules {
+        match rule {
+            Rule::Range {
+                min,
+                max,
+                exclusive_min,
+                exclusive_max,
+            } => {
+                let bounds = [
+                    ("minimum", min),
+                    ("maximum", max),
+                    ("exclusiveMinimum", exclusive_min),
+                    ("exclusiveMaximum", exclusive_max),
+                ];
+                for (key, value) in bounds {
+                    if let Some(value) = value {
+                        map.insert(key.to_string(), number(*value));
+                    }
+                }
+            }
+            Rule::Pattern(pattern) => {
+                map.insert("pattern".to_string(), json!(pattern));
+            }
+            Rule::Email => {
+                map.insert("format".to_string(), json!("email"));
+            }
+            Rule::Url => {
+                map.insert("format".to_string(), json!("uri"));
+            }
+            _ => {}
+        }
+    }
+
+    schema
+}
+
+fn type_schema(field_type: &FieldType, parents: &mut Vec<&'static str>) -> Value {
+    match field_type {
+        FieldType::String => json!({ "type": "string" }),
+        FieldType::Integer => json!({ "type": "integer" }),
+        FieldType::Number => json!({ "type": "number" }),
+        FieldType::Boolean => json!({ "type": "boolean" }),
+        FieldType::Array(items) => json!({ "type": "array", "items": type_schema(items, parents) }),
+        FieldType::Map(values) => {
+            json!({ "type": "object", "additionalProperties": type_schema(values, parents) })
+        }
+        FieldType::Object(object) => {
+            // Recursive types are only expanded once
+            if parents.contains(&object.type_name()) {
+                return json!({ "type": "object" });
+            }
+            parents.push(object.type_name());
+            let schema = object_schema(&object.rules(), parents);
+            parents.pop();
+            schema
+        }
+        FieldType::Any => json!({}),
+    }
+}
+
+fn number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
+
+/// The name of a type without its module path and generics
+fn type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split_once('<').map(|(name, _)| name).unwrap_or(name);
+    name.rsplit_once("::").map(|(_, name)| name).unwrap_or(name)
+}
+
+/// A service serving the JSON Schemas of registered types from `GET {path}/{type}`.
+///
+/// Types are registered under their name (without module path), or a custom name with
+/// [`Schemas::register_as`]. Unknown names respond with `404 Not Found`.
+///
+/// ```ignore
+/// App::new().service(
+///     Schemas::new("/schemas")
+///         .register::<CreateUser>()
+///         .register_as::<UpdateUser>("user-update"),
+/// )
+/// ```
+pub struct Schemas {
+    path: String,
+    schemas: BTreeMap<String, Value>,
+}
+
+impl Schemas {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            schemas: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the schema of `T` under the name of `T`
+    pub fn register<T: ValidationRules>(self) -> Self {
+        self.register_as::<T>(type_name::<T>())
+    }
+
+    /// Registers the schema of `T` under a custom name
+    pub fn register_as<T: ValidationRules>(mut self, name: impl Into<String>) -> Self {
+        self.schemas.insert(name.into(), json_schema::<T>());
+        self
+    }
+}
+
+impl HttpServiceFactory for Schemas {
+    fn register(self, config: &mut AppService) {
+        let path = format!("{}/{{type}}", self.path.trim_end_matches('/'));
+        let resource = Resource::new(path)
+            .app_data(web::Data::new(self.schemas))
+            .route(web::get().to(serve_schema));
+        HttpServiceFactory::register(resource, config)
+    }
+}
+
+async fn serve_schema(
+    req: HttpRequest,
+    schemas: web::Data<BTreeMap<String, Value>>,
+) -> HttpResponse {
+    let name = req.match_info().get("type").unwrap_or_default();
+    match schemas.get(name) {
+        Some(schema) => HttpResponse::Ok()
+            .content_type("application/schema+json")
+            .json(schema),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::{test, App};
+    use serde::Deserialize;
+    use serde_json::json;
+    use validator::Validate;
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Validate, ValidationRules)]
+    #[serde(rename_all = "camelCase")]
+    struct CreateUser {
+        #[validate(length(min = 3, max = 32))]
+        user_name: String,
+        #[validate(email)]
+        email: Option<String>,
+        #[validate(range(min = 18, max = 150))]
+        age: u8,
+        #[validate(length(min = 1, max = 5))]
+        tags: Vec<String>,
+        #[validate(nested)]
+        address: Address,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Validate, ValidationRules)]
+    struct Address {
+        #[validate(url)]
+        website: String,
+        #[validate(range(exclusive_min = 0.5))]
+        score: f64,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Validate, ValidationRules)]
+    struct Category {
+        #[validate(length(min = 1))]
+        name: String,
+        #[validate(nested)]
+        children: Vec<Category>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, garde::Validate, ValidationRules)]
+    struct Slug {
+        #[garde(length(chars, min = 1))]
+        name: String,
+        #[garde(length(max = 16))]
+        code: String,
+        #[garde(length(utf16, max = 16))]
+        label: String,
+        #[garde(length(max = 2))]
+        tags: Vec<String>,
+    }
+
+    #[actix_web::test]
+    async fn should_only_emit_string_lengths_measured_in_characters() {
+        let schema = json_schema::<Slug>();
+
+        assert_eq!(
+            schema["properties"],
+            json!({
+                "name": { "type": "string", "minLength": 1 },
+                "code": { "type": "string" },
+                "label": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" }, "maxItems": 2 },
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn should_generate_json_schema() {
+        let schema = json_schema::<CreateUser>();
+
+        assert_eq!(
+            schema,
+            json!({
+                "$schema": DIALECT,
+                "title": "CreateUser",
+                "type": "object",
+                "properties": {
+                    "userName": { "type": "string", "minLength": 3, "maxLength": 32 },
+                    "email": { "type": ["string", "null"], "format": "email" },
+                    "age": { "type": "integer", "minimum": 18, "maximum": 150 },
+                    "tags": { "type": "array", "items": { "type": "string" }, "minItems": 1, "maxItems": 5 },
+                    "address": {
+                        "type": "object",
+                        "properties": {
+                            "website": { "type": "string", "format": "uri" },
+                            "score": { "type": "number", "exclusiveMinimum": 0.5 },
+                        },
+                        "required": ["website", "score"],
+                    },
+                },
+                "required": ["userName", "age", "tags", "address"],
+            })
+        );
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Validate, ValidationRules)]
+    struct Profile {
+        #[validate(length(min = 2))]
+        nickname: Option<String>,
+        #[serde(default)]
+        bio: String,
+        #[validate(nested)]
+        address: Option<Address>,
+        #[validate(required, email)]
+        email: Option<String>,
+    }
+
+    #[actix_web::test]
+    async fn should_accept_null_for_optional_fields() {
+        let schema = json_schema::<Profile>();
+        let properties = &schema["properties"];
+
+        assert_eq!(
+            properties["nickname"],
+            json!({ "type": ["string", "null"], "minLength": 2 })
+        );
+        assert_eq!(properties["bio"], json!({ "type": "string" }));
+        assert_eq!(properties["address"]["type"], json!(["object", "null"]));
+        assert_eq!(
+            properties["email"],
+            json!({ "type": "string", "format": "email" })
+        );
+        assert_eq!(schema["required"], json!(["email"]));
+    }
+
+    #[actix_web::test]
+    async fn should_expand_recursive_types_once() {
+        let schema = json_schema::<Category>();
+
+        assert_eq!(
+            schema["properties"]["children"],
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "minLength": 1 },
+                        "children": { "type": "array", "items": { "type": "object" } },
+                    },
+                    "required": ["name", "children"],
+                },
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn should_serve_registered_schemas() {
+        let app = test::init_service(
+            App::new().service(
+                Schemas::new("/schemas")
+                    .register::<CreateUser>()
+                    .register_as::<Address>("address"),
+            ),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/schemas/CreateUser")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "application/schema+json"
+        );
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body, json_schema::<CreateUser>());
+
+        let req = test::TestRequest::get()
+            .uri("/schemas/address")
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["title"], json!("Address"));
+
+        let req = test::TestRequest::get()
+            .uri("/schemas/Unknown")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
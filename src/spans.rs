@@ -0,0 +1,421 @@
+//! Source locations of invalid values in JSON bodies. Requires the `spans` feature flag
+//!
+//! Finding a deeply nested value that failed validation is hard for clients. Register the types
+//! whose errors should point at the offending value in the original body:
+//!
+//! ```ignore
+//! use actix_web_validation::spans::SourceSpansExt;
+//!
+//! App::new().source_spans::<CreateOrder>()
+//! ```
+//!
+//! The body read by the inner extractor (eg. [`Json`](actix_web::web::Json)) is recorded and the
+//! [`Span`] of each invalid value is added to its error: as the `line`, `column` and `offset`
+//! params (`validator`), appended to the message (`garde`) or stored in the error (`custom` and
+//! `jsonschema`). Error handlers read it back with [`ErrorSpan::span`] and the built-in error
+//! responses show it after each error:
+//!
+//! ```text
+//! Validation errors in fields:
+//!     items[1].name: Validation error: length [...] (line 4, column 14)
+//! ```
+//!
+//! Spans are looked up by the path of the error in the body, so types with renamed fields must
+//! also be registered with [`serialized_names`](crate::field_names::SerializedNamesExt).
+//! Only JSON bodies are supported.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+use actix_web::dev::{Payload, ServiceFactory, ServiceRequest};
+use actix_web::{App, HttpRequest};
+
+use crate::path::{FieldPath, PathSyntax};
+
+/// Values nested deeper are not located, matching the recursion limit of serde_json
+const MAX_DEPTH: usize = 128;
+
+/// The location of a value in the request body
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// The byte offset of the first byte of the value
+    pub offset: usize,
+    /// The line of the value, starting at 1
+    pub line: usize,
+    /// The column of the value in characters, starting at 1
+    pub column: usize,
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// The source span of a validation error, set for the types registered with
+/// [`source_spans`](SourceSpansExt::source_spans)
+pub trait ErrorSpan {
+    /// The location of the invalid value in the request body
+    fn span(&self) -> Option<Span>;
+}
+
+/// Read from the `line`, `column` and `offset` params
+#[cfg(feature = "validator")]
+impl ErrorSpan for validator::ValidationError {
+    fn span(&self) -> Option<Span> {
+        let param = |name: &str| {
+            self.params
+                .get(name)
+                .and_then(serde_json::Value::as_u64)
+                .map(|value| value as usize)
+        };
+        Some(Span {
+            offset: param("offset")?,
+            line: param("line")?,
+            column: param("column")?,
+        })
+    }
+}
+
+/// Parsed from the end of the message, eg. `(line 4, column 14, offset 52)`
+#[cfg(feature = "garde")]
+impl ErrorSpan for garde::Error {
+    fn span(&self) -> Option<Span> {
+        let message = self.message().strip_suffix(')')?;
+        let (_, span) = message.rsplit_once(" (line ")?;
+        let (line, rest) = span.split_once(", column ")?;
+        let (column, offset) = rest.split_once(", offset ")?;
+        Some(Span {
+            offset: offset.parse().ok()?,
+            line: line.parse().ok()?,
+            column: column.parse().ok()?,
+        })
+    }
+}
+
+#[cfg(feature = "custom")]
+impl ErrorSpan for crate::custom::ValidationError {
+    fn span(&self) -> Option<Span> {
+        crate::custom::ValidationError::span(self)
+    }
+}
+
+#[cfg(feature = "jsonschema")]
+impl ErrorSpan for crate::jsonschema::SchemaError {
+    fn span(&self) -> Option<Span> {
+        crate::jsonschema::SchemaError::span(self)
+    }
+}
+
+/// Appends the span of `error` to a line of the built-in error responses
+#[cfg(any(feature = "validator", feature = "custom", feature = "jsonschema"))]
+pub(crate) fn with_span(line: String, error: &impl ErrorSpan) -> String {
+    match error.span() {
+        Some(span) => format!("{line} ({span})"),
+        None => line,
+    }
+}
+
+/// The spans of the values of a JSON body, by path
+#[derive(Clone, Debug, Default)]
+pub struct SpanMap {
+    spans: HashMap<String, Span>,
+}
+
+impl SpanMap {
+    /// Locates the values of the JSON `body`. Values after a syntax error are not located.
+    pub fn parse(body: &[u8]) -> Self {
+        let mut scanner = Scanner {
+            body,
+            pos: 0,
+            line: 1,
+            column: 1,
+            counted: 0,
+            offsets: HashMap::new(),
+        };
+        let _ = scanner.value(&mut String::new(), 0);
+        Self {
+            spans: scanner.offsets,
+        }
+    }
+
+    /// The span of the value at `path`
+    pub fn get(&self, path: &FieldPath) -> Option<Span> {
+        self.spans
+            .get(&path.render(PathSyntax::JsonPointer))
+            .copied()
+    }
+}
+
+/// A minimal JSON scanner recording the span of every value by its JSON Pointer
+struct Scanner<'a> {
+    body: &'a [u8],
+    pos: usize,
+    line: usize,
+    /// The column of the byte at `counted`
+    column: usize,
+    /// The bytes of the current line before this offset are counted in `column`
+    counted: usize,
+    offsets: HashMap<String, Span>,
+}
+
+impl Scanner<'_> {
+    fn value(&mut self, pointer: &mut String, depth: usize) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        self.whitespace();
+        // Only the bytes consumed since the previous value are counted, as rescanning the line
+        // would be quadratic on minified bodies
+        self.column += self.body[self.counted..self.pos]
+            .iter()
+            .filter(|&&byte| byte & 0xC0 != 0x80)
+            .count();
+        self.counted = self.pos;
+        self.offsets.insert(
+            pointer.clone(),
+            Span {
+                offset: self.pos,
+                line: self.line,
+                column: self.column,
+            },
+        );
+
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+                self.whitespace();
+                if self.peek()? == b'}' {
+                    self.pos += 1;
+                    return Some(());
+                }
+                loop {
+                    self.whitespace();
+                    let key = self.string()?;
+                    self.whitespace();
+                    self.expect(b':')?;
+
+                    let len = pointer.len();
+                    pointer.push('/');
+                    pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                    self.value(pointer, depth + 1)?;
+                    pointer.truncate(len);
+
+                    self.whitespace();
+                    match self.next()? {
+                        b',' => continue,
+                        b'}' => return Some(()),
+                        _ => return None,
+                    }
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                self.whitespace();
+                if self.peek()? == b']' {
+                    self.pos += 1;
+                    return Some(());
+                }
+                for index in 0.. {
+                    let len = pointer.len();
+                    pointer.push_str(&format!("/{index}"));
+                    self.value(pointer, depth + 1)?;
+                    pointer.truncate(len);
+
+                    self.whitespace();
+                    match self.next()? {
+                        b',' => continue,
+                        b']' => return Some(()),
+                        _ => return None,
+                    }
+                }
+                None
+            }
+            b'"' => self.string().map(|_| ()),
+            _ => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n')
+                ) {
+                    self.pos += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    /// Reads a string, returning its unescaped value
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.next()? {
+                b'\\' => self.pos += 1,
+                b'"' => break,
+                _ => {}
+            }
+        }
+        serde_json::from_slice(self.body.get(start..self.pos)?).ok()
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(byte @ (b' ' | b'\t' | b'\r' | b'\n')) = self.peek() {
+            self.pos += 1;
+            if byte == b'\n' {
+                self.line += 1;
+                self.column = 1;
+                self.counted = self.pos;
+            }
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.next()? == byte).then_some(())
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.body.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+}
+
+/// The source spans registered for `T`
+#[cfg_attr(
+    not(any(
+        feature = "validator",
+        feature = "garde",
+        feature = "custom",
+        feature = "jsonschema"
+    )),
+    allow(dead_code)
+)]
+struct SourceSpans<T: ?Sized> {
+    _type: PhantomData<fn() -> T>,
+}
+
+/// Records the body read from `payload` if spans are reported for `T`, returning the span map
+/// to build once the body was extracted
+#[cfg_attr(
+    not(any(
+        feature = "validator",
+        feature = "garde",
+        feature = "custom",
+        feature = "jsonschema"
+    )),
+    allow(dead_code)
+)]
+pub(crate) fn record<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    payload: &mut Payload,
+) -> Option<Box<dyn FnOnce() -> SpanMap>> {
+    req.app_data::<SourceSpans<T>>()?;
+
+    let body = crate::body::record(payload);
+    Some(Box::new(move || SpanMap::parse(&body.borrow())))
+}
+
+pub trait SourceSpansExt {
+    /// Reports the source location of invalid values in JSON bodies extracted as `T`
+    fn source_spans<T: 'static>(self) -> Self;
+}
+
+impl<A> SourceSpansExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn source_spans<T: 'static>(self) -> Self {
+        self.app_data(SourceSpans::<T> { _type: PhantomData })
+    }
+}
+
+impl SourceSpansExt for &mut actix_web::web::ServiceConfig {
+    fn source_spans<T: 'static>(self) -> Self {
+        self.app_data(SourceSpans::<T> { _type: PhantomData })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl SourceSpansExt for crate::test_util::ValidationProbe {
+    fn source_spans<T: 'static>(self) -> Self {
+        self.app_data(SourceSpans::<T> { _type: PhantomData })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_locate_values() {
+        let body = "{\n  \"name\": \"a\",\n  \"items\": [1, {\"a/b\": \"é\", \"c\": null}]\n}";
+        let spans = SpanMap::parse(body.as_bytes());
+
+        let span = |path: FieldPath| spans.get(&path).map(|s| (s.line, s.column, s.offset));
+        assert_eq!(span(FieldPath::new()), Some((1, 1, 0)));
+        assert_eq!(span(FieldPath::new().field("name")), Some((2, 11, 12)));
+        assert_eq!(
+            span(FieldPath::new().field("items").index(0)),
+            Some((3, 13, 29))
+        );
+        assert_eq!(
+            span(FieldPath::new().field("items").index(1).key("a/b")),
+            Some((3, 24, 40))
+        );
+        assert_eq!(
+            span(FieldPath::new().field("items").index(1).field("c")),
+            Some((3, 34, 51))
+        );
+        assert_eq!(span(FieldPath::new().field("missing")), None);
+    }
+
+    #[test]
+    fn should_locate_values_far_into_a_single_line() {
+        let body = format!("[{}\"é\"]", "\"é\",".repeat(100_000));
+        let spans = SpanMap::parse(body.as_bytes());
+
+        let span = spans.get(&FieldPath::new().index(100_000)).unwrap();
+        assert_eq!(span.line, 1);
+        assert_eq!(span.column, 400_002);
+        assert_eq!(span.offset, 500_001);
+    }
+
+    #[cfg(feature = "validator")]
+    #[test]
+    fn should_read_spans_of_validator_errors() {
+        let mut error = validator::ValidationError::new("length");
+        assert_eq!(error.span(), None);
+
+        error.add_param("line".into(), &4);
+        error.add_param("column".into(), &14);
+        error.add_param("offset".into(), &52);
+        assert_eq!(
+            error.span(),
+            Some(Span {
+                offset: 52,
+                line: 4,
+                column: 14,
+            })
+        );
+    }
+
+    #[cfg(feature = "garde")]
+    #[test]
+    fn should_read_spans_of_garde_errors() {
+        assert_eq!(garde::Error::new("length is lower than 5").span(), None);
+        assert_eq!(
+            garde::Error::new("length is lower than 5 (line 4, column 14, offset 52)").span(),
+            Some(Span {
+                offset: 52,
+                line: 4,
+                column: 14,
+            })
+        );
+    }
+}
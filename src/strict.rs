@@ -0,0 +1,160 @@
+//! Rejection of unknown fields in JSON bodies. Requires the `strict` feature flag
+//!
+//! serde ignores unknown keys, so a typo like `"usernme"` silently passes validation. Register
+//! the types that should reject unknown fields, even without `#[serde(deny_unknown_fields)]`:
+//!
+//! ```ignore
+//! use actix_web_validation::strict::StrictFieldsExt;
+//!
+//! App::new().strict_fields::<CreateUser>()
+//! ```
+//!
+//! The body read by the inner extractor (eg. [`Json`](actix_web::web::Json)) is recorded and
+//! checked for keys that are not deserialized into the type. Each unknown field is reported
+//! alongside the other validation errors, with the `unknown_field` code (`validator` and
+//! `custom`) or the `unknown field` message (`garde`, which has no error codes):
+//!
+//! ```text
+//! Validation errors in fields:
+//!     usernme: Validation error: unknown_field [{}]
+//! ```
+//!
+//! Only JSON bodies are checked.
+
+use std::marker::PhantomData;
+
+use actix_web::dev::{Payload, ServiceFactory, ServiceRequest};
+use actix_web::{App, HttpRequest};
+use serde::de::DeserializeOwned;
+
+use crate::path::FieldPath;
+
+/// The unknown field check registered for `T`
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+struct StrictFields<T: ?Sized> {
+    unknown_fields: fn(&[u8]) -> Vec<FieldPath>,
+    _type: PhantomData<fn() -> T>,
+}
+
+/// The paths (eg. `address.zip` or `tags[0].nme`) of the fields in the JSON `body` that are
+/// not deserialized into `T`.
+///
+/// serde does not tell map keys apart from struct fields, so both are
+/// [`Field`](crate::path::PathSegment::Field) segments.
+pub fn unknown_fields<T: DeserializeOwned>(body: &[u8]) -> Vec<FieldPath> {
+    let mut fields = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let _ = serde_ignored::deserialize::<_, _, T>(&mut deserializer, |path| {
+        fields.push(field_path(&path))
+    });
+    fields
+}
+
+fn field_path(path: &serde_ignored::Path) -> FieldPath {
+    match path {
+        serde_ignored::Path::Root => FieldPath::new(),
+        serde_ignored::Path::Seq { parent, index } => field_path(parent).index(*index),
+        serde_ignored::Path::Map { parent, key } => field_path(parent).field(key.as_str()),
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => field_path(parent),
+    }
+}
+
+/// Records the body read from `payload` if unknown fields are rejected for `T`, returning the
+/// check to run once the body was extracted
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+pub(crate) fn record<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    payload: &mut Payload,
+) -> Option<Box<dyn FnOnce() -> Vec<FieldPath>>> {
+    let unknown_fields = req.app_data::<StrictFields<T>>()?.unknown_fields;
+
+    let body = crate::body::record(payload);
+    Some(Box::new(move || unknown_fields(&body.borrow())))
+}
+
+/// The check of the JSON `body` for unknown fields if they are rejected for `T`
+#[cfg(feature = "patch")]
+pub(crate) fn check<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    body: Vec<u8>,
+) -> Option<Box<dyn FnOnce() -> Vec<FieldPath>>> {
+    let unknown_fields = req.app_data::<StrictFields<T>>()?.unknown_fields;
+    Some(Box::new(move || unknown_fields(&body)))
+}
+
+pub trait StrictFieldsExt {
+    /// Rejects unknown fields in JSON bodies extracted as `T`
+    fn strict_fields<T: DeserializeOwned + 'static>(self) -> Self;
+}
+
+impl<A> StrictFieldsExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn strict_fields<T: DeserializeOwned + 'static>(self) -> Self {
+        self.app_data(StrictFields::<T> {
+            unknown_fields: unknown_fields::<T>,
+            _type: PhantomData,
+        })
+    }
+}
+
+impl StrictFieldsExt for &mut actix_web::web::ServiceConfig {
+    fn strict_fields<T: DeserializeOwned + 'static>(self) -> Self {
+        self.app_data(StrictFields::<T> {
+            unknown_fields: unknown_fields::<T>,
+            _type: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl StrictFieldsExt for crate::test_util::ValidationProbe {
+    fn strict_fields<T: DeserializeOwned + 'static>(self) -> Self {
+        self.app_data(StrictFields::<T> {
+            unknown_fields: unknown_fields::<T>,
+            _type: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct User {
+        name: String,
+        addresses: Vec<Address>,
+        nickname: Option<String>,
+    }
+
+    #[test]
+    fn should_find_unknown_fields() {
+        let body = br#"{"name": "a", "nme": "b", "addresses": [{"city": "c", "zip": "d"}]}"#;
+        assert_eq!(
+            unknown_fields::<User>(body),
+            vec![
+                FieldPath::new().field("nme"),
+                FieldPath::new().field("addresses").index(0).field("zip")
+            ]
+        );
+        assert!(unknown_fields::<User>(br#"{"name": "a", "addresses": []}"#).is_empty());
+    }
+}
@@ -0,0 +1,440 @@
+//! Helpers for testing validated endpoints and DTOs. Requires the `test-util` feature flag
+//!
+//! [`assert_validation_error`] reads a response and the errors of the backend it was created from,
+//! so assertions don't depend on the exact response body:
+//!
+//! ```ignore
+//! use actix_web_validation::test_util::assert_validation_error;
+//!
+//! let resp = test::call_service(&app, req).await;
+//! assert_validation_error(resp)
+//!     .await
+//!     .has_field("name")
+//!     .with_code("length");
+//! ```
+//!
+//! [`ValidationProbe`] runs an extractor against a request without an `App`, applying the same
+//! app data (error handlers, sanitizers, [`ErrorLimit`](crate::limit::ErrorLimit), ...):
+//!
+//! ```ignore
+//! use actix_web_validation::test_util::ValidationProbe;
+//!
+//! ValidationProbe::json(&json!({ "name": "a" }))
+//!     .app_data(ErrorLimit::fail_fast())
+//!     .assert_invalid::<Validated<Json<CreateUser>>>()
+//!     .await
+//!     .has_field("name");
+//! ```
+//!
+//! Errors are read from the error type of the backend (eg. [`crate::validator::Error`]) rather
+//! than from the response text, so messages and path syntaxes don't affect the assertions.
+//! Codes are reported by every backend but `garde`, which has none. Responses of custom error
+//! handlers carry no errors, use [`ValidationErrorResponse::body`] for them.
+
+use std::fmt::Display;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::ServiceResponse;
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use actix_web::web::Bytes;
+use actix_web::{FromRequest, HttpResponse};
+
+use crate::limit::TRUNCATED_HEADER;
+use crate::path::{FieldPath, PathSyntax};
+
+/// A validation error reported by a backend
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReportedError {
+    field: Option<FieldPath>,
+    code: Option<String>,
+    message: String,
+}
+
+impl ReportedError {
+    #[cfg_attr(
+        not(any(
+            feature = "validator",
+            feature = "garde",
+            feature = "custom",
+            feature = "jsonschema"
+        )),
+        allow(dead_code)
+    )]
+    fn new(field: Option<FieldPath>, code: Option<String>, message: String) -> Self {
+        Self {
+            field: field.filter(|f| !f.is_empty()),
+            code,
+            message,
+        }
+    }
+
+    /// The path of the invalid field, if reported
+    pub fn field(&self) -> Option<&FieldPath> {
+        self.field.as_ref()
+    }
+
+    /// The error code, if reported
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ReportedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{field}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// The errors carried by `error` if it is the error type of a backend
+#[cfg_attr(
+    not(any(
+        feature = "validator",
+        feature = "garde",
+        feature = "custom",
+        feature = "jsonschema"
+    )),
+    allow(unused_variables)
+)]
+fn reported_errors(error: &actix_web::Error) -> Vec<ReportedError> {
+    #[cfg(feature = "validator")]
+    if let Some(error) = error.as_error::<crate::validator::Error>() {
+        return crate::validator::error_paths(error.errors())
+            .into_iter()
+            .map(|(path, e)| {
+                ReportedError::new(Some(path), Some(e.code.to_string()), e.to_string())
+            })
+            .collect();
+    }
+
+    #[cfg(feature = "garde")]
+    if let Some(error) = error.as_error::<crate::garde::Error>() {
+        return crate::garde::error_paths(error.report())
+            .into_iter()
+            .map(|(path, e)| ReportedError::new(Some(path), None, e.message().to_string()))
+            .collect();
+    }
+
+    #[cfg(feature = "custom")]
+    if let Some(error) = error.as_error::<crate::custom::Error>() {
+        return error
+            .errors()
+            .iter()
+            .map(|e| {
+                ReportedError::new(
+                    e.path().cloned(),
+                    e.code().map(str::to_string),
+                    e.message().to_string(),
+                )
+            })
+            .collect();
+    }
+
+    #[cfg(feature = "jsonschema")]
+    if let Some(error) = error.as_error::<crate::jsonschema::Error>() {
+        return error
+            .errors()
+            .iter()
+            .map(|e| {
+                ReportedError::new(
+                    Some(e.field_path()),
+                    Some(e.keyword().to_string()),
+                    e.message().to_string(),
+                )
+            })
+            .collect();
+    }
+
+    Vec::new()
+}
+
+/// A validation error response and the errors reported by the backend
+#[derive(Clone, Debug)]
+pub struct ValidationErrorResponse {
+    status: StatusCode,
+    truncated: bool,
+    body: Bytes,
+    errors: Vec<ReportedError>,
+}
+
+impl ValidationErrorResponse {
+    /// Reads an error response. Panics if the response is not an error.
+    pub fn from_response(response: HttpResponse) -> Self {
+        let status = response.status();
+        let truncated = response.headers().contains_key(TRUNCATED_HEADER);
+        let errors = response.error().map(reported_errors).unwrap_or_default();
+        let body = response
+            .into_body()
+            .try_into_bytes()
+            .unwrap_or_else(|_| panic!("expected a validation error response with a full body"));
+        Self::new(status, truncated, body, errors)
+    }
+
+    fn new(status: StatusCode, truncated: bool, body: Bytes, errors: Vec<ReportedError>) -> Self {
+        assert!(
+            status.is_client_error() || status.is_server_error(),
+            "expected a validation error response, got {status}: {}",
+            String::from_utf8_lossy(&body)
+        );
+
+        Self {
+            status,
+            truncated,
+            body,
+            errors,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The raw response body
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    pub fn errors(&self) -> &[ReportedError] {
+        &self.errors
+    }
+
+    /// Returns `true` when errors were dropped because of the [`ErrorLimit`](crate::limit::ErrorLimit)
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Asserts the response has the given status
+    pub fn has_status(&self, status: impl TryInto<StatusCode>) -> &Self {
+        let status = status
+            .try_into()
+            .unwrap_or_else(|_| panic!("invalid status code"));
+        assert_eq!(self.status, status, "unexpected status, body: {}", self);
+        self
+    }
+
+    /// Asserts the response reports `count` errors
+    pub fn has_errors(&self, count: usize) -> &Self {
+        assert_eq!(self.errors.len(), count, "unexpected errors: {}", self);
+        self
+    }
+
+    /// Asserts the response reports an error for `field`, a dotted path (eg. `items[0].name`)
+    /// or a JSON Pointer (eg. `/items/0/name`)
+    pub fn has_field(&self, field: &str) -> FieldErrors<'_> {
+        let errors = self
+            .errors
+            .iter()
+            .filter(|e| e.field().is_some_and(|path| is_field(path, field)))
+            .collect::<Vec<_>>();
+        assert!(
+            !errors.is_empty(),
+            "no error for field `{field}` in: {}",
+            self
+        );
+        FieldErrors {
+            field: field.to_string(),
+            errors,
+        }
+    }
+
+    /// Asserts the response does not report an error for `field`
+    pub fn lacks_field(&self, field: &str) -> &Self {
+        assert!(
+            !self
+                .errors
+                .iter()
+                .any(|e| e.field().is_some_and(|path| is_field(path, field))),
+            "unexpected error for field `{field}` in: {}",
+            self
+        );
+        self
+    }
+}
+
+/// JSON Pointers don't tell map keys from fields, so they are compared rendered
+fn is_field(path: &FieldPath, field: &str) -> bool {
+    if field.starts_with('/') {
+        path.render(PathSyntax::JsonPointer) == field
+    } else {
+        *path == FieldPath::parse(field)
+    }
+}
+
+impl Display for ValidationErrorResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.body))
+    }
+}
+
+/// The errors reported for a single field
+#[derive(Debug)]
+pub struct FieldErrors<'a> {
+    field: String,
+    errors: Vec<&'a ReportedError>,
+}
+
+impl FieldErrors<'_> {
+    /// Asserts one of the errors of the field has `code`
+    pub fn with_code(self, code: &str) -> Self {
+        assert!(
+            self.errors.iter().any(|e| e.code() == Some(code)),
+            "no error with code `{code}` for field `{}` in: {:?}",
+            self.field,
+            self.errors
+        );
+        self
+    }
+
+    /// Asserts the message of one of the errors of the field contains `message`
+    pub fn with_message(self, message: &str) -> Self {
+        assert!(
+            self.errors.iter().any(|e| e.message().contains(message)),
+            "no error with message `{message}` for field `{}` in: {:?}",
+            self.field,
+            self.errors
+        );
+        self
+    }
+
+    pub fn errors(&self) -> &[&ReportedError] {
+        &self.errors
+    }
+}
+
+/// Reads `response` and the validation errors it was created from. Panics if the response is
+/// not an error.
+pub async fn assert_validation_error<B: MessageBody>(
+    response: ServiceResponse<B>,
+) -> ValidationErrorResponse {
+    let status = response.status();
+    let truncated = response.headers().contains_key(TRUNCATED_HEADER);
+    let errors = response
+        .response()
+        .error()
+        .map(reported_errors)
+        .unwrap_or_default();
+    let body = actix_web::test::read_body(response).await;
+    ValidationErrorResponse::new(status, truncated, body, errors)
+}
+
+/// Runs extractors against a test request, without an `App`
+pub struct ValidationProbe {
+    req: TestRequest,
+}
+
+impl ValidationProbe {
+    /// Probes requests built with `req`
+    pub fn new(req: TestRequest) -> Self {
+        Self { req }
+    }
+
+    /// Probes a request with `value` as its JSON body
+    pub fn json(value: &impl serde::Serialize) -> Self {
+        Self::new(TestRequest::post().set_json(value))
+    }
+
+    /// Probes a request with the query string `query` (eg. `name=a&page=1`)
+    pub fn query(query: &str) -> Self {
+        Self::new(TestRequest::get().uri(&format!("/?{query}")))
+    }
+
+    /// Sets app data of the request, like [`App::app_data`](actix_web::App::app_data)
+    pub fn app_data<T: 'static>(self, data: T) -> Self {
+        Self {
+            req: self.req.app_data(data),
+        }
+    }
+
+    /// Extracts `E` from the request, reading the error response on failure
+    pub async fn extract<E: FromRequest>(self) -> Result<E, ValidationErrorResponse> {
+        let (req, mut payload) = self.req.to_http_parts();
+        E::from_request(&req, &mut payload)
+            .await
+            .map_err(|e| ValidationErrorResponse::from_response(HttpResponse::from_error(e)))
+    }
+
+    /// Extracts `E` from the request. Panics if extraction fails.
+    pub async fn assert_valid<E: FromRequest>(self) -> E {
+        self.extract::<E>()
+            .await
+            .unwrap_or_else(|e| panic!("expected the request to be valid, got: {e}"))
+    }
+
+    /// Extracts `E` from the request and returns the error response. Panics if extraction succeeds.
+    pub async fn assert_invalid<E: FromRequest>(self) -> ValidationErrorResponse {
+        match self.extract::<E>().await {
+            Ok(_) => panic!("expected the request to be invalid"),
+            Err(e) => e,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "custom"))]
+mod test {
+    use super::*;
+    use crate::custom::{Validate, Validated, ValidationError};
+    use actix_web::web::Json;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct ExamplePayload {
+        name: String,
+    }
+
+    impl Validate for ExamplePayload {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            if self.name.len() > 4 {
+                return Ok(());
+            }
+            Err(vec![
+                ValidationError::new("length: too short")
+                    .with_field("name")
+                    .with_code("length"),
+                ValidationError::new("unknown locale")
+                    .with_path(FieldPath::new().field("labels").key("en.US")),
+                ValidationError::new("name: not long enough"),
+            ])
+        }
+    }
+
+    #[actix_web::test]
+    async fn should_read_errors_of_the_backend() {
+        let response = ValidationProbe::json(&ExamplePayload {
+            name: "a".to_string(),
+        })
+        .assert_invalid::<Validated<Json<ExamplePayload>>>()
+        .await;
+
+        response.has_status(400).has_errors(3).lacks_field("email");
+        response
+            .has_field("name")
+            .with_code("length")
+            .with_message("too short");
+        response
+            .has_field("labels[\"en.US\"]")
+            .with_message("unknown locale");
+        response.has_field("/labels/en.US");
+
+        // Messages are never mistaken for paths
+        assert_eq!(response.errors()[2].field(), None);
+        assert_eq!(response.errors()[2].message(), "name: not long enough");
+    }
+
+    #[actix_web::test]
+    #[should_panic(expected = "no error for field `email`")]
+    async fn should_panic_for_missing_fields() {
+        ValidationProbe::json(&ExamplePayload {
+            name: "a".to_string(),
+        })
+        .assert_invalid::<Validated<Json<ExamplePayload>>>()
+        .await
+        .has_field("email");
+    }
+}
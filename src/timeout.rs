@@ -0,0 +1,258 @@
+//! Timeouts for validation.
+//!
+//! Validators that hang or run too long (eg. regex backtracking) would otherwise stall the
+//! request. Register a [`ValidationTimeout`] for a `Validated` type to fail extractions whose
+//! validation does not complete in time:
+//!
+//! ```ignore
+//! use std::time::Duration;
+//! use actix_web_validation::timeout::{ValidationTimeout, ValidationTimeoutExt};
+//!
+//! App::new()
+//!     // 503 Service Unavailable
+//!     .validation_timeout::<Validated<Json<Document>>>(ValidationTimeout::new(Duration::from_millis(100)))
+//!     // 422 Unprocessable Entity
+//!     .validation_timeout::<Validated<Json<Upload>>>(
+//!         ValidationTimeout::new(Duration::from_millis(100)).unprocessable_entity(),
+//!     )
+//! ```
+//!
+//! Inline validation cannot be interrupted, so types with a timeout are always validated on
+//! the blocking thread pool, regardless of their
+//! [`OffloadPolicy`](crate::blocking::OffloadPolicy). The same requirements as for
+//! [offloading](crate::blocking) apply: the registered type must wrap the exact extractor used
+//! by the handler, its extractor must be `Send`, and the `jsonschema` backend is not supported.
+//!
+//! A timed out validation keeps running on its blocking thread, only its result is discarded.
+//! Repeated slow payloads can therefore occupy every thread of the blocking pool, after which
+//! offloaded validation of every type queues up behind them. Bound the input that reaches slow
+//! rules (eg. with payload size limits) rather than relying on the timeout alone.
+//!
+//! Extraction fails with a [`ValidationTimedOut`] error, which handlers taking
+//! `Result<Validated<T>, actix_web::Error>` can tell apart from validation errors with
+//! [`actix_web::Error::as_error`]. With the `metrics` feature flag, the
+//! [`VALIDATION_TIMEOUTS`](crate::metrics::VALIDATION_TIMEOUTS) counter is incremented.
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use actix_web::dev::{ServiceFactory, ServiceRequest};
+use actix_web::http::header::ContentType;
+use actix_web::http::StatusCode;
+use actix_web::{App, HttpResponse, ResponseError};
+use thiserror::Error;
+
+use crate::blocking::{BlockingValidate, Spawn};
+
+/// The maximum time validation of a `Validated` type may take
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationTimeout {
+    duration: Duration,
+    status: StatusCode,
+}
+
+impl ValidationTimeout {
+    /// Responds with `503 Service Unavailable` when validation takes longer than `duration`
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            status: StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Responds with `422 Unprocessable Entity` instead of `503 Service Unavailable`
+    pub fn unprocessable_entity(self) -> Self {
+        Self {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            ..self
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// The error returned when validation did not complete within its [`ValidationTimeout`]
+#[derive(Clone, Copy, Debug, Error)]
+#[error("Validation did not complete within {}ms", .timeout.as_millis())]
+pub struct ValidationTimedOut {
+    timeout: Duration,
+    status: StatusCode,
+}
+
+impl ValidationTimedOut {
+    /// The timeout that was exceeded
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl ResponseError for ValidationTimedOut {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status)
+            .content_type(ContentType::plaintext())
+            .body(self.to_string())
+    }
+}
+
+/// The timeout registered for the extractor `E` validated with the group `G`, reporting errors
+/// as `R`
+#[cfg_attr(
+    not(any(feature = "validator", feature = "garde", feature = "custom")),
+    allow(dead_code)
+)]
+pub(crate) struct TypeTimeout<E, G, R> {
+    pub(crate) timeout: ValidationTimeout,
+    pub(crate) spawn: Spawn<E, R>,
+    _group: PhantomData<fn() -> G>,
+}
+
+/// The timeout registered for the extractor `E` and the group `G`, if any
+#[cfg(any(feature = "validator", feature = "garde", feature = "custom"))]
+pub(crate) fn timeout<E: 'static, G: 'static, R: 'static>(
+    req: &actix_web::HttpRequest,
+) -> Option<&TypeTimeout<E, G, R>> {
+    req.app_data::<TypeTimeout<E, G, R>>()
+}
+
+/// Fails `validation` with [`ValidationTimedOut`] if it exceeds `timeout`
+#[cfg(any(feature = "validator", feature = "garde", feature = "custom"))]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn with_timeout<T: 'static, R: 'static>(
+    req: &actix_web::HttpRequest,
+    type_name: &'static str,
+    timeout: ValidationTimeout,
+    validation: crate::blocking::BlockingValidation<T, R>,
+) -> crate::blocking::BlockingValidation<T, R> {
+    #[cfg(feature = "metrics")]
+    let req = req.clone();
+
+    Box::pin(async move {
+        match actix_web::rt::time::timeout(timeout.duration, validation).await {
+            Ok(res) => res,
+            Err(_) => {
+                #[cfg(feature = "metrics")]
+                crate::metrics::record_timeout(&req, type_name);
+
+                Err(ValidationTimedOut {
+                    timeout: timeout.duration,
+                    status: timeout.status,
+                }
+                .into())
+            }
+        }
+    })
+}
+
+pub trait ValidationTimeoutExt {
+    /// Fails extractions of the `Validated` type `V` whose validation exceeds `timeout`.
+    /// Validation of `V` is always offloaded to the blocking thread pool.
+    fn validation_timeout<V>(self, timeout: ValidationTimeout) -> Self
+    where
+        V: BlockingValidate + 'static,
+        V::Extractor: Send + 'static,
+        V::Report: Send + 'static;
+}
+
+fn type_timeout<V>(timeout: ValidationTimeout) -> TypeTimeout<V::Extractor, V::Group, V::Report>
+where
+    V: BlockingValidate + 'static,
+    V::Extractor: Send + 'static,
+    V::Report: Send + 'static,
+{
+    TypeTimeout {
+        timeout,
+        spawn: crate::blocking::spawn::<V>,
+        _group: PhantomData,
+    }
+}
+
+impl<A> ValidationTimeoutExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn validation_timeout<V>(self, timeout: ValidationTimeout) -> Self
+    where
+        V: BlockingValidate + 'static,
+        V::Extractor: Send + 'static,
+        V::Report: Send + 'static,
+    {
+        self.app_data(type_timeout::<V>(timeout))
+    }
+}
+
+impl ValidationTimeoutExt for &mut actix_web::web::ServiceConfig {
+    fn validation_timeout<V>(self, timeout: ValidationTimeout) -> Self
+    where
+        V: BlockingValidate + 'static,
+        V::Extractor: Send + 'static,
+        V::Report: Send + 'static,
+    {
+        self.app_data(type_timeout::<V>(timeout))
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl ValidationTimeoutExt for crate::test_util::ValidationProbe {
+    fn validation_timeout<V>(self, timeout: ValidationTimeout) -> Self
+    where
+        V: BlockingValidate + 'static,
+        V::Extractor: Send + 'static,
+        V::Report: Send + 'static,
+    {
+        self.app_data(type_timeout::<V>(timeout))
+    }
+}
+
+#[cfg(all(
+    test,
+    any(feature = "validator", feature = "garde", feature = "custom")
+))]
+mod test {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[actix_web::test]
+    async fn should_fail_slow_validation() {
+        let req = TestRequest::default().to_http_request();
+        let timeout = ValidationTimeout::new(Duration::from_millis(10)).unprocessable_entity();
+
+        let pending = Box::pin(std::future::pending());
+        let validation = with_timeout::<(), ()>(&req, "my::Type", timeout, pending);
+        let err = validation.await.unwrap_err();
+
+        let timed_out = err.as_error::<ValidationTimedOut>().unwrap();
+        assert_eq!(timed_out.timeout(), Duration::from_millis(10));
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        let response = err.error_response();
+        assert_eq!(
+            response
+                .headers()
+                .get(actix_web::http::header::CONTENT_TYPE)
+                .unwrap(),
+            "text/plain; charset=utf-8"
+        );
+    }
+
+    #[actix_web::test]
+    async fn should_pass_through_fast_validation() {
+        let req = TestRequest::default().to_http_request();
+        let timeout = ValidationTimeout::new(Duration::from_secs(10));
+
+        let validation = with_timeout::<(), ()>(
+            &req,
+            "my::Type",
+            timeout,
+            Box::pin(async { Ok(((), Ok(()))) }),
+        );
+        assert!(validation.await.is_ok());
+    }
+}
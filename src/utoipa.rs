@@ -0,0 +1,459 @@
+//! [utoipa](https://github.com/juhaku/utoipa) OpenAPI integration.
+//!
+//! With the `utoipa` feature flag, the validation constraints of a type implementing
+//! [`ValidationRules`] can be exported into its OpenAPI schema (`minLength`, `maximum`,
+//! `pattern`, `format: email`, etc):
+//!
+//! * [`ApplyValidationRules`] is an OpenAPI modifier augmenting the component schema of a request
+//!   body type.
+//! * `Validated<Query<T>>` and `Validated<Path<T>>` implement [`IntoParams`], augmenting the
+//!   parameters of `T`.
+//! * `Validated<T>` implements [`IntoResponses`](utoipa::IntoResponses), documenting the
+//!   `400 Bad Request` response returned when validation fails. `ValidatedPatch<T>` also
+//!   documents the `409 Conflict` and `415 Unsupported Media Type` responses of patches that
+//!   cannot be applied.
+//!
+//! ```ignore
+//! use actix_web_validation::rules::ValidationRules;
+//! use actix_web_validation::utoipa::ApplyValidationRules;
+//! use actix_web_validation::Validated;
+//!
+//! #[derive(Deserialize, Validate, ToSchema, ValidationRules)]
+//! struct CreateUser {
+//!     #[validate(length(min = 3, max = 32))]
+//!     name: String,
+//! }
+//!
+//! #[utoipa::path(
+//!     post,
+//!     path = "/users",
+//!     request_body = CreateUser,
+//!     responses((status = 201, description = "User created"), Validated<Json<CreateUser>>),
+//! )]
+//! #[post("/users")]
+//! async fn create_user(user: Validated<Json<CreateUser>>) -> HttpResponse {
+//!     // ...
+//! }
+//!
+//! const CREATE_USER_RULES: ApplyValidationRules<CreateUser> = ApplyValidationRules::new();
+//!
+//! #[derive(OpenApi)]
+//! #[openapi(paths(create_user), modifiers(&CREATE_USER_RULES))]
+//! struct ApiDoc;
+//! ```
+//!
+//! Nested types that are referenced (`$ref`) rather than inlined need their own
+//! [`ApplyValidationRules`] modifier.
+//!
+//! Note that the documented error responses match the built-in error responses. Applications
+//! using a custom error handler should document their own error responses instead.
+//!
+//! Responses that depend on how the app is configured are not documented: the response of a
+//! [validation timeout](crate::timeout) (`503 Service Unavailable`, or `422 Unprocessable Entity`
+//! when registered with
+//! [`unprocessable_entity`](crate::timeout::ValidationTimeout::unprocessable_entity)) must be listed in the `responses` of the
+//! endpoints it applies to.
+
+use std::collections::BTreeMap;
+
+use std::marker::PhantomData;
+use utoipa::openapi::path::Parameter;
+use utoipa::openapi::response::Response;
+use utoipa::openapi::schema::{ArrayItems, KnownFormat, Schema, SchemaFormat};
+
+use utoipa::openapi::{Content, OpenApi, RefOr, Required};
+use utoipa::{IntoParams, Modify, PartialSchema, ToSchema};
+
+use crate::rules::{FieldRules, FieldType, LengthMode, Rule, ValidationRules};
+
+/// Returns the schema of `T` augmented with the validation rules of `T`
+pub fn schema<T>() -> RefOr<Schema>
+where
+    T: ToSchema + ValidationRules,
+{
+    let mut schema = T::schema();
+    apply_rules(&mut schema, &T::rules());
+    schema
+}
+
+/// An OpenAPI modifier applying the validation rules of `T` to the component schema of `T`
+pub struct ApplyValidationRules<T>(PhantomData<fn() -> T>);
+
+impl<T> ApplyValidationRules<T> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for ApplyValidationRules<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Modify for ApplyValidationRules<T>
+where
+    T: ToSchema + ValidationRules,
+{
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Some(components) = &mut openapi.components else {
+            return;
+        };
+        if let Some(schema) = components.schemas.get_mut(T::name().as_ref()) {
+            apply_rules(schema, &T::rules());
+        }
+    }
+}
+
+/// Returns the parameters of `T` augmented with the validation rules of `T`
+pub fn params<T>(
+    parameter_in_provider: impl Fn() -> Option<utoipa::openapi::path::ParameterIn>,
+) -> Vec<Parameter>
+where
+    T: IntoParams + ValidationRules,
+{
+    let rules = T::rules();
+    let mut params = T::into_params(parameter_in_provider);
+    for param in &mut params {
+        let Some(field) = rules.iter().find(|f| f.serialized_name == param.name) else {
+            continue;
+        };
+        if field.rules.contains(&Rule::Required) {
+            param.required = Required::True;
+        }
+        if let Some(schema) = &mut param.schema {
+            apply_field_rules(schema, field);
+        }
+    }
+    params
+}
+
+/// Applies validation rules to the properties of an object schema.
+///
+/// Composite schemas (`allOf`, `oneOf`, `anyOf`) have the rules applied to each of their members.
+/// References are left untouched.
+pub fn apply_rules(schema: &mut RefOr<Schema>, rules: &[FieldRules]) {
+    let RefOr::T(schema) = schema else {
+        return;
+    };
+
+    match schema {
+        Schema::Object(object) => {
+            for field in rules {
+                if let Some(property) = object.properties.get_mut(field.serialized_name) {
+                    apply_field_rules(property, field);
+                }
+                let name = field.serialized_name.to_string();
+                if field.rules.contains(&Rule::Required) && !object.required.contains(&name) {
+                    object.required.push(name);
+                }
+            }
+        }
+        Schema::AllOf(all_of) => {
+            for item in &mut all_of.items {
+                apply_rules(item, rules);
+            }
+        }
+        Schema::OneOf(one_of) => {
+            for item in &mut one_of.items {
+                apply_rules(item, rules);
+            }
+        }
+        Schema::AnyOf(any_of) => {
+            for item in &mut any_of.items {
+                apply_rules(item, rules);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn apply_field_rules(schema: &mut RefOr<Schema>, field: &FieldRules) {
+    let RefOr::T(inner) = schema else {
+        return;
+    };
+
+    match inner {
+        Schema::Object(object) => {
+            for rule in &field.rules {
+                match rule {
+                    Rule::Range {
+                        min,
+                        max,
+                        exclusive_min,
+                        exclusive_max,
+                    } => {
+                        object.minimum = min.map(number).or(object.minimum.take());
+                        object.maximum = max.map(number).or(object.maximum.take());
+                        object.exclusive_minimum = exclusive_min
+                            .map(number)
+                            .or(object.exclusive_minimum.take());
+                        object.exclusive_maximum = exclusive_max
+                            .map(number)
+                            .or(object.exclusive_maximum.take());
+                    }
+                    Rule::Pattern(pattern) => object.pattern = Some(pattern.clone()),
+                    Rule::Email => {
+                        object.format = Some(SchemaFormat::KnownFormat(KnownFormat::Email))
+                    }
+                    Rule::Url => object.format = Some(SchemaFormat::Custom("uri".to_string())),
+                    _ => {}
+                }
+            }
+            match (field.length(), &field.field_type) {
+                (Some((min, max)), FieldType::Map(_) | FieldType::Object(_)) => {
+                    object.min_properties = min.map(|v| v as usize).or(object.min_properties);
+                    object.max_properties = max.map(|v| v as usize).or(object.max_properties);
+                }
+                // JSON Schema measures strings in code points, other length modes are omitted
+                (Some((min, max)), _) if field.length_mode() == Some(LengthMode::Chars) => {
+                    object.min_length = min.map(|v| v as usize).or(object.min_length);
+                    object.max_length = max.map(|v| v as usize).or(object.max_length);
+                }
+                _ => {}
+            }
+            if let FieldType::Object(object) = &field.field_type {
+                apply_rules(schema, &object.rules());
+            }
+        }
+        Schema::Array(array) => {
+            if let Some((min, max)) = field.length() {
+                array.min_items = min.map(|v| v as usize).or(array.min_items);
+                array.max_items = max.map(|v| v as usize).or(array.max_items);
+            }
+            if let (FieldType::Array(item_type), ArrayItems::RefOrSchema(items)) =
+                (&field.field_type, &mut array.items)
+            {
+                if let FieldType::Object(object) = item_type.as_ref() {
+                    apply_rules(items, &object.rules());
+                }
+            }
+        }
+        Schema::AllOf(_) | Schema::OneOf(_) | Schema::AnyOf(_) => {
+            if let FieldType::Object(object) = &field.field_type {
+                apply_rules(schema, &object.rules());
+            }
+        }
+        _ => {}
+    }
+}
+
+fn number(value: f64) -> utoipa::Number {
+    if value.fract() == 0.0 && value.abs() < isize::MAX as f64 {
+        utoipa::Number::Int(value as isize)
+    } else {
+        utoipa::Number::Float(value)
+    }
+}
+
+/// The `400 Bad Request` response returned by the built-in error responses when validation fails
+pub fn validation_error_responses() -> BTreeMap<String, RefOr<Response>> {
+    BTreeMap::from([(
+        "400".to_string(),
+        text_response("Validation errors in the request"),
+    )])
+}
+
+/// The responses of [`validation_error_responses`] along with the `409 Conflict` and
+/// `415 Unsupported Media Type` responses returned when a patch cannot be applied
+#[cfg(feature = "patch")]
+pub fn patch_error_responses() -> BTreeMap<String, RefOr<Response>> {
+    let mut responses = validation_error_responses();
+    responses.insert(
+        "409".to_string(),
+        text_response("The patch could not be applied to the resource"),
+    );
+    responses.insert(
+        "415".to_string(),
+        text_response("Unsupported patch content type"),
+    );
+    responses
+}
+
+fn text_response(description: &str) -> RefOr<Response> {
+    let response = Response::builder()
+        .description(description)
+        .content("text/plain", Content::new(Some(String::schema())))
+        .build();
+    RefOr::T(response)
+}
+
+// Generic impls shared by every `Validated` type
+#[cfg(any(
+    feature = "validator",
+    feature = "garde",
+    feature = "custom",
+    feature = "jsonschema"
+))]
+macro_rules! validated_openapi_definition {
+    ($validated:ident $(<$group:ident>)?) => {
+        impl<T $(, $group)?> ::utoipa::IntoParams
+            for $validated<::actix_web::web::Query<T> $(, $group)?>
+        where
+            T: ::utoipa::IntoParams + $crate::rules::ValidationRules,
+        {
+            fn into_params(
+                parameter_in_provider: impl Fn() -> Option<::utoipa::openapi::path::ParameterIn>,
+            ) -> Vec<::utoipa::openapi::path::Parameter> {
+                $crate::utoipa::params::<T>(parameter_in_provider)
+            }
+        }
+
+        impl<T $(, $group)?> ::utoipa::IntoParams
+            for $validated<::actix_web::web::Path<T> $(, $group)?>
+        where
+            T: ::utoipa::IntoParams + $crate::rules::ValidationRules,
+        {
+            fn into_params(
+                parameter_in_provider: impl Fn() -> Option<::utoipa::openapi::path::ParameterIn>,
+            ) -> Vec<::utoipa::openapi::path::Parameter> {
+                $crate::utoipa::params::<T>(parameter_in_provider)
+            }
+        }
+
+        impl<T $(, $group)?> ::utoipa::IntoResponses for $validated<T $(, $group)?> {
+            fn responses() -> std::collections::BTreeMap<
+                String,
+                ::utoipa::openapi::RefOr<::utoipa::openapi::response::Response>,
+            > {
+                $crate::utoipa::validation_error_responses()
+            }
+        }
+    };
+}
+
+#[cfg(any(
+    feature = "validator",
+    feature = "garde",
+    feature = "custom",
+    feature = "jsonschema"
+))]
+pub(crate) use validated_openapi_definition;
+
+#[cfg(all(test, feature = "derive", feature = "validator"))]
+mod test {
+    use actix_web::web::{Json, Query};
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use utoipa::{IntoParams, IntoResponses, OpenApi, ToSchema};
+    use validator::Validate;
+
+    use super::ApplyValidationRules;
+    use crate::rules::ValidationRules;
+    use crate::validator::Validated;
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Validate, ToSchema, ValidationRules)]
+    #[serde(rename_all = "camelCase")]
+    struct CreateUser {
+        #[validate(length(min = 3, max = 32))]
+        user_name: String,
+        #[validate(email)]
+        email: String,
+        #[validate(range(min = 18, max = 150))]
+        age: Option<u8>,
+        #[validate(length(min = 1))]
+        tags: Vec<String>,
+        #[validate(length(min = 1, max = 10))]
+        labels: HashMap<String, String>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Validate, IntoParams, ValidationRules)]
+    struct Search {
+        #[validate(required, length(max = 64))]
+        query: Option<String>,
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/users",
+        request_body = CreateUser,
+        params(Validated<Query<Search>>),
+        responses((status = 201, description = "User created"), Validated<Json<CreateUser>>),
+    )]
+    #[allow(dead_code)]
+    async fn create_user(_user: Validated<Json<CreateUser>>, _search: Validated<Query<Search>>) {}
+
+    #[derive(OpenApi)]
+    #[openapi(paths(create_user), modifiers(&CREATE_USER_RULES))]
+    struct ApiDoc;
+
+    const CREATE_USER_RULES: ApplyValidationRules<CreateUser> = ApplyValidationRules::new();
+
+    #[test]
+    fn should_augment_schema() {
+        let schema = serde_json::to_value(super::schema::<CreateUser>()).unwrap();
+        let properties = &schema["properties"];
+
+        assert_eq!(properties["userName"]["minLength"], json!(3));
+        assert_eq!(properties["userName"]["maxLength"], json!(32));
+        assert_eq!(properties["email"]["format"], json!("email"));
+        assert_eq!(properties["age"]["minimum"], json!(18));
+        assert_eq!(properties["age"]["maximum"], json!(150));
+        assert_eq!(properties["tags"]["minItems"], json!(1));
+        assert_eq!(properties["labels"]["minProperties"], json!(1));
+        assert_eq!(properties["labels"]["maxProperties"], json!(10));
+        assert!(properties["labels"].get("minLength").is_none());
+    }
+
+    #[test]
+    fn should_augment_params() {
+        let params = Validated::<Query<Search>>::into_params(|| None);
+
+        assert_eq!(params.len(), 1);
+        let param = serde_json::to_value(&params[0]).unwrap();
+        assert_eq!(param["required"], json!(true));
+        assert_eq!(param["schema"]["maxLength"], json!(64));
+    }
+
+    #[test]
+    fn should_document_validation_error_response() {
+        let responses = Validated::<Json<CreateUser>>::responses();
+
+        assert_eq!(responses.keys().collect::<Vec<_>>(), vec!["400"]);
+    }
+
+    #[test]
+    fn should_document_grouped_extractors() {
+        use crate::group::Update;
+        use crate::validator::ValidatedGroup;
+
+        let params = ValidatedGroup::<Query<Search>, Update>::into_params(|| None);
+        let param = serde_json::to_value(&params[0]).unwrap();
+        assert_eq!(param["schema"]["maxLength"], json!(64));
+
+        let responses = ValidatedGroup::<Json<CreateUser>, Update>::responses();
+        assert_eq!(responses.keys().collect::<Vec<_>>(), vec!["400"]);
+    }
+
+    #[cfg(feature = "patch")]
+    #[test]
+    fn should_document_patch_error_responses() {
+        use crate::validator::ValidatedPatch;
+
+        let responses = ValidatedPatch::<CreateUser>::responses();
+        assert_eq!(
+            responses.keys().collect::<Vec<_>>(),
+            vec!["400", "409", "415"]
+        );
+    }
+
+    #[test]
+    fn should_document_validated_endpoints() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let operation = &doc["paths"]["/users"]["post"];
+
+        assert_eq!(
+            operation["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            json!("#/components/schemas/CreateUser")
+        );
+        assert!(operation["responses"]["201"].is_object());
+        assert!(operation["responses"]["400"]["content"]["text/plain"].is_object());
+        assert_eq!(
+            doc["components"]["schemas"]["CreateUser"]["properties"]["userName"]["minLength"],
+            json!(3)
+        );
+    }
+}
@@ -14,16 +14,27 @@
 //! For usage examples, see the documentation for [`Validated`]
 //!
 
-use crate::validated_definition;
+use crate::blocking::{offload, BlockingValidate, BlockingValidation};
+use crate::field_names::FieldNames;
+use crate::group::{All, ByMethod, Create, Group, Update, ValidationGroup};
+use crate::limit;
+use crate::optional::{Peek, Presence};
+use crate::path::{FieldPath, PathSegment, PathSyntax};
+use crate::redaction::{RedactionPolicy, REDACTED};
+use crate::sanitize::sanitize;
+use crate::{validated_definition, validated_into_definition, IntoInner};
 use ::validator::Validate;
 use actix_web::dev::{ServiceFactory, ServiceRequest};
 use actix_web::http::StatusCode;
 use actix_web::FromRequest;
 use actix_web::{App, HttpRequest, HttpResponse, ResponseError};
+use pin_project_lite::pin_project;
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::future::Future;
+use std::marker::PhantomData;
 use std::sync::Arc;
-use std::{fmt::Debug, ops::Deref, pin::Pin, task::Poll};
+use std::{fmt::Debug, ops::Deref, task::Poll};
 use thiserror::Error;
 use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
 
@@ -50,59 +61,570 @@ use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
 /// ```
 pub struct Validated<T>(pub T);
 
-validated_definition!();
+validated_definition!(Validated);
 
-/// Future that extracts and validates actix requests using the Actix Web [`FromRequest`] trait
+/// A validated extractor applying the rules of the validation group `G`.
 ///
-/// End users of this library should not need to use this directly for most usecases
-pub struct ValidatedFut<T: FromRequest> {
-    req: actix_web::HttpRequest,
-    fut: <T as FromRequest>::Future,
-    error_handler: Option<ValidatorErrHandler>,
+/// Extracts and validates like [`Validated`], `G` selects the rules that are applied, see
+/// [`crate::group`] and [`ValidateGroup`].
+///
+/// ```ignore
+/// use actix_web_validation::group::{Create, Update};
+///
+/// async fn create(user: ValidatedGroup<Json<User>, Create>) -> impl Responder { .. }
+/// async fn update(user: ValidatedGroup<Json<User>, Update>) -> impl Responder { .. }
+/// ```
+pub struct ValidatedGroup<T, G>(pub T, PhantomData<fn() -> G>);
+
+validated_definition!(ValidatedGroup<G>);
+
+pin_project! {
+    /// Future that extracts and validates actix requests using the Actix Web [`FromRequest`] trait
+    ///
+    /// End users of this library should not need to use this directly for most usecases
+    pub struct ValidatedFut<T: FromRequest, G = All, O = Validated<T>> {
+        req: actix_web::HttpRequest,
+        #[pin]
+        fut: <T as FromRequest>::Future,
+        error_handler: Option<ValidatorErrHandler>,
+        finish: Finish<T, O>,
+        validating: Option<(BlockingValidation<T, ValidationErrors>, std::time::Instant)>,
+        // Checks the recorded body for unknown fields, see `crate::strict`
+        unknown_fields: Option<Box<dyn FnOnce() -> Vec<FieldPath>>>,
+        spans: Option<AddSpans>,
+        _group: PhantomData<fn() -> G>,
+    }
+}
+
+/// Turns the extracted value and its validation result into the output of [`ValidatedFut`]
+type Finish<T, O> = fn(
+    &HttpRequest,
+    Option<&ValidatorErrHandler>,
+    T,
+    Result<(), ValidationErrors>,
+) -> Result<O, actix_web::Error>;
+
+impl<T, G, O> ValidatedFut<T, G, O>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+{
+    fn new(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+        finish: Finish<T, O>,
+    ) -> Self {
+        let error_handler = req
+            .app_data::<ValidatorErrorHandler>()
+            .map(|h| h.handler.clone());
+
+        #[cfg(feature = "strict")]
+        let unknown_fields = crate::strict::record::<T::Target>(req, payload);
+        #[cfg(not(feature = "strict"))]
+        let unknown_fields = None;
+
+        #[cfg(feature = "spans")]
+        let spans = crate::spans::record::<T::Target>(req, payload).map(|spans| -> AddSpans {
+            Box::new(move |mut errors| {
+                with_spans(&mut errors, FieldPath::new(), &spans());
+                errors
+            })
+        });
+        #[cfg(not(feature = "spans"))]
+        let spans = None;
+
+        let fut = T::from_request(req, payload);
+
+        ValidatedFut {
+            fut,
+            error_handler,
+            finish,
+            req: req.clone(),
+            validating: None,
+            unknown_fields,
+            spans,
+            _group: PhantomData,
+        }
+    }
 }
-impl<T> Future for ValidatedFut<T>
+
+impl<T, G, O> Future for ValidatedFut<T, G, O>
 where
-    T: FromRequest + Debug + Deref,
-    T::Future: Unpin,
-    T::Target: Validate,
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    G: Group + GroupRules<T::Target>,
 {
-    type Output = Result<Validated<T>, actix_web::Error>;
+    type Output = Result<O, actix_web::Error>;
 
     fn poll(
         self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
-        let this = self.get_mut();
+        let mut this = self.project();
 
-        let Poll::Ready(res) = Pin::new(&mut this.fut).poll(cx) else {
-            return std::task::Poll::Pending;
-        };
+        loop {
+            if let Some((validating, started)) = this.validating {
+                let Poll::Ready(res) = validating.as_mut().poll(cx) else {
+                    return std::task::Poll::Pending;
+                };
+                let started = *started;
+                *this.validating = None;
+
+                return Poll::Ready(res.and_then(|(data, result)| {
+                    let result = complete::<T::Target>(
+                        this.req,
+                        started,
+                        result,
+                        this.unknown_fields.take(),
+                        this.spans.take(),
+                    );
+                    (this.finish)(this.req, this.error_handler.as_ref(), data, result)
+                }));
+            }
+
+            let Poll::Ready(res) = this.fut.as_mut().poll(cx) else {
+                return std::task::Poll::Pending;
+            };
+
+            let mut data = match res {
+                Ok(data) => data,
+                Err(e) => return Poll::Ready(Err(e.into())),
+            };
+            sanitize(this.req, &mut data);
+
+            let group = G::group(this.req);
+            let started = std::time::Instant::now();
+
+            match offload::<T, G, ValidationErrors>(this.req, data, group) {
+                // Poll the blocking validation right away, it wakes the task once done
+                Ok(validating) => *this.validating = Some((validating, started)),
+                Err(data) => {
+                    let result = complete::<T::Target>(
+                        this.req,
+                        started,
+                        G::validate(&data, group),
+                        this.unknown_fields.take(),
+                        this.spans.take(),
+                    );
+                    return Poll::Ready((this.finish)(
+                        this.req,
+                        this.error_handler.as_ref(),
+                        data,
+                        result,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Applies the checks registered for `T` to its validation result
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+fn complete<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    started: std::time::Instant,
+    result: Result<(), ValidationErrors>,
+    unknown_fields: Option<Box<dyn FnOnce() -> Vec<FieldPath>>>,
+    spans: Option<AddSpans>,
+) -> Result<(), ValidationErrors> {
+    let result = with_serialized_names::<T>(req, with_redaction(req, result));
+    let unknown_fields = unknown_fields.map_or_else(Vec::new, |check| check());
+
+    // Unknown fields are named by the client, so they are not recorded by their path
+    #[cfg(feature = "metrics")]
+    {
+        record_metrics::<T>(req, started, &result);
+        crate::metrics::record_unknown_fields(
+            req,
+            std::any::type_name::<T>(),
+            unknown_fields.len(),
+        );
+    }
+
+    let result = with_unknown_fields(result, unknown_fields);
+    result.map_err(|errors| match spans {
+        Some(add_spans) => add_spans(errors),
+        None => errors,
+    })
+}
+
+/// Responds with the validation errors of invalid values, gives valid values back. Used by the
+/// [`Finish`] of [`Validated`] and [`ValidatedGroup`], by `ValidatedPatch` and for WebSocket
+/// messages.
+///
+/// The value error handler registered for the value takes precedence over the error handler.
+fn reject<T>(
+    req: &HttpRequest,
+    error_handler: Option<&ValidatorErrHandler>,
+    data: T,
+    result: Result<(), ValidationErrors>,
+) -> Result<T, actix_web::Error>
+where
+    T: Deref,
+    T::Target: 'static,
+{
+    let Err(e) = result else {
+        return Ok(data);
+    };
+
+    match req.app_data::<ValidatorValueErrorHandler<T::Target>>() {
+        Some(value_handler) => {
+            let handler = |errors, req: &HttpRequest| (value_handler.handler)(&data, errors, req);
+            Err(respond(req, Some(&handler), e))
+        }
+        None => Err(validation_error(req, error_handler, e)),
+    }
+}
+
+/// Adds the source span of each invalid value to its error, see [`crate::spans`]
+type AddSpans = Box<dyn FnOnce(ValidationErrors) -> ValidationErrors>;
+
+/// Adds the `line`, `column` and `offset` params of the invalid value to each error
+#[cfg(feature = "spans")]
+fn with_spans(errors: &mut ValidationErrors, path: FieldPath, spans: &crate::spans::SpanMap) {
+    for (field, err) in errors.errors_mut() {
+        let path = path.clone().field(field.as_ref());
+        match err {
+            ValidationErrorsKind::Field(field_errors) => {
+                if let Some(span) = spans.get(&path) {
+                    for error in field_errors {
+                        error.add_param(Cow::Borrowed("line"), &span.line);
+                        error.add_param(Cow::Borrowed("column"), &span.column);
+                        error.add_param(Cow::Borrowed("offset"), &span.offset);
+                    }
+                }
+            }
+            ValidationErrorsKind::List(list_error) => {
+                for (index, errors) in list_error {
+                    with_spans(errors, path.clone().index(*index), spans);
+                }
+            }
+            ValidationErrorsKind::Struct(struct_errors) => with_spans(struct_errors, path, spans),
+        }
+    }
+}
+
+/// Masks the `value` param of the errors of fields matched by the redaction policy
+fn with_redaction(
+    req: &HttpRequest,
+    result: Result<(), ValidationErrors>,
+) -> Result<(), ValidationErrors> {
+    match req.app_data::<RedactionPolicy>() {
+        Some(policy) => result.map_err(|mut errors| {
+            redact(&mut errors, FieldPath::new(), policy);
+            errors
+        }),
+        None => result,
+    }
+}
 
-        let res = match res {
-            Ok(data) => {
-                if let Err(e) = data.validate() {
-                    if let Some(error_handler) = &this.error_handler {
-                        Err((*error_handler)(e, &this.req))
-                    } else {
-                        let err: Error = e.into();
-                        Err(err.into())
+fn redact(errors: &mut ValidationErrors, path: FieldPath, policy: &RedactionPolicy) {
+    for (field, err) in errors.errors_mut() {
+        let path = path.clone().field(field.as_ref());
+        match err {
+            ValidationErrorsKind::Field(field_errors) => {
+                if policy.is_redacted(&path) {
+                    for error in field_errors {
+                        if error.params.contains_key("value") {
+                            error.add_param(Cow::Borrowed("value"), &REDACTED);
+                        }
                     }
-                } else {
-                    Ok(Validated(data))
                 }
             }
-            Err(e) => Err(e.into()),
+            ValidationErrorsKind::List(list_error) => {
+                for (index, errors) in list_error {
+                    redact(errors, path.clone().index(*index), policy);
+                }
+            }
+            ValidationErrorsKind::Struct(struct_errors) => redact(struct_errors, path, policy),
+        }
+    }
+}
+
+/// Renames the error fields of `T` to their serialized names, see [`crate::field_names`]
+fn with_serialized_names<T: ?Sized + 'static>(
+    req: &HttpRequest,
+    result: Result<(), ValidationErrors>,
+) -> Result<(), ValidationErrors> {
+    match crate::field_names::field_names::<T>(req) {
+        Some(names) => result.map_err(|errors| rename_fields(errors, &names)),
+        None => result,
+    }
+}
+
+fn rename_fields(errors: ValidationErrors, names: &FieldNames) -> ValidationErrors {
+    let mut renamed = ValidationErrors::new();
+    for (field, err) in errors.into_errors() {
+        let (field, nested) = match names.field(&field) {
+            Some((name, nested)) => (Cow::Borrowed(name), nested),
+            None => (field, None),
+        };
+        let err = match (err, nested) {
+            (ValidationErrorsKind::Struct(struct_errors), Some(nested)) => {
+                ValidationErrorsKind::Struct(Box::new(rename_fields(*struct_errors, &nested)))
+            }
+            (ValidationErrorsKind::List(list_error), Some(nested)) => ValidationErrorsKind::List(
+                list_error
+                    .into_iter()
+                    .map(|(index, errors)| (index, Box::new(rename_fields(*errors, &nested))))
+                    .collect(),
+            ),
+            (err, _) => err,
         };
+        renamed.errors_mut().insert(field, err);
+    }
+    renamed
+}
 
-        Poll::Ready(res)
+/// Adds an `unknown_field` error for each of the unknown `fields`
+fn with_unknown_fields(
+    result: Result<(), ValidationErrors>,
+    fields: Vec<FieldPath>,
+) -> Result<(), ValidationErrors> {
+    if fields.is_empty() {
+        return result;
+    }
+
+    let mut errors = result.err().unwrap_or_default();
+    for field in fields {
+        if !add_unknown_field(&mut errors, field.segments()) {
+            // The path has no nested errors equivalent (eg. nested lists), keep it flat
+            errors.errors_mut().insert(
+                Cow::Owned(field.to_string()),
+                ValidationErrorsKind::Field(vec![ValidationError::new("unknown_field")]),
+            );
+        }
+    }
+    Err(errors)
+}
+
+/// Adds an `unknown_field` error nested at `path`, returning `false` if `path` can not be
+/// represented by [`ValidationErrors`]
+fn add_unknown_field(errors: &mut ValidationErrors, path: &[PathSegment]) -> bool {
+    let [PathSegment::Field(field) | PathSegment::Key(field), rest @ ..] = path else {
+        return false;
+    };
+
+    let entry = errors.errors_mut().entry(Cow::Owned(field.clone()));
+    match rest {
+        [] => match entry.or_insert_with(|| ValidationErrorsKind::Field(Vec::new())) {
+            ValidationErrorsKind::Field(field_errors) => {
+                field_errors.push(ValidationError::new("unknown_field"));
+                true
+            }
+            _ => false,
+        },
+        [PathSegment::Index(index), rest @ ..] => {
+            match entry.or_insert_with(|| ValidationErrorsKind::List(Default::default())) {
+                ValidationErrorsKind::List(list_error) => {
+                    add_unknown_field(list_error.entry(*index).or_default(), rest)
+                }
+                _ => false,
+            }
+        }
+        _ => match entry.or_insert_with(|| ValidationErrorsKind::Struct(Default::default())) {
+            ValidationErrorsKind::Struct(struct_errors) => add_unknown_field(struct_errors, rest),
+            _ => false,
+        },
+    }
+}
+
+/// Deserializes a JSON text or binary WebSocket frame into `T` and validates it.
+/// Requires the `ws` feature flag
+///
+/// The sanitizer, error limit, value error handler and error handler registered for the
+/// WebSocket upgrade request `req` are applied. Returns `None` for frames without data (eg. ping and close frames).
+///
+/// See the [`ws`](crate::ws) module for an example.
+#[cfg(feature = "ws")]
+pub fn validate_message<T>(
+    req: &HttpRequest,
+    msg: &impl crate::ws::DataFrame,
+) -> Option<Result<T, crate::ws::ErrorFrame>>
+where
+    T: serde::de::DeserializeOwned + Validate + 'static,
+{
+    let data = msg.data()?;
+
+    Some(crate::ws::deserialize::<T>(data).and_then(|mut value| {
+        sanitize(req, &mut value);
+
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        let result = crate::redaction::masked(|| value.validate());
+        let result = with_serialized_names::<T>(req, with_redaction(req, result));
+
+        #[cfg(feature = "metrics")]
+        record_metrics::<T>(req, started, &result);
+
+        let error_handler = req
+            .app_data::<ValidatorErrorHandler>()
+            .map(|h| h.handler.clone());
+        reject(req, error_handler.as_ref(), Box::new(value), result)
+            .map(|value| *value)
+            .map_err(Into::into)
+    }))
+}
+
+/// A `PATCH` request body applied to the current value of the resource and validated.
+/// Requires the `patch` feature flag
+///
+/// The resource is loaded with the [`PatchLoader`](crate::patch::PatchLoader) registered for
+/// `T`, see the [`patch`](crate::patch) module. Validation errors get a
+/// `patch_path` param with the JSON Pointer of the patch path that caused them.
+#[cfg(feature = "patch")]
+pub struct ValidatedPatch<T>(pub T);
+
+#[cfg(feature = "patch")]
+crate::validated_patch_definition!();
+
+#[cfg(feature = "patch")]
+impl<T> FromRequest for ValidatedPatch<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + Validate + 'static,
+{
+    type Error = actix_web::Error;
+
+    type Future = std::pin::Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+        use actix_web::web::Json;
+
+        let req = req.clone();
+        let patched = crate::patch::patched::<T>(&req, payload);
+
+        Box::pin(async move {
+            let patched = patched.await?;
+            let mut value = patched.value;
+            sanitize(&req, &mut value);
+
+            let error_handler = req
+                .app_data::<ValidatorErrorHandler>()
+                .map(|h| h.handler.clone());
+            let group = All::group(&req);
+            let started = std::time::Instant::now();
+
+            // Offloading and timeouts registered for `Validated<Json<T>>` apply
+            let (data, result) =
+                match offload::<Json<T>, All, ValidationErrors>(&req, Json(value), group) {
+                    Ok(validating) => validating.await?,
+                    Err(data) => {
+                        let result = <All as GroupRules<T>>::validate(&data, group);
+                        (data, result)
+                    }
+                };
+            let result = complete::<T>(&req, started, result, patched.unknown_fields, None)
+                .map_err(|e| with_patch_paths(e, &patched.patch));
+            reject(&req, error_handler.as_ref(), data, result)
+                .map(|Json(value)| ValidatedPatch(value))
+        })
+    }
+}
+
+/// Adds the patch path that caused each error as a `patch_path` param
+#[cfg(feature = "patch")]
+fn with_patch_paths(mut errors: ValidationErrors, patch: &crate::patch::Patch) -> ValidationErrors {
+    _with_patch_paths(&mut errors, FieldPath::new(), patch);
+    errors
+}
+
+#[cfg(feature = "patch")]
+fn _with_patch_paths(errors: &mut ValidationErrors, path: FieldPath, patch: &crate::patch::Patch) {
+    for (field, err) in errors.errors_mut() {
+        let path = path.clone().field(field.as_ref());
+        match err {
+            ValidationErrorsKind::Field(field_errors) => {
+                if let Some(patch_path) = patch.path_of(&path) {
+                    for error in field_errors {
+                        error.add_param(Cow::Borrowed("patch_path"), &patch_path);
+                    }
+                }
+            }
+            ValidationErrorsKind::List(list_error) => {
+                for (index, errors) in list_error {
+                    _with_patch_paths(errors, path.clone().index(*index), patch);
+                }
+            }
+            ValidationErrorsKind::Struct(struct_errors) => {
+                _with_patch_paths(struct_errors, path, patch)
+            }
+        }
+    }
+}
+
+/// The rules applied by a validation group marker of [`ValidatedGroup`]
+pub trait GroupRules<T: ?Sized> {
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), ValidationErrors>;
+}
+
+/// A type with different rules per validation group
+///
+/// ```
+/// use actix_web_validation::group::ValidationGroup;
+/// use actix_web_validation::validator::ValidateGroup;
+/// use validator::{Validate, ValidationErrors};
+///
+/// #[derive(Validate)]
+/// struct User {
+///     #[validate(length(min = 1))]
+///     name: Option<String>,
+/// }
+///
+/// impl ValidateGroup for User {
+///     fn validate_group(&self, group: ValidationGroup) -> Result<(), ValidationErrors> {
+///         if group == ValidationGroup::Create && self.name.is_none() {
+///             let mut errors = ValidationErrors::new();
+///             errors.add("name", validator::ValidationError::new("required"));
+///             return Err(errors);
+///         }
+///         self.validate()
+///     }
+/// }
+/// ```
+pub trait ValidateGroup {
+    fn validate_group(&self, group: ValidationGroup) -> Result<(), ValidationErrors>;
+}
+
+impl<T: Validate + ?Sized> GroupRules<T> for All {
+    fn validate(value: &T, _: ValidationGroup) -> Result<(), ValidationErrors> {
+        crate::redaction::masked(|| value.validate())
+    }
+}
+
+macro_rules! group_rules_impl {
+    ($($group:ident),*) => {
+        $(
+            impl<T: ValidateGroup + ?Sized> GroupRules<T> for $group {
+                fn validate(value: &T, group: ValidationGroup) -> Result<(), ValidationErrors> {
+                    crate::redaction::masked(|| value.validate_group(group))
+                }
+            }
+        )*
+    };
+}
+
+group_rules_impl!(Create, Update, ByMethod);
+
+impl<T> BlockingValidate for Validated<T>
+where
+    T: Deref,
+    All: GroupRules<T::Target>,
+{
+    type Extractor = T;
+    type Group = All;
+    type Report = ValidationErrors;
+
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), ValidationErrors> {
+        All::validate(value, group)
     }
 }
 
 impl<T> FromRequest for Validated<T>
 where
-    T: FromRequest + Debug + Deref,
-    T::Future: Unpin,
-    T::Target: Validate,
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
 {
     type Error = actix_web::Error;
 
@@ -112,255 +634,2106 @@ where
         req: &actix_web::HttpRequest,
         payload: &mut actix_web::dev::Payload,
     ) -> Self::Future {
-        let error_handler = req
-            .app_data::<ValidatorErrorHandler>()
-            .map(|h| h.handler.clone());
+        ValidatedFut::new(req, payload, |req, error_handler, data, result| {
+            reject(req, error_handler, data, result).map(Validated)
+        })
+    }
+}
+
+impl<T, G> BlockingValidate for ValidatedGroup<T, G>
+where
+    T: Deref,
+    G: GroupRules<T::Target> + 'static,
+{
+    type Extractor = T;
+    type Group = G;
+    type Report = ValidationErrors;
+
+    fn validate(value: &T, group: ValidationGroup) -> Result<(), ValidationErrors> {
+        G::validate(value, group)
+    }
+}
+
+impl<T, G> FromRequest for ValidatedGroup<T, G>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    G: Group + GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedFut<T, G, Self>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedFut::new(req, payload, |req, error_handler, data, result| {
+            reject(req, error_handler, data, result).map(|data| ValidatedGroup(data, PhantomData))
+        })
+    }
+}
+
+/// A validated extractor handing validation failures to the handler.
+///
+/// Extracts and validates like [`Validated`], but invalid values are given to the handler as
+/// a [`Rejected`] instead of responding with the validation errors, eg. to render a form again
+/// with the input of the user. Extraction errors (eg. malformed JSON) are still responded with.
+///
+/// ```
+/// use actix_web::{post, web::Form, HttpResponse};
+/// use serde::Deserialize;
+/// use validator::Validate;
+/// use actix_web_validation::validator::{ValidationResult, Validated};
+///
+/// #[derive(Debug, Deserialize, Validate)]
+/// struct Signup {
+///     #[validate(length(min = 5))]
+///     username: String,
+/// }
+///
+/// #[post("/signup")]
+/// async fn signup(result: ValidationResult<Form<Signup>>) -> HttpResponse {
+///     match result.into_result() {
+///         Ok(Validated(Form(signup))) => HttpResponse::Ok().body(signup.username),
+///         Err(rejected) => HttpResponse::Ok().body(format!(
+///             "<input value=\"{}\"> {}",
+///             rejected.value().username,
+///             rejected.errors()
+///         )),
+///     }
+/// }
+/// ```
+pub struct ValidationResult<T>(pub Result<Validated<T>, Rejected<T>>);
+
+impl<T> ValidationResult<T> {
+    pub fn into_result(self) -> Result<Validated<T>, Rejected<T>> {
+        self.0
+    }
+}
+
+impl<T: Debug> Debug for ValidationResult<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ValidationResult").field(&self.0).finish()
+    }
+}
+
+/// An extracted value that failed validation, see [`ValidationResult`]
+pub struct Rejected<T> {
+    value: T,
+    errors: ValidationErrors,
+}
+
+impl<T> Rejected<T> {
+    /// The extracted (and sanitized) value
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn errors(&self) -> &ValidationErrors {
+        &self.errors
+    }
+
+    pub fn into_parts(self) -> (T, ValidationErrors) {
+        (self.value, self.errors)
+    }
+}
+
+impl<T: Debug> Debug for Rejected<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rejected")
+            .field("value", &self.value)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+/// Hands invalid values to the handler, the [`Finish`] of [`ValidationResult`]
+fn keep_rejected<T>(
+    _: &HttpRequest,
+    _: Option<&ValidatorErrHandler>,
+    value: T,
+    result: Result<(), ValidationErrors>,
+) -> Result<ValidationResult<T>, actix_web::Error> {
+    Ok(ValidationResult(match result {
+        Ok(()) => Ok(Validated(value)),
+        Err(errors) => Err(Rejected { value, errors }),
+    }))
+}
+
+impl<T> FromRequest for ValidationResult<T>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedFut<T, All, Self>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedFut::new(req, payload, keep_rejected)
+    }
+}
+
+/// A validated extractor for optional input.
+///
+/// `None` when the input is absent (eg. a request without a query string for [`Query`](actix_web::web::Query),
+/// see [`Presence`]). Present input is validated like [`Validated`], so invalid input is still
+/// responded with the validation errors instead of being ignored like with `Option<Validated<T>>`.
+///
+/// ```
+/// use actix_web::{get, web::Query};
+/// use serde::Deserialize;
+/// use validator::Validate;
+/// use actix_web_validation::validator::OptionalValidated;
+///
+/// #[derive(Debug, Deserialize, Validate)]
+/// struct UserFilter {
+///     #[validate(length(min = 3))]
+///     name: String,
+/// }
+///
+/// #[get("/users")]
+/// async fn list_users(filter: OptionalValidated<Query<UserFilter>>) -> String {
+///     match filter.into_inner() {
+///         Some(filter) => format!("Users named {}", filter.name),
+///         None => "All users".to_string(),
+///     }
+/// }
+/// ```
+pub struct OptionalValidated<T>(pub Option<Validated<T>>);
+
+impl<T> OptionalValidated<T> {
+    pub fn into_inner(self) -> Option<Validated<T>> {
+        self.0
+    }
+}
+
+impl<T: Debug> Debug for OptionalValidated<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OptionalValidated").field(&self.0).finish()
+    }
+}
+
+pin_project! {
+    /// Future that extracts and validates optional input for [`OptionalValidated`]
+    pub struct OptionalValidatedFut<T: FromRequest> {
+        req: HttpRequest,
+        peek: Option<Peek>,
+        #[pin]
+        fut: Option<ValidatedFut<T>>,
+    }
+}
+
+impl<T> Future for OptionalValidatedFut<T>
+where
+    T: FromRequest + Deref + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Output = Result<OptionalValidated<T>, actix_web::Error>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let mut this = self.project();
+        if let Some(peek) = this.peek.as_mut() {
+            let (present, mut payload) = std::task::ready!(std::pin::Pin::new(peek).poll(cx));
+            *this.peek = None;
+            if present {
+                let fut = Validated::from_request(this.req, &mut payload);
+                this.fut.set(Some(fut));
+            }
+        }
+        match this.fut.as_pin_mut() {
+            Some(fut) => fut
+                .poll(cx)
+                .map(|res| res.map(|validated| OptionalValidated(Some(validated)))),
+            None => Poll::Ready(Ok(OptionalValidated(None))),
+        }
+    }
+}
+
+impl<T> FromRequest for OptionalValidated<T>
+where
+    T: FromRequest + Deref + Presence + 'static,
+    T::Target: 'static,
+    All: GroupRules<T::Target>,
+{
+    type Error = actix_web::Error;
+
+    type Future = OptionalValidatedFut<T>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        OptionalValidatedFut {
+            req: req.clone(),
+            peek: Some(Peek::new::<T>(req, payload)),
+            fut: None,
+        }
+    }
+}
+
+/// A validated extractor converting the validated value into a domain type.
+///
+/// The inner extractor is validated like [`Validated`], then its value is converted into `D`
+/// with [`TryFrom`]. Conversion errors go through the same error handler as validation errors.
+///
+/// ```
+/// use actix_web::{post, web::{self, Json}, App};
+/// use serde::Deserialize;
+/// use validator::{Validate, ValidationError, ValidationErrors};
+/// use actix_web_validation::validator::ValidatedInto;
+///
+/// #[derive(Debug, Deserialize, Validate)]
+/// struct CreateUserDto {
+///     #[validate(length(min = 5))]
+///     username: String,
+/// }
+///
+/// struct CreateUser {
+///     username: String,
+/// }
+///
+/// impl TryFrom<CreateUserDto> for CreateUser {
+///     type Error = ValidationErrors;
+///
+///     fn try_from(dto: CreateUserDto) -> Result<Self, Self::Error> {
+///         if dto.username == "admin" {
+///             let mut errors = ValidationErrors::new();
+///             errors.add("username", ValidationError::new("reserved"));
+///             return Err(errors);
+///         }
+///         Ok(CreateUser { username: dto.username })
+///     }
+/// }
+///
+/// #[post("/")]
+/// async fn index(user: ValidatedInto<Json<CreateUserDto>, CreateUser>) -> String {
+///     format!("Welcome {}!", user.username)
+/// }
+/// ```
+pub struct ValidatedInto<T, D> {
+    inner: D,
+    _extractor: PhantomData<fn() -> T>,
+}
+
+validated_into_definition!();
+
+impl<T, D> Future for ValidatedIntoFut<T, D>
+where
+    T: FromRequest + Deref + IntoInner + 'static,
+    T::Target: Validate + 'static,
+    D: TryFrom<T::Inner>,
+    D::Error: Into<ValidationErrors>,
+{
+    type Output = Result<ValidatedInto<T, D>, actix_web::Error>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let mut this = self.project();
+        let Poll::Ready(res) = this.fut.as_mut().poll(cx) else {
+            return std::task::Poll::Pending;
+        };
+
+        let res = res.and_then(|Validated(data)| {
+            crate::redaction::masked(|| D::try_from(data.into_inner()))
+                .map(ValidatedInto::new)
+                .map_err(|e| {
+                    // Conversion errors are post-processed like the errors of `validate()`
+                    let req = &this.fut.req;
+                    let errors = with_redaction(req, Err(e.into()));
+                    let errors = with_serialized_names::<T::Target>(req, errors).unwrap_err();
+                    validation_error(req, this.fut.error_handler.as_ref(), errors)
+                })
+        });
+
+        Poll::Ready(res)
+    }
+}
+
+impl<T, D> FromRequest for ValidatedInto<T, D>
+where
+    T: FromRequest + Deref + IntoInner + 'static,
+    T::Target: Validate + 'static,
+    D: TryFrom<T::Inner>,
+    D::Error: Into<ValidationErrors>,
+{
+    type Error = actix_web::Error;
+
+    type Future = ValidatedIntoFut<T, D>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        ValidatedIntoFut {
+            fut: Validated::<T>::from_request(req, payload),
+            _domain: PhantomData,
+        }
+    }
+}
+
+/// Converts validation errors into an error response, using the custom error handler if any
+fn validation_error(
+    req: &HttpRequest,
+    error_handler: Option<&ValidatorErrHandler>,
+    errors: ValidationErrors,
+) -> actix_web::Error {
+    respond(
+        req,
+        error_handler.map(|handler| handler.as_ref() as &RespondWith),
+        errors,
+    )
+}
+
+/// An error handler borrowing from the caller
+type RespondWith<'a> = dyn Fn(ValidationErrors, &HttpRequest) -> actix_web::Error + 'a;
+
+/// Responds with `errors` after applying the registered error limit
+fn respond(
+    req: &HttpRequest,
+    error_handler: Option<&RespondWith>,
+    mut errors: ValidationErrors,
+) -> actix_web::Error {
+    let truncated = limit::max_errors(req).is_some_and(|max| truncate(&mut errors, max));
+    if truncated {
+        limit::mark_truncated(req);
+    }
+    if let Some(error_handler) = error_handler {
+        (*error_handler)(errors, req)
+    } else {
+        Error {
+            errors,
+            truncated,
+            syntax: crate::path::syntax(req),
+        }
+        .into()
+    }
+}
+
+/// The error returned when validation fails and no error handler is registered.
+///
+/// Middleware can recognise validation failures with [`actix_web::Error::as_error`] and read
+/// the report, eg. to log or reformat it:
+///
+/// ```ignore
+/// if let Some(error) = res.response().error().and_then(|e| e.as_error::<actix_web_validation::validator::Error>()) {
+///     log::warn!("validation failed: {}", error);
+/// }
+/// ```
+#[derive(Error, Debug)]
+pub struct Error {
+    errors: validator::ValidationErrors,
+    truncated: bool,
+    syntax: PathSyntax,
+}
+
+impl Error {
+    /// The validation errors, truncated to the registered limit
+    pub fn errors(&self) -> &validator::ValidationErrors {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> validator::ValidationErrors {
+        self.errors
+    }
+
+    /// Whether errors were dropped to respect the limit, see [`crate::limit`]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.errors)
+    }
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        limit::error_response(
+            self.status_code(),
+            format!(
+                "Validation errors in fields:\n{}",
+                flatten_errors(&self.errors)
+                    .iter()
+                    .map(|(_, field, err)| {
+                        let line = format!("\t{}: {}", field.render(self.syntax), err);
+                        #[cfg(feature = "spans")]
+                        let line = crate::spans::with_span(line, *err);
+                        line
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            self.truncated,
+            "\t",
+        )
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn record_metrics<T: ?Sized>(
+    req: &HttpRequest,
+    started: std::time::Instant,
+    result: &Result<(), ValidationErrors>,
+) {
+    let violations = match result {
+        Ok(()) => Vec::new(),
+        Err(errors) => flatten_errors(errors)
+            .into_iter()
+            .map(|(_, field, err)| (field, Some(err.code.to_string())))
+            .collect(),
+    };
+
+    crate::metrics::record(
+        req,
+        std::any::type_name::<T>(),
+        started.elapsed(),
+        violations,
+    );
+}
+
+/// The path and error of every field error in `errors`, see [`crate::path`]
+pub fn error_paths(errors: &ValidationErrors) -> Vec<(FieldPath, &ValidationError)> {
+    flatten_errors(errors)
+        .into_iter()
+        .map(|(_, path, error)| (path, error))
+        .collect()
+}
+
+/// Helper function for error extraction and formatting.
+/// Return Vec of tuples where first element is full field path
+/// and second is error.
+#[inline]
+fn flatten_errors(errors: &ValidationErrors) -> Vec<(u16, FieldPath, &ValidationError)> {
+    _flatten_errors(errors, FieldPath::new(), None)
+}
+
+#[inline]
+fn _flatten_errors(
+    errors: &ValidationErrors,
+    path: FieldPath,
+    indent: Option<u16>,
+) -> Vec<(u16, FieldPath, &ValidationError)> {
+    errors
+        .errors()
+        .iter()
+        .flat_map(|(field, err)| {
+            let indent = indent.unwrap_or(0);
+            let actual_path = path.clone().field(field.as_ref());
+            match err {
+                ValidationErrorsKind::Field(field_errors) => field_errors
+                    .iter()
+                    .map(|error| (indent, actual_path.clone(), error))
+                    .collect::<Vec<_>>(),
+                ValidationErrorsKind::List(list_error) => list_error
+                    .iter()
+                    .flat_map(|(index, errors)| {
+                        let actual_path = actual_path.clone().index(*index);
+                        _flatten_errors(errors, actual_path, Some(indent + 1))
+                    })
+                    .collect::<Vec<_>>(),
+                ValidationErrorsKind::Struct(struct_errors) => {
+                    _flatten_errors(struct_errors, actual_path, Some(indent + 1))
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Keeps the first `max` errors, returning `true` if any error was dropped
+fn truncate(errors: &mut ValidationErrors, max: usize) -> bool {
+    let total = flatten_errors(errors).len();
+    _truncate(errors, &mut max.min(total));
+    total > max
+}
+
+fn _truncate(errors: &mut ValidationErrors, remaining: &mut usize) {
+    errors.errors_mut().retain(|_, err| match err {
+        ValidationErrorsKind::Field(field_errors) => {
+            field_errors.truncate(*remaining);
+            *remaining -= field_errors.len();
+            !field_errors.is_empty()
+        }
+        ValidationErrorsKind::List(list_error) => {
+            list_error.retain(|_, errors| {
+                _truncate(errors, remaining);
+                !errors.is_empty()
+            });
+            !list_error.is_empty()
+        }
+        ValidationErrorsKind::Struct(struct_errors) => {
+            _truncate(struct_errors, remaining);
+            !struct_errors.is_empty()
+        }
+    });
+}
+
+pub type ValidatorErrHandler =
+    Arc<dyn Fn(validator::ValidationErrors, &HttpRequest) -> actix_web::Error + Send + Sync>;
+
+struct ValidatorErrorHandler {
+    handler: ValidatorErrHandler,
+}
+
+/// Extension trait to provide a convenience method for adding custom error handler
+pub trait ValidatorErrorHandlerExt {
+    /// Add a custom error handler for validator validated requests
+    fn validator_error_handler(self, handler: ValidatorErrHandler) -> Self;
+}
+
+impl<T> ValidatorErrorHandlerExt for App<T>
+where
+    T: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn validator_error_handler(self, handler: ValidatorErrHandler) -> Self {
+        self.app_data(ValidatorErrorHandler { handler })
+    }
+}
+
+impl ValidatorErrorHandlerExt for &mut actix_web::web::ServiceConfig {
+    fn validator_error_handler(self, handler: ValidatorErrHandler) -> Self {
+        self.app_data(ValidatorErrorHandler { handler })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl ValidatorErrorHandlerExt for crate::test_util::ValidationProbe {
+    fn validator_error_handler(self, handler: ValidatorErrHandler) -> Self {
+        self.app_data(ValidatorErrorHandler { handler })
+    }
+}
+
+/// An error handler receiving the rejected value of type `T` along with its errors
+pub type ValidatorValueErrHandler<T> =
+    Arc<dyn Fn(&T, validator::ValidationErrors, &HttpRequest) -> actix_web::Error + Send + Sync>;
+
+struct ValidatorValueErrorHandler<T: ?Sized> {
+    handler: ValidatorValueErrHandler<T>,
+}
+
+/// Extension trait to provide a convenience method for adding error handlers per validated type
+pub trait ValidatorValueErrorHandlerExt {
+    /// Add an error handler for requests whose extracted value of type `T` failed validation.
+    /// Takes precedence over the error handler of [`ValidatorErrorHandlerExt`].
+    fn validator_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: ValidatorValueErrHandler<T>,
+    ) -> Self;
+}
+
+impl<A> ValidatorValueErrorHandlerExt for App<A>
+where
+    A: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
+{
+    fn validator_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: ValidatorValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(ValidatorValueErrorHandler { handler })
+    }
+}
+
+impl ValidatorValueErrorHandlerExt for &mut actix_web::web::ServiceConfig {
+    fn validator_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: ValidatorValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(ValidatorValueErrorHandler { handler })
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl ValidatorValueErrorHandlerExt for crate::test_util::ValidationProbe {
+    fn validator_value_error_handler<T: ?Sized + 'static>(
+        self,
+        handler: ValidatorValueErrHandler<T>,
+    ) -> Self {
+        self.app_data(ValidatorValueErrorHandler { handler })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::redaction::Redacted;
+    use crate::sanitize::{self, Sanitize, SanitizerExt};
+    use actix_web::web::Bytes;
+    use actix_web::{http::header::ContentType, post, test, web::Json, App, Responder};
+    use serde::{Deserialize, Serialize};
+    use std::pin::Pin;
+    use validator::Validate;
+
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    struct ExamplePayload {
+        #[validate(length(min = 5))]
+        name: String,
+    }
+
+    #[post("/")]
+    async fn endpoint(v: Validated<Json<ExamplePayload>>) -> impl Responder {
+        assert!(v.name.len() > 4);
+        HttpResponse::Ok().body(())
+    }
+
+    #[actix_web::test]
+    async fn should_validate_simple() {
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        // Valid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "123456".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        // Invalid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
+
+    // TODO: This test is unstable because the error or appears to be non-dermimistic
+    #[ignore]
+    #[actix_web::test]
+    async fn should_respond_with_errors_correctly() {
+        use actix_web::http::header::CONTENT_TYPE;
+
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        // Invalid request
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+        let result = test::read_body(resp).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"Validation errors in fields:\n\tname: Validation error: length [{\"min\": Number(5), \"value\": String(\"1234\")}]")
+        );
+    }
+
+    #[derive(Debug, Serialize, Error)]
+    struct CustomErrorResponse {
+        custom_message: String,
+        errors: Vec<String>,
+    }
+
+    impl Display for CustomErrorResponse {
+        fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            unimplemented!()
+        }
+    }
+
+    impl ResponseError for CustomErrorResponse {
+        fn status_code(&self) -> actix_web::http::StatusCode {
+            actix_web::http::StatusCode::BAD_REQUEST
+        }
+
+        fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
+            HttpResponse::build(self.status_code()).body(serde_json::to_string(self).unwrap())
+        }
+    }
+
+    fn error_handler(errors: ::validator::ValidationErrors, _: &HttpRequest) -> actix_web::Error {
+        CustomErrorResponse {
+            custom_message: "My custom message".to_string(),
+            errors: errors.errors().keys().map(|err| err.to_string()).collect(),
+        }
+        .into()
+    }
+
+    #[actix_web::test]
+    async fn should_use_allow_custom_error_responses() {
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .validator_error_handler(Arc::new(error_handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::plaintext())
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"{\"custom_message\":\"My custom message\",\"errors\":[\"name\"]}")
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[actix_web::test]
+    async fn should_record_metrics() {
+        use crate::metrics::test::recorded;
+        use crate::metrics::{VALIDATION_DURATION, VALIDATION_FAILURES};
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let _guard = ::metrics::set_default_local_recorder(&recorder);
+
+        let app = test::init_service(App::new().service(endpoint)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let metrics = recorded(&snapshotter);
+        let type_label = std::any::type_name::<ExamplePayload>().to_string();
+
+        let duration = metrics
+            .iter()
+            .find(|(name, _, _)| name == VALIDATION_DURATION)
+            .unwrap();
+        assert_eq!(
+            duration.1,
+            vec![
+                ("route".to_string(), "/".to_string()),
+                ("type".to_string(), type_label.clone())
+            ]
+        );
+
+        let failures = metrics
+            .iter()
+            .find(|(name, _, _)| name == VALIDATION_FAILURES)
+            .unwrap();
+        assert_eq!(
+            failures.1,
+            vec![
+                ("code".to_string(), "length".to_string()),
+                ("field".to_string(), "name".to_string()),
+                ("route".to_string(), "/".to_string()),
+                ("type".to_string(), type_label)
+            ]
+        );
+        assert_eq!(failures.2, DebugValue::Counter(1));
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    struct SensitivePayload {
+        #[validate(length(min = 8))]
+        password: Redacted<String>,
+        #[validate(length(min = 10))]
+        token: String,
+    }
+
+    #[post("/sensitive")]
+    async fn sensitive_endpoint(_: Validated<Json<SensitivePayload>>) -> impl Responder {
+        HttpResponse::Ok().body(())
+    }
+
+    #[actix_web::test]
+    async fn should_redact_sensitive_values() {
+        let app = test::init_service(
+            App::new()
+                .service(sensitive_endpoint)
+                .app_data(RedactionPolicy::new().field("token")),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/sensitive")
+            .insert_header(ContentType::json())
+            .set_payload(r#"{"password":"hunter2","token":"s3cr3t"}"#)
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        let result = std::str::from_utf8(&result).unwrap();
+
+        assert!(result.contains("password"));
+        assert!(result.contains("token"));
+        assert!(result.contains(REDACTED));
+        assert!(!result.contains("hunter2"));
+        assert!(!result.contains("s3cr3t"));
+    }
+
+    #[test]
+    async fn should_replace_redacted_value_params() {
+        let req = test::TestRequest::default()
+            .app_data(RedactionPolicy::new().field("token"))
+            .to_http_request();
+
+        let payload = SensitivePayload {
+            password: Redacted("hunter2".to_string()),
+            token: "s3cr3t".to_string(),
+        };
+        let errors = with_redaction(&req, payload.validate()).unwrap_err();
+        let errors = serde_json::to_value(&errors).unwrap();
+
+        assert_eq!(errors["token"][0]["params"]["value"], "[REDACTED]");
+        assert_eq!(errors["token"][0]["params"]["min"], 10);
+    }
+
+    #[test]
+    async fn debug_for_validated_should_redact_sensitive_values() {
+        let v = Validated(SensitivePayload {
+            password: Redacted("hunter2".to_string()),
+            token: "abc".to_string(),
+        });
+
+        assert_eq!(
+            "Validated(SensitivePayload { password: \"[REDACTED]\", token: \"abc\" })",
+            format!("{v:?}")
+        );
+    }
+
+    #[test]
+    async fn debug_for_validated_should_work() {
+        let v = Validated(ExamplePayload {
+            name: "abcde".to_string(),
+        });
+
+        assert_eq!(
+            "Validated(ExamplePayload { name: \"abcde\" })",
+            format!("{v:?}")
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    struct SignupPayload {
+        #[validate(email)]
+        email: String,
+    }
+
+    impl Sanitize for SignupPayload {
+        fn sanitize(&mut self) {
+            sanitize::trim(&mut self.email);
+            sanitize::lowercase(&mut self.email);
+        }
+    }
+
+    #[post("/signup")]
+    async fn signup_endpoint(v: Validated<Json<SignupPayload>>) -> impl Responder {
+        v.into_inner().into_inner().email
+    }
+
+    #[actix_web::test]
+    async fn should_sanitize_before_validating() {
+        let payload = SignupPayload {
+            email: " John@Example.com \n".to_string(),
+        };
+
+        let app = test::init_service(App::new().service(signup_endpoint)).await;
+        let req = test::TestRequest::post()
+            .uri("/signup")
+            .set_json(&payload)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let app = test::init_service(
+            App::new()
+                .sanitizer::<SignupPayload>()
+                .service(signup_endpoint),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/signup")
+            .set_json(&payload)
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(result, Bytes::from_static(b"john@example.com"));
+    }
+
+    struct Username(String);
+
+    impl TryFrom<ExamplePayload> for Username {
+        type Error = ValidationErrors;
+
+        fn try_from(payload: ExamplePayload) -> Result<Self, Self::Error> {
+            if payload.name == "admin" {
+                let mut errors = ValidationErrors::new();
+                errors.add("name", ValidationError::new("reserved"));
+                return Err(errors);
+            }
+            Ok(Username(payload.name))
+        }
+    }
+
+    #[post("/into")]
+    async fn into_endpoint(v: ValidatedInto<Json<ExamplePayload>, Username>) -> impl Responder {
+        v.into_inner().0
+    }
+
+    #[actix_web::test]
+    async fn should_convert_validated_values() {
+        let app = test::init_service(App::new().service(into_endpoint)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/into")
+            .set_json(ExamplePayload {
+                name: "123456".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(result, Bytes::from_static(b"123456"));
+
+        // Validation runs before the conversion
+        let req = test::TestRequest::post()
+            .uri("/into")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let req = test::TestRequest::post()
+            .uri("/into")
+            .set_json(ExamplePayload {
+                name: "admin".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(
+                b"Validation errors in fields:\n\tname: Validation error: reserved [{}]"
+            )
+        );
+    }
+
+    #[actix_web::test]
+    async fn should_use_custom_error_handler_for_conversion_errors() {
+        let app = test::init_service(
+            App::new()
+                .service(into_endpoint)
+                .validator_error_handler(Arc::new(error_handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/into")
+            .set_json(ExamplePayload {
+                name: "admin".to_string(),
+            })
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(b"{\"custom_message\":\"My custom message\",\"errors\":[\"name\"]}")
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    struct ManyErrorsPayload {
+        #[validate(nested)]
+        items: Vec<ExamplePayload>,
+    }
+
+    #[post("/many")]
+    async fn many_errors_endpoint(_: Validated<Json<ManyErrorsPayload>>) -> impl Responder {
+        HttpResponse::Ok().body(())
+    }
+
+    fn truncating_error_handler(errors: ValidationErrors, req: &HttpRequest) -> actix_web::Error {
+        CustomErrorResponse {
+            custom_message: format!("truncated: {}", crate::limit::is_truncated(req)),
+            errors: flatten_errors(&errors)
+                .into_iter()
+                .map(|(_, field, _)| field.to_string())
+                .collect(),
+        }
+        .into()
+    }
+
+    #[actix_web::test]
+    async fn should_limit_reported_errors() {
+        let payload = ManyErrorsPayload {
+            items: (0..100)
+                .map(|_| ExamplePayload {
+                    name: "a".to_string(),
+                })
+                .collect(),
+        };
+
+        let app = test::init_service(
+            App::new()
+                .service(many_errors_endpoint)
+                .app_data(crate::limit::ErrorLimit::max_errors(3)),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/many")
+            .set_json(&payload)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(crate::limit::TRUNCATED_HEADER).unwrap(),
+            "true"
+        );
+        let result = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert_eq!(result.matches("\titems[").count(), 3);
+        assert!(result.ends_with("\n\t(more errors truncated)"));
+
+        // Custom error handlers receive the truncated errors
+        let app = test::init_service(
+            App::new()
+                .service(many_errors_endpoint)
+                .app_data(crate::limit::ErrorLimit::fail_fast())
+                .validator_error_handler(Arc::new(truncating_error_handler)),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/many")
+            .set_json(&payload)
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            result,
+            Bytes::from_static(
+                b"{\"custom_message\":\"truncated: true\",\"errors\":[\"items[0].name\"]}"
+            )
+        );
+    }
+
+    pin_project! {
+        /// A `!Unpin` future, like the ones returned by async blocks
+        struct PinnedFut {
+            #[pin]
+            _pinned: std::marker::PhantomPinned,
+            value: Option<ExamplePayload>,
+        }
+    }
+
+    impl Future for PinnedFut {
+        type Output = Result<PinnedExtractor, actix_web::Error>;
+
+        fn poll(self: Pin<&mut Self>, _: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+            Poll::Ready(Ok(PinnedExtractor(self.project().value.take().unwrap())))
+        }
+    }
+
+    #[derive(Debug, derive_more::Deref)]
+    struct PinnedExtractor(ExamplePayload);
+
+    impl FromRequest for PinnedExtractor {
+        type Error = actix_web::Error;
+        type Future = PinnedFut;
+
+        fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+            PinnedFut {
+                _pinned: std::marker::PhantomPinned,
+                value: Some(ExamplePayload {
+                    name: req.query_string().to_string(),
+                }),
+            }
+        }
+    }
+
+    #[post("/pinned")]
+    async fn pinned_endpoint(v: Validated<PinnedExtractor>) -> impl Responder {
+        v.into_inner().0.name
+    }
+
+    #[actix_web::test]
+    async fn should_validate_extractors_with_pinned_futures() {
+        let app = test::init_service(App::new().service(pinned_endpoint)).await;
+
+        let req = test::TestRequest::post().uri("/pinned?123456").to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(result, Bytes::from_static(b"123456"));
+
+        let req = test::TestRequest::post().uri("/pinned?1234").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
+
+    #[actix_web::test]
+    async fn should_offload_validation() {
+        use crate::blocking::{OffloadPolicy, OffloadValidationExt};
+
+        let app = test::init_service(
+            App::new()
+                .service(endpoint)
+                .offload_validation::<Validated<Json<ExamplePayload>>>(OffloadPolicy::always()),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "123456".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains("name"));
+    }
+
+    #[cfg(feature = "ws")]
+    #[actix_web::test]
+    async fn should_validate_websocket_messages() {
+        use actix_ws::Message;
+
+        let req = test::TestRequest::default()
+            .app_data(ValidatorErrorHandler {
+                handler: Arc::new(error_handler),
+            })
+            .to_http_request();
+
+        let msg = Message::Text(r#"{"name":"123456"}"#.into());
+        let payload = validate_message::<ExamplePayload>(&req, &msg);
+        assert_eq!(payload.unwrap().unwrap().name, "123456");
+
+        // Rejected messages use the registered error handler
+        let msg = Message::Binary(Bytes::from_static(br#"{"name":"1234"}"#));
+        let frame = validate_message::<ExamplePayload>(&req, &msg)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(frame.status().as_u16(), 400);
+        assert_eq!(
+            frame.text(),
+            "{\"custom_message\":\"My custom message\",\"errors\":[\"name\"]}"
+        );
+
+        let msg = Message::Text("not json".into());
+        let frame = validate_message::<ExamplePayload>(&req, &msg)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(frame.status().as_u16(), 400);
+
+        let msg = Message::Ping(Bytes::new());
+        assert!(validate_message::<ExamplePayload>(&req, &msg).is_none());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[actix_web::test]
+    async fn should_assert_validation_errors() {
+        use crate::test_util::{assert_validation_error, ValidationProbe};
+
+        let app = test::init_service(App::new().service(endpoint)).await;
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_validation_error(resp)
+            .await
+            .has_errors(1)
+            .has_field("name")
+            .with_code("length");
+
+        // Probes apply the app data without an app
+        let payload = ValidationProbe::json(&ExamplePayload {
+            name: "123456".to_string(),
+        })
+        .assert_valid::<Validated<Json<ExamplePayload>>>()
+        .await;
+        assert_eq!(payload.name, "123456");
+
+        let response = ValidationProbe::json(&ExamplePayload {
+            name: "1234".to_string(),
+        })
+        .validator_error_handler(Arc::new(error_handler))
+        .assert_invalid::<Validated<Json<ExamplePayload>>>()
+        .await;
+        assert_eq!(
+            response.body(),
+            &Bytes::from_static(
+                b"{\"custom_message\":\"My custom message\",\"errors\":[\"name\"]}"
+            )
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Validate)]
+    struct UserPayload {
+        #[validate(length(min = 5))]
+        name: Option<String>,
+    }
+
+    impl ValidateGroup for UserPayload {
+        fn validate_group(&self, group: ValidationGroup) -> Result<(), ValidationErrors> {
+            if group == ValidationGroup::Create && self.name.is_none() {
+                let mut errors = ValidationErrors::new();
+                errors.add("name", validator::ValidationError::new("required"));
+                return Err(errors);
+            }
+            self.validate()
+        }
+    }
+
+    async fn create_user(_: ValidatedGroup<Json<UserPayload>, Create>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    async fn update_user(_: ValidatedGroup<Json<UserPayload>, Update>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    async fn upsert_user(_: ValidatedGroup<Json<UserPayload>, ByMethod>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn should_validate_groups() {
+        use crate::group::MethodGroups;
+        use actix_web::{http::Method, web};
+
+        let app = test::init_service(
+            App::new()
+                .app_data(MethodGroups::default().method(Method::PUT, ValidationGroup::Update))
+                .route("/create", web::post().to(create_user))
+                .route("/update", web::post().to(update_user))
+                .route("/upsert", web::post().to(upsert_user))
+                .route("/upsert", web::put().to(upsert_user)),
+        )
+        .await;
+
+        let cases = [
+            (test::TestRequest::post().uri("/create"), 400),
+            (test::TestRequest::post().uri("/update"), 200),
+            (test::TestRequest::post().uri("/upsert"), 400),
+            (test::TestRequest::put().uri("/upsert"), 200),
+        ];
+        for (req, status) in cases {
+            let req = req.set_json(UserPayload { name: None }).to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status().as_u16(), status);
+        }
+
+        // The regular rules apply to every group
+        let req = test::TestRequest::post()
+            .uri("/update")
+            .set_json(UserPayload {
+                name: Some("1234".to_string()),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+    }
+
+    #[cfg(feature = "patch")]
+    async fn patch_endpoint(v: ValidatedPatch<ExamplePayload>) -> impl Responder {
+        HttpResponse::Ok().body(v.into_inner().name)
+    }
+
+    #[cfg(feature = "patch")]
+    #[actix_web::test]
+    async fn should_validate_patched_values() {
+        use crate::patch::{PatchLoaderExt, JSON_PATCH};
+        use actix_web::{http::header::CONTENT_TYPE, web};
+        use serde_json::json;
+
+        let app = test::init_service(
+            App::new()
+                .patch_loader::<ExamplePayload, _, _>(|_| async {
+                    Ok(ExamplePayload {
+                        name: "123456".to_string(),
+                    })
+                })
+                .route("/", web::patch().to(patch_endpoint)),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(json!({ "name": "abcdef" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+        assert_eq!(test::read_body(resp).await, Bytes::from_static(b"abcdef"));
+
+        // An empty merge patch keeps the loaded value
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(json!({}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(test::read_body(resp).await, Bytes::from_static(b"123456"));
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, JSON_PATCH))
+            .set_payload(r#"[{"op": "replace", "path": "/name", "value": "abc"}]"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("patch_path"), "{body}");
+        assert!(body.contains("\"/name\""), "{body}");
+
+        // Patches that cannot be applied conflict with the resource
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, JSON_PATCH))
+            .set_payload(r#"[{"op": "test", "path": "/name", "value": "abc"}]"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 409);
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+    }
+
+    #[cfg(all(feature = "patch", feature = "strict"))]
+    #[actix_web::test]
+    async fn should_validate_patched_values_like_json_bodies() {
+        use crate::patch::PatchLoaderExt;
+        use crate::strict::StrictFieldsExt;
+        use crate::timeout::{ValidationTimeout, ValidationTimeoutExt};
+        use actix_web::web;
+        use serde_json::json;
+        use std::time::Duration;
+
+        let app = test::init_service(
+            App::new()
+                .strict_fields::<ExamplePayload>()
+                .validation_timeout::<Validated<Json<SlowPayload>>>(ValidationTimeout::new(
+                    Duration::from_millis(50),
+                ))
+                .patch_loader::<ExamplePayload, _, _>(|_| async {
+                    Ok(ExamplePayload {
+                        name: "123456".to_string(),
+                    })
+                })
+                .patch_loader::<SlowPayload, _, _>(|_| async { Ok(SlowPayload { millis: 0 }) })
+                .route("/", web::patch().to(patch_endpoint))
+                .route(
+                    "/slow",
+                    web::patch()
+                        .to(|_: ValidatedPatch<SlowPayload>| async { HttpResponse::Ok().finish() }),
+                ),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(json!({ "nme": "abcdef" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("\tnme: "), "{body}");
+        assert!(body.contains("unknown_field"), "{body}");
+
+        let req = test::TestRequest::patch()
+            .uri("/slow")
+            .set_json(json!({ "millis": 500 }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 503);
+    }
+
+    #[cfg(feature = "patch")]
+    async fn sensitive_patch_endpoint(v: ValidatedPatch<SensitivePayload>) -> impl Responder {
+        let v = v.into_inner();
+        HttpResponse::Ok().body(format!("{} {}", v.password.as_str(), v.token))
+    }
+
+    #[cfg(feature = "patch")]
+    #[actix_web::test]
+    async fn should_preserve_redacted_values_of_patched_resources() {
+        use crate::patch::PatchLoaderExt;
+        use actix_web::web;
+        use serde_json::json;
+
+        let app = test::init_service(
+            App::new()
+                .patch_loader::<SensitivePayload, _, _>(|_| async {
+                    Ok(SensitivePayload {
+                        password: Redacted("hunter2hunter2".to_string()),
+                        token: "0123456789".to_string(),
+                    })
+                })
+                .route("/", web::patch().to(sensitive_patch_endpoint)),
+        )
+        .await;
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(json!({ "token": "9876543210" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+        assert_eq!(
+            test::read_body(resp).await,
+            Bytes::from_static(b"hunter2hunter2 9876543210")
+        );
+
+        // The rejected value is still masked in errors
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(json!({ "password": "hunter2" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains(REDACTED), "{body}");
+        assert!(!body.contains("hunter2"), "{body}");
+    }
+
+    #[cfg(feature = "strict")]
+    #[actix_web::test]
+    async fn should_reject_unknown_fields() {
+        use crate::strict::StrictFieldsExt;
+        use serde_json::json;
+
+        let app = test::init_service(
+            App::new()
+                .strict_fields::<ExamplePayload>()
+                .service(endpoint),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({ "name": "123456" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({ "name": "123", "nme": "123456" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(
+            body.contains("\tnme: Validation error: unknown_field"),
+            "{body}"
+        );
+        assert!(body.contains("\tname: Validation error: length"), "{body}");
+    }
+
+    #[cfg(feature = "strict")]
+    #[actix_web::test]
+    async fn should_nest_unknown_fields() {
+        use crate::path::PathSyntax;
+        use crate::strict::StrictFieldsExt;
+
+        #[derive(Debug, Deserialize, Serialize, Validate)]
+        struct Item {
+            #[validate(length(min = 5))]
+            name: String,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, Validate)]
+        struct Order {
+            #[validate(nested)]
+            items: Vec<Item>,
+        }
+
+        async fn order(_: Validated<Json<Order>>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+
+        for (syntax, name, zip) in [
+            (PathSyntax::Dotted, "items[0].name", "items[0].zip"),
+            (PathSyntax::JsonPointer, "/items/0/name", "/items/0/zip"),
+            (PathSyntax::Brackets, "items[0][name]", "items[0][zip]"),
+        ] {
+            let app = test::init_service(
+                App::new()
+                    .app_data(syntax)
+                    .strict_fields::<Order>()
+                    .route("/", actix_web::web::post().to(order)),
+            )
+            .await;
+
+            let req = test::TestRequest::post()
+                .uri("/")
+                .set_json(serde_json::json!({ "items": [{ "name": "abc", "zip": "1" }] }))
+                .to_request();
+            let body = test::call_and_read_body(&app, req).await;
+            let body = std::str::from_utf8(&body).unwrap();
+            assert!(
+                body.contains(&format!("\t{name}: Validation error: length")),
+                "{body}"
+            );
+            assert!(
+                body.contains(&format!("\t{zip}: Validation error: unknown_field")),
+                "{body}"
+            );
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    #[actix_web::test]
+    async fn should_report_serialized_field_names() {
+        use crate::field_names::SerializedNamesExt;
+        use crate::rules::ValidationRules;
+
+        #[derive(Debug, Deserialize, Serialize, Validate, ValidationRules)]
+        #[serde(rename_all = "camelCase")]
+        struct Address {
+            #[validate(length(min = 5))]
+            zip_code: String,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, Validate, ValidationRules)]
+        #[serde(rename_all = "camelCase")]
+        struct Signup {
+            #[validate(length(min = 5))]
+            first_name: String,
+            #[validate(nested)]
+            home_address: Address,
+        }
+
+        async fn signup(_: Validated<Json<Signup>>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+
+        let app = test::init_service(
+            App::new()
+                .serialized_names::<Signup>()
+                .app_data(RedactionPolicy::new().field("home_address"))
+                .route("/", actix_web::web::post().to(signup)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(serde_json::json!({
+                "firstName": "abc",
+                "homeAddress": { "zipCode": "123" },
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("\tfirstName: "), "{body}");
+        assert!(body.contains("\thomeAddress.zipCode: "), "{body}");
+        // Redaction policies use the Rust field names
+        assert!(body.contains("abc"), "{body}");
+        assert!(body.contains(REDACTED), "{body}");
+    }
+
+    #[cfg(feature = "derive")]
+    #[actix_web::test]
+    async fn should_post_process_conversion_errors() {
+        use crate::field_names::SerializedNamesExt;
+        use crate::rules::ValidationRules;
+
+        #[derive(Debug, Deserialize, Serialize, Validate, ValidationRules)]
+        #[serde(rename_all = "camelCase")]
+        struct SignupDto {
+            #[validate(length(min = 3))]
+            first_name: String,
+        }
+
+        struct Signup;
+
+        impl TryFrom<SignupDto> for Signup {
+            type Error = ValidationErrors;
+
+            fn try_from(dto: SignupDto) -> Result<Self, Self::Error> {
+                let mut error = ValidationError::new("reserved");
+                error.add_param(Cow::Borrowed("value"), &dto.first_name);
+                let mut errors = ValidationErrors::new();
+                errors.add("first_name", error);
+                Err(errors)
+            }
+        }
+
+        async fn signup(_: ValidatedInto<Json<SignupDto>, Signup>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+
+        let app = test::init_service(
+            App::new()
+                .serialized_names::<SignupDto>()
+                .app_data(RedactionPolicy::new().field("first_name"))
+                .route("/", actix_web::web::post().to(signup)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(serde_json::json!({ "firstName": "admin" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(
+            body.contains("\tfirstName: Validation error: reserved"),
+            "{body}"
+        );
+        assert!(body.contains(REDACTED), "{body}");
+        assert!(!body.contains("admin"), "{body}");
+    }
+
+    #[cfg(all(feature = "derive", feature = "patch"))]
+    #[actix_web::test]
+    async fn should_report_patch_paths_with_serialized_names() {
+        use crate::field_names::SerializedNamesExt;
+        use crate::patch::PatchLoaderExt;
+        use crate::rules::ValidationRules;
+
+        #[derive(Debug, Deserialize, Serialize, Validate, ValidationRules)]
+        #[serde(rename_all = "camelCase")]
+        struct Address {
+            #[validate(length(min = 5))]
+            zip_code: String,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, Validate, ValidationRules)]
+        #[serde(rename_all = "camelCase")]
+        struct Profile {
+            #[validate(nested)]
+            home_address: Address,
+        }
+
+        async fn update(_: ValidatedPatch<Profile>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+
+        let app = test::init_service(
+            App::new()
+                .serialized_names::<Profile>()
+                .patch_loader::<Profile, _, _>(|_| async {
+                    Ok(Profile {
+                        home_address: Address {
+                            zip_code: "12345".to_string(),
+                        },
+                    })
+                })
+                .route("/", actix_web::web::patch().to(update)),
+        )
+        .await;
 
-        let fut = T::from_request(req, payload);
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(serde_json::json!({ "homeAddress": { "zipCode": "123" } }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("\thomeAddress.zipCode: "), "{body}");
+        assert!(body.contains("\"/homeAddress/zipCode\""), "{body}");
+    }
 
-        ValidatedFut {
-            fut,
-            error_handler,
-            req: req.clone(),
+    #[actix_web::test]
+    async fn should_render_paths_in_configured_syntax() {
+        use crate::path::PathSyntax;
+
+        #[derive(Debug, Deserialize, Serialize, Validate)]
+        struct Item {
+            #[validate(length(min = 5))]
+            name: String,
         }
-    }
-}
 
-#[derive(Error, Debug)]
-struct Error {
-    errors: validator::ValidationErrors,
-}
+        #[derive(Debug, Deserialize, Serialize, Validate)]
+        struct Order {
+            #[validate(nested)]
+            items: Vec<Item>,
+        }
 
-impl From<validator::ValidationErrors> for Error {
-    fn from(value: validator::ValidationErrors) -> Self {
-        Self { errors: value }
-    }
-}
+        async fn order(_: Validated<Json<Order>>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.errors)
-    }
-}
+        for (syntax, path) in [
+            (PathSyntax::Dotted, "items[0].name"),
+            (PathSyntax::JsonPointer, "/items/0/name"),
+            (PathSyntax::Brackets, "items[0][name]"),
+        ] {
+            let app = test::init_service(
+                App::new()
+                    .app_data(syntax)
+                    .route("/", actix_web::web::post().to(order)),
+            )
+            .await;
 
-impl ResponseError for Error {
-    fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(StatusCode::BAD_REQUEST).body(format!(
-            "Validation errors in fields:\n{}",
-            flatten_errors(&self.errors)
-                .iter()
-                .map(|(_, field, err)| { format!("\t{}: {}", field, err) })
-                .collect::<Vec<_>>()
-                .join("\n")
-        ))
+            let req = test::TestRequest::post()
+                .uri("/")
+                .set_json(serde_json::json!({ "items": [{ "name": "abc" }] }))
+                .to_request();
+            let body = test::call_and_read_body(&app, req).await;
+            let body = std::str::from_utf8(&body).unwrap();
+            assert!(body.contains(&format!("\t{path}: ")), "{body}");
+        }
     }
-}
-
-/// Helper function for error extraction and formatting.
-/// Return Vec of tuples where first element is full field path (separated by dot)
-/// and second is error.
-#[inline]
-fn flatten_errors(errors: &ValidationErrors) -> Vec<(u16, String, &ValidationError)> {
-    _flatten_errors(errors, None, None)
-}
 
-#[inline]
-fn _flatten_errors(
-    errors: &ValidationErrors,
-    path: Option<String>,
-    indent: Option<u16>,
-) -> Vec<(u16, String, &ValidationError)> {
-    errors
-        .errors()
-        .iter()
-        .flat_map(|(field, err)| {
-            let indent = indent.unwrap_or(0);
-            let actual_path = path
-                .as_ref()
-                .map(|path| [path.as_str(), &field].join("."))
-                .unwrap_or_else(|| field.to_string());
-            match err {
-                ValidationErrorsKind::Field(field_errors) => field_errors
-                    .iter()
-                    .map(|error| (indent, actual_path.clone(), error))
-                    .collect::<Vec<_>>(),
-                ValidationErrorsKind::List(list_error) => list_error
-                    .iter()
-                    .flat_map(|(index, errors)| {
-                        let actual_path = format!("{}[{}]", actual_path.as_str(), index);
-                        _flatten_errors(errors, Some(actual_path), Some(indent + 1))
-                    })
-                    .collect::<Vec<_>>(),
-                ValidationErrorsKind::Struct(struct_errors) => {
-                    _flatten_errors(struct_errors, Some(actual_path), Some(indent + 1))
-                }
-            }
-        })
-        .collect::<Vec<_>>()
-}
+    #[cfg(feature = "spans")]
+    #[actix_web::test]
+    async fn should_report_source_spans() {
+        use crate::spans::SourceSpansExt;
 
-pub type ValidatorErrHandler =
-    Arc<dyn Fn(validator::ValidationErrors, &HttpRequest) -> actix_web::Error + Send + Sync>;
+        #[derive(Debug, Deserialize, Serialize, Validate)]
+        struct Item {
+            #[validate(length(min = 5))]
+            name: String,
+        }
 
-struct ValidatorErrorHandler {
-    handler: ValidatorErrHandler,
-}
+        #[derive(Debug, Deserialize, Serialize, Validate)]
+        struct Order {
+            #[validate(nested)]
+            items: Vec<Item>,
+        }
 
-/// Extension trait to provide a convenience method for adding custom error handler
-pub trait ValidatorErrorHandlerExt {
-    /// Add a custom error handler for validator validated requests
-    fn validator_error_handler(self, handler: ValidatorErrHandler) -> Self;
-}
+        async fn order(_: Validated<Json<Order>>) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
 
-impl<T> ValidatorErrorHandlerExt for App<T>
-where
-    T: ServiceFactory<ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>,
-{
-    fn validator_error_handler(self, handler: ValidatorErrHandler) -> Self {
-        self.app_data(ValidatorErrorHandler { handler })
-    }
-}
+        let app = test::init_service(
+            App::new()
+                .source_spans::<Order>()
+                .route("/", actix_web::web::post().to(order)),
+        )
+        .await;
 
-impl ValidatorErrorHandlerExt for &mut actix_web::web::ServiceConfig {
-    fn validator_error_handler(self, handler: ValidatorErrHandler) -> Self {
-        self.app_data(ValidatorErrorHandler { handler })
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_payload(
+                "{\n  \"items\": [\n    {\"name\": \"abcdef\"},\n    {\"name\": \"abc\"}\n  ]\n}",
+            )
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("\titems[1].name: "), "{body}");
+        assert!(body.contains("\"line\": Number(4)"), "{body}");
+        assert!(body.contains("\"column\": Number(14)"), "{body}");
+        assert!(body.contains("\"offset\": Number(52)"), "{body}");
+        assert!(body.ends_with(" (line 4, column 14)"), "{body}");
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use actix_web::web::Bytes;
-    use actix_web::{http::header::ContentType, post, test, web::Json, App, Responder};
-    use serde::{Deserialize, Serialize};
-    use validator::Validate;
+    #[actix_web::test]
+    async fn should_expose_errors_to_middleware() {
+        use actix_web::dev::Service;
+        use actix_web::http::header::{HeaderName, HeaderValue};
 
-    #[derive(Debug, Deserialize, Serialize, Validate)]
-    struct ExamplePayload {
-        #[validate(length(min = 5))]
-        name: String,
-    }
+        let app = test::init_service(
+            App::new()
+                .wrap_fn(|req, srv| {
+                    let fut = srv.call(req);
+                    async move {
+                        let mut res = fut.await?;
+                        let error = res.response().error();
+                        let status = error.map(|e| e.as_response_error().status_code());
+                        let fields = error
+                            .and_then(|e| e.as_error::<Error>())
+                            .map(|e| e.errors().errors().len());
+                        if let (Some(status), Some(fields)) = (status, fields) {
+                            res.headers_mut().insert(
+                                HeaderName::from_static("x-error-status"),
+                                HeaderValue::from(status.as_u16()),
+                            );
+                            res.headers_mut().insert(
+                                HeaderName::from_static("x-invalid-fields"),
+                                HeaderValue::from(fields),
+                            );
+                        }
+                        Ok(res)
+                    }
+                })
+                .service(endpoint),
+        )
+        .await;
 
-    #[post("/")]
-    async fn endpoint(v: Validated<Json<ExamplePayload>>) -> impl Responder {
-        assert!(v.name.len() > 4);
-        HttpResponse::Ok().body(())
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "1234".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(resp.headers().get("x-error-status").unwrap(), "400");
+        assert_eq!(resp.headers().get("x-invalid-fields").unwrap(), "1");
     }
 
     #[actix_web::test]
-    async fn should_validate_simple() {
-        let app = test::init_service(App::new().service(endpoint)).await;
+    async fn should_hand_rejected_values_to_the_handler() {
+        async fn signup(result: ValidationResult<Json<ExamplePayload>>) -> HttpResponse {
+            match result.into_result() {
+                Ok(_) => HttpResponse::Ok().body("welcome"),
+                Err(rejected) => {
+                    let fields = rejected.errors().errors().len();
+                    HttpResponse::Ok().body(format!("{} ({fields})", rejected.value().name))
+                }
+            }
+        }
+
+        let app =
+            test::init_service(App::new().route("/", actix_web::web::post().to(signup))).await;
 
-        // Valid request
         let req = test::TestRequest::post()
             .uri("/")
-            .insert_header(ContentType::plaintext())
             .set_json(ExamplePayload {
-                name: "123456".to_string(),
+                name: "1234".to_string(),
             })
             .to_request();
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status().as_u16(), 200);
+        assert_eq!(test::read_body(resp).await, Bytes::from_static(b"1234 (1)"));
 
-        // Invalid request
         let req = test::TestRequest::post()
             .uri("/")
-            .insert_header(ContentType::plaintext())
             .set_json(ExamplePayload {
-                name: "1234".to_string(),
+                name: "123456".to_string(),
             })
             .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, Bytes::from_static(b"welcome"));
+
+        // Extraction errors are still responded with
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .set_payload("{")
+            .to_request();
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status().as_u16(), 400);
     }
 
-    // TODO: This test is unstable because the error or appears to be non-dermimistic
-    #[ignore]
     #[actix_web::test]
-    async fn should_respond_with_errors_correctly() {
-        let app = test::init_service(App::new().service(endpoint)).await;
+    async fn should_pass_rejected_values_to_value_error_handlers() {
+        fn value_handler(
+            value: &ExamplePayload,
+            errors: ValidationErrors,
+            _: &HttpRequest,
+        ) -> actix_web::Error {
+            let fields = errors.errors().len();
+            actix_web::error::ErrorBadRequest(format!("{} ({fields})", value.name))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .validator_error_handler(Arc::new(error_handler))
+                .validator_value_error_handler(Arc::new(value_handler))
+                .service(endpoint),
+        )
+        .await;
 
-        // Invalid request
         let req = test::TestRequest::post()
             .uri("/")
-            .insert_header(ContentType::plaintext())
             .set_json(ExamplePayload {
                 name: "1234".to_string(),
             })
             .to_request();
-        let result = test::call_and_read_body(&app, req).await;
-        assert_eq!(
-            result,
-            Bytes::from_static(b"Validation errors in fields:\n\tname: Validation error: length [{\"min\": Number(5), \"value\": String(\"1234\")}]")
-        );
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(test::read_body(resp).await, Bytes::from_static(b"1234 (1)"));
     }
 
-    #[derive(Debug, Serialize, Error)]
-    struct CustomErrorResponse {
-        custom_message: String,
-        errors: Vec<String>,
-    }
+    #[cfg(feature = "ws")]
+    #[actix_web::test]
+    async fn should_pass_rejected_messages_to_value_error_handlers() {
+        use actix_ws::Message;
 
-    impl Display for CustomErrorResponse {
-        fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            unimplemented!()
+        fn value_handler(
+            value: &ExamplePayload,
+            errors: ValidationErrors,
+            _: &HttpRequest,
+        ) -> actix_web::Error {
+            actix_web::error::ErrorBadRequest(format!("{} ({})", value.name, errors.errors().len()))
         }
-    }
 
-    impl ResponseError for CustomErrorResponse {
-        fn status_code(&self) -> actix_web::http::StatusCode {
-            actix_web::http::StatusCode::BAD_REQUEST
-        }
+        let req = test::TestRequest::default()
+            .app_data(ValidatorErrorHandler {
+                handler: Arc::new(error_handler),
+            })
+            .app_data(ValidatorValueErrorHandler::<ExamplePayload> {
+                handler: Arc::new(value_handler),
+            })
+            .to_http_request();
 
-        fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
-            HttpResponse::build(self.status_code()).body(serde_json::to_string(self).unwrap())
-        }
+        let msg = Message::Text(r#"{"name":"1234"}"#.into());
+        let frame = validate_message::<ExamplePayload>(&req, &msg)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(frame.status().as_u16(), 400);
+        assert_eq!(frame.text(), "1234 (1)");
     }
 
-    fn error_handler(errors: ::validator::ValidationErrors, _: &HttpRequest) -> actix_web::Error {
-        CustomErrorResponse {
-            custom_message: "My custom message".to_string(),
-            errors: errors
-                .errors()
-                .iter()
-                .map(|(err, _)| err.to_string())
-                .collect(),
+    #[cfg(feature = "patch")]
+    #[actix_web::test]
+    async fn should_pass_rejected_patches_to_value_error_handlers() {
+        use crate::patch::PatchLoaderExt;
+        use actix_web::web;
+
+        fn value_handler(
+            value: &ExamplePayload,
+            errors: ValidationErrors,
+            _: &HttpRequest,
+        ) -> actix_web::Error {
+            actix_web::error::ErrorBadRequest(format!("{} ({})", value.name, errors.errors().len()))
         }
-        .into()
-    }
 
-    #[actix_web::test]
-    async fn should_use_allow_custom_error_responses() {
         let app = test::init_service(
             App::new()
-                .service(endpoint)
-                .validator_error_handler(Arc::new(error_handler)),
+                .patch_loader::<ExamplePayload, _, _>(|_| async {
+                    Ok(ExamplePayload {
+                        name: "123456".to_string(),
+                    })
+                })
+                .validator_error_handler(Arc::new(error_handler))
+                .validator_value_error_handler(Arc::new(value_handler))
+                .route("/", web::patch().to(patch_endpoint)),
         )
         .await;
 
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .set_json(serde_json::json!({ "name": "1234" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+        assert_eq!(test::read_body(resp).await, Bytes::from_static(b"1234 (1)"));
+    }
+
+    #[actix_web::test]
+    async fn should_reject_invalid_optional_input() {
+        async fn update(payload: OptionalValidated<Json<ExamplePayload>>) -> HttpResponse {
+            match payload.into_inner() {
+                Some(payload) => HttpResponse::Ok().body(payload.name.clone()),
+                None => HttpResponse::Ok().body("absent"),
+            }
+        }
+
+        let app =
+            test::init_service(App::new().route("/", actix_web::web::post().to(update))).await;
+
+        let req = test::TestRequest::post().uri("/").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, Bytes::from_static(b"absent"));
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(ExamplePayload {
+                name: "123456".to_string(),
+            })
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, Bytes::from_static(b"123456"));
+
         let req = test::TestRequest::post()
             .uri("/")
-            .insert_header(ContentType::plaintext())
             .set_json(ExamplePayload {
                 name: "1234".to_string(),
             })
             .to_request();
-        let result = test::call_and_read_body(&app, req).await;
-        assert_eq!(
-            result,
-            Bytes::from_static(b"{\"custom_message\":\"My custom message\",\"errors\":[\"name\"]}")
-        );
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        // Streamed bodies (chunked or HTTP/2) have no Content-Length
+        let mut req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .to_request();
+        *req.payload() = Bytes::from_static(br#"{"name":"1234"}"#).into();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        let mut req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(ContentType::json())
+            .to_request();
+        *req.payload() = Bytes::from_static(br#"{"name":"123456"}"#).into();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, Bytes::from_static(b"123456"));
     }
 
-    #[test]
-    async fn debug_for_validated_should_work() {
-        let v = Validated(ExamplePayload {
-            name: "abcde".to_string(),
-        });
+    #[derive(Debug, Deserialize, Serialize)]
+    struct SlowPayload {
+        millis: u64,
+    }
 
-        assert_eq!(
-            "Validated(ExamplePayload { name: \"abcde\" })",
-            format!("{v:?}")
-        );
+    impl Validate for SlowPayload {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            std::thread::sleep(std::time::Duration::from_millis(self.millis));
+            Ok(())
+        }
+    }
+
+    #[actix_web::test]
+    async fn should_time_out_slow_validation() {
+        use crate::timeout::{ValidationTimeout, ValidationTimeoutExt};
+        use std::time::Duration;
+
+        // Registering a timeout is enough, validation is offloaded so it can be interrupted
+        let app = test::init_service(
+            App::new()
+                .route(
+                    "/slow",
+                    actix_web::web::post().to(|_: Validated<Json<SlowPayload>>| async {
+                        HttpResponse::Ok().finish()
+                    }),
+                )
+                .validation_timeout::<Validated<Json<SlowPayload>>>(ValidationTimeout::new(
+                    Duration::from_millis(50),
+                )),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/slow")
+            .set_json(SlowPayload { millis: 0 })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        let req = test::TestRequest::post()
+            .uri("/slow")
+            .set_json(SlowPayload { millis: 500 })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 503);
     }
 }
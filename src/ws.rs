@@ -0,0 +1,135 @@
+//! Validation of JSON messages received over [actix-ws](https://docs.rs/actix-ws/latest/actix_ws)
+//! WebSockets. Requires the `ws` feature flag
+//!
+//! `Validated` only applies to HTTP requests. Each backend provides a `validate_message`
+//! function that deserializes a text or binary frame and validates it like `Validated` would:
+//! the registered sanitizer, [`ErrorLimit`](crate::limit::ErrorLimit) and error handler of the
+//! WebSocket upgrade request are applied, and rejected messages produce an [`ErrorFrame`] with
+//! the body of the HTTP error response.
+//!
+//! ```ignore
+//! use actix_web_validation::validator::validate_message;
+//!
+//! async fn ws(req: HttpRequest, body: web::Payload) -> actix_web::Result<impl Responder> {
+//!     let (response, mut session, stream) = actix_ws::handle(&req, body)?;
+//!     let mut stream = stream.aggregate_continuations();
+//!
+//!     actix_web::rt::spawn(async move {
+//!         while let Some(Ok(msg)) = stream.next().await {
+//!             match validate_message::<ChatMessage>(&req, &msg) {
+//!                 Some(Ok(chat)) => { /* handle the message */ }
+//!                 Some(Err(error)) => error.send(&mut session).await.unwrap(),
+//!                 None => { /* ping, pong and close frames */ }
+//!             }
+//!         }
+//!     });
+//!
+//!     Ok(response)
+//! }
+//! ```
+//!
+//! Messages are not validated by the `jsonschema` backend.
+
+use std::borrow::Cow;
+
+use actix_web::body::MessageBody;
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_ws::{AggregatedMessage, Message};
+
+/// A WebSocket frame that may carry a JSON message
+pub trait DataFrame {
+    /// The payload of text and binary frames, `None` for other frames
+    fn data(&self) -> Option<&[u8]>;
+}
+
+impl DataFrame for Message {
+    fn data(&self) -> Option<&[u8]> {
+        match self {
+            Message::Text(text) => Some(text.as_bytes()),
+            Message::Binary(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+impl DataFrame for AggregatedMessage {
+    fn data(&self) -> Option<&[u8]> {
+        match self {
+            AggregatedMessage::Text(text) => Some(text.as_bytes()),
+            AggregatedMessage::Binary(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+/// The error frame produced for a rejected message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorFrame {
+    status: StatusCode,
+    body: Bytes,
+}
+
+impl ErrorFrame {
+    /// The status of the equivalent HTTP error response
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The body of the equivalent HTTP error response
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    /// Sends the error as a text frame
+    pub async fn send(self, session: &mut actix_ws::Session) -> Result<(), actix_ws::Closed> {
+        session.text(self.text().into_owned()).await
+    }
+}
+
+impl From<actix_web::Error> for ErrorFrame {
+    fn from(error: actix_web::Error) -> Self {
+        let response = error.error_response();
+        let status = response.status();
+        let body = response
+            .into_body()
+            .try_into_bytes()
+            .unwrap_or_else(|_| Bytes::from(error.to_string()));
+        Self { status, body }
+    }
+}
+
+/// Deserializes a JSON message, rejecting it like the [`Json`](actix_web::web::Json) extractor
+#[cfg(any(feature = "validator", feature = "garde", feature = "custom"))]
+pub(crate) fn deserialize<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, ErrorFrame> {
+    serde_json::from_slice(data).map_err(|e| {
+        actix_web::Error::from(actix_web::error::JsonPayloadError::Deserialize(e)).into()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_only_read_data_frames() {
+        assert_eq!(Message::Text("{}".into()).data(), Some(&b"{}"[..]));
+        assert_eq!(
+            AggregatedMessage::Binary(Bytes::from_static(b"[]")).data(),
+            Some(&b"[]"[..])
+        );
+        assert_eq!(Message::Ping(Bytes::new()).data(), None);
+        assert_eq!(AggregatedMessage::Close(None).data(), None);
+    }
+
+    #[test]
+    fn should_build_frames_from_error_responses() {
+        let frame = ErrorFrame::from(actix_web::error::ErrorBadRequest("invalid message"));
+        assert_eq!(frame.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(frame.text(), "invalid message");
+    }
+}